@@ -0,0 +1,168 @@
+//a Imports
+use crate::{CatalogIndex, Error};
+
+//a AsterismPattern
+//tp AsterismPattern
+/// A pattern of N stars to search for in a catalog (with
+/// [crate::Catalog::find_asterisms]), given by the angles between
+/// some of the pairs of stars
+///
+/// The angles between other pairs of stars are unknown, and may be
+/// anything; but every star must have a known angle to at least one
+/// other star, and all of the stars must be connected by the known
+/// angles (otherwise the search could not place them).
+#[derive(Debug, Clone, Default)]
+pub struct AsterismPattern {
+    /// The number of stars in the pattern
+    num_stars: usize,
+    /// The known angles, in radians, between pairs of stars (a, b)
+    /// with a < b
+    angles: Vec<(usize, usize, f64)>,
+}
+
+//ip AsterismPattern
+impl AsterismPattern {
+    //cp new
+    /// Create a new [AsterismPattern] of a number of stars, with no
+    /// known angles between them
+    pub fn new(num_stars: usize) -> Self {
+        Self {
+            num_stars,
+            angles: vec![],
+        }
+    }
+
+    //cp with_angle
+    /// Add (or replace) the angle, in radians, between two stars of
+    /// the pattern, returning the pattern
+    pub fn with_angle(mut self, a: usize, b: usize, angle: f64) -> Self {
+        self.set_angle(a, b, angle);
+        self
+    }
+
+    //mp set_angle
+    /// Set the angle, in radians, between two stars of the pattern
+    ///
+    /// Panics if either star is not in the pattern, or if they are
+    /// the same star
+    pub fn set_angle(&mut self, a: usize, b: usize, angle: f64) {
+        assert!(
+            a < self.num_stars && b < self.num_stars,
+            "Star of an asterism pattern out of range"
+        );
+        assert_ne!(a, b, "Angle between a star of a pattern and itself");
+        let (a, b) = (a.min(b), a.max(b));
+        self.angles.retain(|(x, y, _)| (*x, *y) != (a, b));
+        self.angles.push((a, b, angle));
+    }
+
+    //ap num_stars
+    /// Get the number of stars in the pattern
+    pub fn num_stars(&self) -> usize {
+        self.num_stars
+    }
+
+    //ap angle
+    /// Get the angle, in radians, between two stars of the pattern, if
+    /// it is known
+    pub fn angle(&self, a: usize, b: usize) -> Option<f64> {
+        let (a, b) = (a.min(b), a.max(b));
+        self.angles
+            .iter()
+            .find(|(x, y, _)| (*x, *y) == (a, b))
+            .map(|(_, _, angle)| *angle)
+    }
+
+    //ap iter_angles
+    /// Iterate over the known angles of the pattern, as (a, b, angle)
+    pub fn iter_angles(&self) -> impl Iterator<Item = (usize, usize, f64)> + '_ {
+        self.angles.iter().copied()
+    }
+
+    //mp search_order
+    /// Determine the order in which to place the stars of the pattern
+    /// when searching, starting with star 0
+    ///
+    /// At each step the star with the most known angles to the stars
+    /// already placed is chosen (as it will prune the search the
+    /// most); its candidates are found around the placed star with
+    /// the smallest angle to it, and checked against the other known
+    /// angles.
+    pub(crate) fn search_order(&self) -> Result<Vec<PatternStep>, Error> {
+        let mut placed = vec![false; self.num_stars];
+        let mut steps = vec![];
+        if self.num_stars == 0 {
+            return Ok(steps);
+        }
+        placed[0] = true;
+        for _ in 1..self.num_stars {
+            let mut best: Option<(usize, usize)> = None;
+            for s in (0..self.num_stars).filter(|s| !placed[*s]) {
+                let n = self
+                    .angles
+                    .iter()
+                    .filter(|(a, b, _)| (*a == s && placed[*b]) || (*b == s && placed[*a]))
+                    .count();
+                if n > 0 && best.is_none_or(|(_, best_n)| n > best_n) {
+                    best = Some((s, n));
+                }
+            }
+            let Some((star, _)) = best else {
+                let s = placed.iter().position(|p| !p).unwrap_or(0);
+                return Err(Error::UnconstrainedPatternStar(s));
+            };
+            let mut checks: Vec<(usize, f64)> = self
+                .angles
+                .iter()
+                .filter_map(|(a, b, angle)| {
+                    if *a == star && placed[*b] {
+                        Some((*b, *angle))
+                    } else if *b == star && placed[*a] {
+                        Some((*a, *angle))
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+            checks.sort_by(|a, b| a.1.total_cmp(&b.1));
+            let (anchor, angle) = checks.remove(0);
+            steps.push(PatternStep {
+                star,
+                anchor,
+                angle,
+                checks,
+            });
+            placed[star] = true;
+        }
+        Ok(steps)
+    }
+}
+
+//tp PatternStep
+/// A step in the search for an [AsterismPattern]: the placing of one
+/// star given the stars already placed
+#[derive(Debug, Clone)]
+pub(crate) struct PatternStep {
+    /// The star of the pattern to place
+    pub star: usize,
+    /// The star already placed around which to look for candidates
+    pub anchor: usize,
+    /// The angle between the anchor and the star
+    pub angle: f64,
+    /// The other stars already placed with known angles to the star
+    pub checks: Vec<(usize, f64)>,
+}
+
+//a AsterismMatch
+//tp AsterismMatch
+/// An assignment of catalog stars to the stars of an
+/// [AsterismPattern], found by [crate::Catalog::find_asterisms_with]
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsterismMatch {
+    /// The catalog star for each star of the pattern
+    pub stars: Vec<CatalogIndex>,
+    /// The root-mean-square of the differences between the actual
+    /// angles and the known angles of the pattern; smaller is a
+    /// better match
+    pub score: f64,
+}
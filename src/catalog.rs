@@ -1,9 +1,13 @@
 use std::collections::HashMap;
+use std::ops::Range;
 
 use geo_nd::Vector;
 use serde::{Deserialize, Serialize};
 
-use crate::{Error, Star, StarFilter, StarFilterFn, Subcube, Vec3};
+use crate::asterism::{AsterismMatch, AsterismPattern, PatternStep};
+use crate::star_vectors::{array_of_vec3, StarVectors};
+use crate::triangle::{self, TriangleMatch, TriangleUniqueness};
+use crate::{Error, Star, StarFilter, StarFilterFn, Subcube, SubcubeIndex, Vec3};
 
 // The sky above a location with latitude L and longitude M (east)
 //
@@ -15,7 +19,7 @@ use crate::{Error, Star, StarFilter, StarFilterFn, Subcube, Vec3};
 /// An index into the Catalog to identify a particular star
 ///
 /// A [CatalogIndex] becomes invalid if the Catalog is sorted again
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
 pub struct CatalogIndex(usize);
 impl CatalogIndex {
     pub fn as_usize(self) -> usize {
@@ -33,7 +37,7 @@ impl From<usize> for CatalogIndex {
 /// The catalog contains an indexed (and possibly named) list of
 /// stars, which can be searched by id, name, or geometrically
 ///
-/// # Design rationale for `Vec<Subcube>`
+/// # Design rationale for the subcube storage
 ///
 /// There are roughly 4,000 (3769 in fact) subcubes used out of 32^3 (i.e. 32,768)
 /// by a star catalog with an ELE_PER_SIDE of 32
@@ -42,6 +46,25 @@ impl From<usize> for CatalogIndex {
 ///
 /// A hash map from subcube to vec with 4k entries used is probably
 /// 64kB plus 128kB for the vecs themselves; access is slower though.
+///
+/// Instead the catalog uses a compressed (CSR-style) layout: a
+/// single array of star indices grouped by subcube, a sorted list of
+/// the occupied subcubes, and for each occupied subcube the offset of
+/// its stars in the index array. That is 32kB for the subcubes plus
+/// the star indices themselves, all contiguous; looking up a subcube
+/// is a binary search of the occupied list.
+///
+/// Searching a range of subcubes walks the occupied list one row (of
+/// consecutive X coordinates) at a time, and so only visits subcubes
+/// that contain stars - which are all on the surface of the sphere.
+///
+/// # Subcube resolution
+///
+/// The number of subcubes per side is chosen when the data is derived;
+/// by default this is picked from the number of stars in the catalog
+/// (see [SubcubeIndex::for_num_stars]), but it may be set explicitly
+/// with [Catalog::set_subcube_ele_per_side]. The [Subcube] of every star
+/// is updated to match when the data is derived.
 #[derive(Default, Serialize, Deserialize)]
 pub struct Catalog {
     /// Stars in the catalog
@@ -55,9 +78,26 @@ pub struct Catalog {
     /// Filter to apply to finding stars
     #[serde(skip)]
     filter: StarFilter,
-    /// Star indices within each subcube
+    /// Number of subcubes per side to use, if not chosen automatically
+    #[serde(skip)]
+    ele_per_side: Option<usize>,
+    /// The subcube division used for the derived data
+    #[serde(skip)]
+    subcube_index: SubcubeIndex,
+    /// The occupied subcubes (as usize values), in ascending order
+    #[serde(skip)]
+    subcube_ids: Vec<u32>,
+    /// Offset into subcube_stars of the stars of each occupied
+    /// subcube, plus a final entry of the total number of stars
+    #[serde(skip)]
+    subcube_starts: Vec<u32>,
+    /// Star indices grouped by subcube, each group sorted by magnitude
+    #[serde(skip)]
+    subcube_stars: Vec<CatalogIndex>,
+    /// Vectors and magnitudes of the stars, in the same order as
+    /// subcube_stars
     #[serde(skip)]
-    subcubes: Vec<Vec<CatalogIndex>>,
+    star_vectors: StarVectors,
 }
 
 impl Catalog {
@@ -65,20 +105,187 @@ impl Catalog {
     /// Retain stars that match a certain criterion; the rest are
     /// dropped
     ///
-    /// This should be invoked prior to any stars being named; it also
-    /// clears the derived data (e.g. geometric searching will not be
-    /// allowed until a derive_data() call is invoked)
+    /// Names of the retained stars are kept, and those of dropped
+    /// stars are removed; this also clears the derived data (e.g.
+    /// geometric searching will not be allowed until a derive_data()
+    /// call is invoked)
     pub fn retain<F>(&mut self, f: F)
     where
         F: StarFilterFn,
     {
+        let keep: Vec<bool> = self
+            .stars
+            .iter()
+            .enumerate()
+            .map(|(i, s)| f(s, i + 1))
+            .collect();
+        self.retain_kept(&keep);
+    }
+
+    //mp retain_brightest_per_subcube
+    /// Retain only the (up to) *max_per_cell* brightest stars in each
+    /// subcube; the rest are dropped
+    ///
+    /// The subcubes are those of the derived data, so their size may
+    /// be chosen with [Catalog::set_subcube_ele_per_side]; every
+    /// subcube that has stars keeps at least one.
+    ///
+    /// As with [Catalog::retain] the names of dropped stars are
+    /// removed, and this clears the derived data
+    ///
+    /// Needs data to have been derived for the Catalog
+    pub fn retain_brightest_per_subcube(&mut self, max_per_cell: usize) {
+        let mut keep = vec![true; self.stars.len()];
+        self.keep_brightest_per_subcube(&mut keep, max_per_cell);
+        self.retain_kept(&keep);
+    }
+
+    //mp thin_uniform
+    /// Thin the catalog to a roughly uniform density of stars, as
+    /// required for (for example) the onboard catalog of a star
+    /// tracker
+    ///
+    /// First, close doubles that cannot be resolved are dropped: any
+    /// star within *min_separation* (in radians) of a brighter star
+    /// that is kept is dropped. Then only the (up to) *max_per_cell*
+    /// brightest of the remaining stars in each subcube are retained.
+    ///
+    /// As only the brightest stars of dense regions are dropped there
+    /// are no holes in the thinned catalog, beyond those of close
+    /// doubles: a subcube keeps at least one of its stars unless all
+    /// of them are within *min_separation* of a brighter star that is
+    /// kept in a neighboring subcube. With a *min_separation* of zero
+    /// every subcube that had stars keeps at least one.
+    ///
+    /// As with [Catalog::retain] the names of dropped stars are
+    /// removed, and this clears the derived data
+    ///
+    /// Needs data to have been derived for the Catalog
+    pub fn thin_uniform(&mut self, max_per_cell: usize, min_separation: f64) {
+        let mut order: Vec<usize> = (0..self.stars.len()).collect();
+        order.sort_by(|a, b| {
+            self.stars[*a]
+                .mag
+                .total_cmp(&self.stars[*b].mag)
+                .then(a.cmp(b))
+        });
+        let mut keep = vec![false; self.stars.len()];
+        let min_cos = min_separation.cos();
+        for i in order {
+            let v = self.stars[i].vector;
+            let mut resolved = true;
+            let subcubes = if min_separation > 0. {
+                self.subcubes_around(&v, min_separation)
+            } else {
+                vec![]
+            };
+            for sub in subcubes {
+                self.star_vectors.for_each_within(
+                    self.subcube_range(sub),
+                    &array_of_vec3(&v),
+                    min_cos,
+                    2.0,
+                    |n, _| {
+                        if keep[self.subcube_stars[n].as_usize()] {
+                            resolved = false;
+                        }
+                    },
+                );
+            }
+            keep[i] = resolved;
+        }
+        self.keep_brightest_per_subcube(&mut keep, max_per_cell);
+        self.retain_kept(&keep);
+    }
+
+    //mp merge_close_pairs
+    /// Merge the stars of each pair that are within an angle (in
+    /// radians) of each other into a single star, as they would be
+    /// seen by a sensor that cannot resolve them; the number of pairs
+    /// merged is returned
+    ///
+    /// The closest pairs (from [Catalog::close_pairs]) are merged
+    /// first, and each star is merged at most once. The fainter star
+    /// is dropped, and the brighter is moved to the flux-weighted
+    /// position of the two with their combined magnitude (keeping its
+    /// id, distance and color).
+    ///
+    /// As with [Catalog::retain] the names of dropped stars are
+    /// removed (the merged star keeps its names), and this clears the
+    /// derived data
+    ///
+    /// Needs data to have been derived for the Catalog
+    pub fn merge_close_pairs(&mut self, max_angle: f64) -> usize {
+        let pairs = self.close_pairs(max_angle);
+        let mut keep = vec![true; self.stars.len()];
+        let mut merged = vec![false; self.stars.len()];
+        let mut count = 0;
+        for p in pairs {
+            let (a, b) = (p.stars[0].as_usize(), p.stars[1].as_usize());
+            if merged[a] || merged[b] {
+                continue;
+            }
+            let (s0, s1) = (&self.stars[a], &self.stars[b]);
+            let f0 = 10.0_f64.powf(-0.4 * s0.mag as f64);
+            let f1 = 10.0_f64.powf(-0.4 * s1.mag as f64);
+            let v = (s0.vector * f0 + s1.vector * f1).normalize();
+            let ra = v[1].atan2(v[0]).rem_euclid(std::f64::consts::TAU);
+            let de = v[2].clamp(-1., 1.).asin();
+            self.stars[a] = Star::new(s0.id, ra, de, s0.ly, p.combined_magnitude, s0.bv);
+            keep[b] = false;
+            merged[a] = true;
+            merged[b] = true;
+            count += 1;
+        }
+        self.retain_kept(&keep);
+        count
+    }
+
+    //mi keep_brightest_per_subcube
+    /// Clear the keep flag of all but the (up to) *max_per_cell*
+    /// brightest of the stars to keep in each subcube
+    fn keep_brightest_per_subcube(&self, keep: &mut [bool], max_per_cell: usize) {
+        assert!(
+            self.has_derived_data(),
+            "Catalog must have derived data to keep the brightest stars per subcube"
+        );
+        for sub in self.iter_subcubes() {
+            let mut count = 0;
+            for index in self[sub].iter() {
+                let n = index.as_usize();
+                if !keep[n] {
+                    continue;
+                }
+                if count >= max_per_cell {
+                    keep[n] = false;
+                } else {
+                    count += 1;
+                }
+            }
+        }
+    }
+
+    //mi retain_kept
+    /// Retain the stars whose keep flag is set; the rest are dropped,
+    /// along with their names
+    fn retain_kept(&mut self, keep: &[bool]) {
         self.sorted = false;
         self.clear_derived_data();
+        let keep = |i: usize| keep.get(i).copied().unwrap_or(false);
+        let mut n = 0;
+        let new_index: Vec<Option<usize>> = (0..self.stars.len())
+            .map(|i| {
+                let index = keep(i).then_some(n);
+                n += keep(i) as usize;
+                index
+            })
+            .collect();
         let mut i = 0;
-        self.stars.retain(move |s| {
+        self.stars.retain(|_| {
             i += 1;
-            f(s, i)
+            keep(i - 1)
         });
+        self.remap_names(&new_index);
     }
 
     //mp clear_filter
@@ -125,7 +332,7 @@ impl Catalog {
     //mi has_derived_data
     /// return true iif the data has been derived
     fn has_derived_data(&self) -> bool {
-        !self.subcubes.is_empty()
+        !self.subcube_starts.is_empty()
     }
 
     //mi clear_derived_data
@@ -133,10 +340,82 @@ impl Catalog {
     /// example)
     fn clear_derived_data(&mut self) {
         if self.has_derived_data() {
-            self.subcubes.clear();
+            self.subcube_ids.clear();
+            self.subcube_starts.clear();
+            self.subcube_stars.clear();
+            self.star_vectors.clear();
         }
     }
 
+    //mp set_subcube_ele_per_side
+    /// Set the number of subcubes per side of the (-1, 1) cube to use
+    /// for geometric searching, or None to choose it automatically
+    /// from the number of stars in the catalog
+    ///
+    /// This clears any derived data
+    pub fn set_subcube_ele_per_side(&mut self, ele_per_side: Option<usize>) {
+        self.clear_derived_data();
+        self.ele_per_side = ele_per_side;
+    }
+
+    //ap subcube_index
+    /// Get the [SubcubeIndex] used for geometric searching
+    ///
+    /// This is only valid once the data has been derived
+    pub fn subcube_index(&self) -> SubcubeIndex {
+        self.subcube_index
+    }
+
+    //mp subcube_of_vector
+    /// Get the subcube of the catalog that contains a unit vector,
+    /// for indexing the catalog by [Subcube]
+    ///
+    /// This is only valid once the data has been derived
+    pub fn subcube_of_vector(&self, v: &Vec3) -> Subcube {
+        self.subcube_index.of_vector(v)
+    }
+
+    //mp iter_subcubes
+    /// Get an iterator over all the subcubes of the catalog that
+    /// contain stars (for use in [Catalog::find_star_triangles], for
+    /// example)
+    ///
+    /// This is only valid once the data has been derived
+    pub fn iter_subcubes(&self) -> SubcubeIter<'_> {
+        SubcubeIter {
+            subcube_index: self.subcube_index,
+            ids: self.subcube_ids.iter(),
+        }
+    }
+
+    //mi occupied_subcubes_in_range
+    /// Get the subcubes that contain stars and that have X, Y or Z
+    /// coordinates within dxyz of a subcube
+    ///
+    /// This walks the occupied subcubes a row at a time, so it only
+    /// visits subcubes on the surface of the sphere
+    fn occupied_subcubes_in_range(&self, subcube: Subcube, dxyz: usize) -> Vec<Subcube> {
+        let e = self.subcube_index.ele_per_side();
+        let (x, y, z) = subcube.into();
+        let xmin = x.saturating_sub(dxyz);
+        let xmax = (x + dxyz).min(e - 1);
+        let mut result = vec![];
+        for z in z.saturating_sub(dxyz)..=(z + dxyz).min(e - 1) {
+            for y in y.saturating_sub(dxyz)..=(y + dxyz).min(e - 1) {
+                let first = self.subcube_index.of_xyz((xmin, y, z)).as_usize() as u32;
+                let last = self.subcube_index.of_xyz((xmax, y, z)).as_usize() as u32;
+                let start = self.subcube_ids.partition_point(|id| *id < first);
+                for id in self.subcube_ids[start..].iter() {
+                    if *id > last {
+                        break;
+                    }
+                    result.push(self.subcube_index.subcube_of_usize(*id as usize));
+                }
+            }
+        }
+        result
+    }
+
     //mp derive_data
     /// Derive data from the stars in the catalog - such as what stars
     /// are in which subcubes
@@ -163,16 +442,60 @@ impl Catalog {
 
     //mi allocate_subcubes
     /// Allocate the subcubes and put the stars in appropriately
+    ///
+    /// The stars within each subcube are sorted by magnitude,
+    /// brightest first, so that searches for the brightest stars in
+    /// a region can stop scanning a subcube early
     fn allocate_subcubes(&mut self) {
         if self.has_derived_data() {
             return;
         }
-        self.subcubes.clear();
-        for _ in 0..Subcube::NUM_SUBCUBES {
-            self.subcubes.push(vec![]);
+        self.subcube_index = match self.ele_per_side {
+            Some(ele_per_side) => SubcubeIndex::new(ele_per_side),
+            None => SubcubeIndex::for_num_stars(self.stars.len()),
+        };
+        for s in self.stars.iter_mut() {
+            s.subcube = self.subcube_index.of_vector(&s.vector);
+        }
+        let stars = &self.stars;
+        self.subcube_stars = (0..stars.len()).map(CatalogIndex).collect();
+        self.subcube_stars.sort_by(|a, b| {
+            let (sa, sb) = (&stars[a.0], &stars[b.0]);
+            sa.subcube
+                .as_usize()
+                .cmp(&sb.subcube.as_usize())
+                .then(sa.mag.total_cmp(&sb.mag))
+        });
+        self.subcube_ids.clear();
+        self.subcube_starts.clear();
+        for (n, index) in self.subcube_stars.iter().enumerate() {
+            let id = stars[index.0].subcube.as_usize() as u32;
+            if self.subcube_ids.last() != Some(&id) {
+                self.subcube_ids.push(id);
+                self.subcube_starts.push(n as u32);
+            }
+        }
+        self.subcube_starts.push(self.subcube_stars.len() as u32);
+        self.star_vectors.clear();
+        for index in self.subcube_stars.iter() {
+            self.star_vectors.push(&stars[index.0]);
         }
-        for (i, s) in self.stars.iter().enumerate() {
-            self.subcubes[s.subcube.as_usize()].push(CatalogIndex(i));
+    }
+
+    //mi subcube_range
+    /// Get the range of positions in subcube_stars (and star_vectors)
+    /// of the stars in a subcube
+    ///
+    /// Panics if the subcube is not of the catalog's subcube index
+    fn subcube_range(&self, q: Subcube) -> Range<usize> {
+        assert_eq!(
+            q.ele_per_side(),
+            self.subcube_index.ele_per_side(),
+            "Subcube is not from the catalog's subcubes"
+        );
+        match self.subcube_ids.binary_search(&(q.as_usize() as u32)) {
+            Ok(n) => (self.subcube_starts[n] as usize)..(self.subcube_starts[n + 1] as usize),
+            Err(_) => 0..0,
         }
     }
 
@@ -180,11 +503,39 @@ impl Catalog {
     /// Sort the stars so that to create the index (and hence
     /// afterwards they can be searched by id)
     ///
-    /// To do: Must remap name identifiers too
+    /// Any names are remapped to the stars' new positions
     pub fn sort(&mut self) {
-        self.stars.sort_by_key(|a| a.id);
         self.clear_derived_data();
         self.sorted = true;
+        if self.named_stars.is_empty() {
+            self.stars.sort_by_key(|a| a.id);
+            return;
+        }
+        let mut order: Vec<usize> = (0..self.stars.len()).collect();
+        order.sort_by_key(|n| self.stars[*n].id);
+        let mut new_index = vec![None; order.len()];
+        for (n, old) in order.iter().enumerate() {
+            new_index[*old] = Some(n);
+        }
+        let mut stars: Vec<Option<Star>> = self.stars.drain(..).map(Some).collect();
+        self.stars = order.iter().map(|n| stars[*n].take().unwrap()).collect();
+        self.remap_names(&new_index);
+    }
+
+    //mi remap_names
+    /// Update the names of stars after the stars have been reordered
+    /// or dropped, given the new index of each star (or None if it
+    /// was dropped)
+    fn remap_names(&mut self, new_index: &[Option<usize>]) {
+        self.named_stars.retain(|_, index| {
+            match new_index.get(index.as_usize()).copied().flatten() {
+                Some(n) => {
+                    *index = CatalogIndex(n);
+                    true
+                }
+                None => false,
+            }
+        });
     }
 
     //mp add_name
@@ -270,25 +621,22 @@ impl Catalog {
             "Attempt to find a star in the Catalog that has not has its data derived"
         );
         let v = Star::vec_of_ra_de(ra, de);
-        let s = Subcube::of_vector(&v);
-        let mut closest = None;
-        for s in s.iter_range(1) {
-            for index in self[s].iter() {
-                let c = v.dot(&self.stars[index.0].vector);
-                if let Some((cc, _)) = closest {
-                    if c > cc {
-                        closest = Some((c, *index));
+        let s = self.subcube_index.of_vector(&v);
+        let v = array_of_vec3(&v);
+        let mut closest: Option<(f64, CatalogIndex)> = None;
+        for s in self.occupied_subcubes_in_range(s, 1) {
+            self.star_vectors
+                .for_each_within(self.subcube_range(s), &v, -2.0, 2.0, |n, c| {
+                    if closest.is_none_or(|(cc, _)| c > cc) {
+                        closest = Some((c, self.subcube_stars[n]));
                     }
-                } else {
-                    closest = Some((c, *index));
-                }
-            }
+                });
         }
         closest
     }
 
     //mp iter_stars
-    pub fn iter_stars(&self) -> StarIter {
+    pub fn iter_stars(&self) -> StarIter<'_> {
         StarIter {
             catalog: self,
             i: 0,
@@ -298,7 +646,7 @@ impl Catalog {
     //mp iter_within_subcubes
     /// Iterate over all the stars in the catalog within a set of
     /// subcubes provide by an iterator
-    pub fn iter_within_subcubes<I>(&self, subcube_iter: I) -> StarSubcubeIter<I>
+    pub fn iter_within_subcubes<I>(&self, subcube_iter: I) -> StarSubcubeIter<'_, I>
     where
         I: std::iter::Iterator<Item = Subcube>,
     {
@@ -310,46 +658,153 @@ impl Catalog {
         }
     }
 
+    //mi subcubes_around
+    /// Find the non-empty subcubes that may contain stars within a
+    /// certain angle around a vector
+    ///
+    /// Needs data to have been derived for the Catalog
+    fn subcubes_around(&self, vector: &Vec3, max_angle: f64) -> Vec<Subcube> {
+        let max_subcube_cos = (max_angle + self.subcube_index.subcube_max_angle())
+            .min(std::f64::consts::PI)
+            .cos();
+        let subcube_range = self.subcube_index.range_for_angle(max_angle);
+        let mut subcubes =
+            self.occupied_subcubes_in_range(self.subcube_index.of_vector(vector), subcube_range);
+        subcubes.retain(|sub| {
+            sub.cos_angle_on_sphere(vector)
+                .is_some_and(|c| c >= max_subcube_cos)
+        });
+        subcubes
+    }
+
     //mp find_stars_around
     /// Find stars within a certain angle around a vector
     ///
     /// Needs data to have been derived for the Catalog
     pub fn find_stars_around(&self, vector: &Vec3, max_angle: f64) -> Vec<CatalogIndex> {
-        let subcube_max_angle = 2.0 * (Subcube::SUBCUBE_RADIUS).asin();
         let max_cos = max_angle.cos();
-        let max_subcube_cos = (max_angle + subcube_max_angle).cos();
-        let subcube_range = (max_angle / subcube_max_angle).trunc() as usize + 3;
+        let v = array_of_vec3(vector);
 
         // Run through all the supplied subcubes
         let mut result = vec![];
-        for sub in Subcube::of_vector(vector).iter_range(subcube_range) {
-            if self[sub].is_empty() {
-                continue;
-            }
-            let Some(c) = sub.cos_angle_on_sphere(vector) else {
-                continue;
-            };
-            if c < max_subcube_cos {
-                continue;
-            }
+        for sub in self.subcubes_around(vector, max_angle) {
+            self.star_vectors
+                .for_each_within(self.subcube_range(sub), &v, max_cos, 2.0, |n, _| {
+                    let index = self.subcube_stars[n];
+                    if self.filter.call(&self[index], result.len()) {
+                        result.push(index);
+                    }
+                });
+        }
+        result
+    }
 
-            for index in self[sub].iter() {
-                let star = &self[*index];
-                let c = star.vector.dot(vector);
-                if c < max_cos {
+    //mp find_brightest_around
+    /// Find the brightest (up to) *k* stars within a certain angle
+    /// around a vector
+    ///
+    /// The result is sorted by magnitude, brightest first
+    ///
+    /// As the stars in each subcube are sorted by magnitude, the scan
+    /// of a subcube stops as soon as its stars are dimmer than the
+    /// dimmest of the *k* found so far
+    ///
+    /// Needs data to have been derived for the Catalog
+    pub fn find_brightest_around(
+        &self,
+        vector: &Vec3,
+        max_angle: f64,
+        k: usize,
+    ) -> Vec<CatalogIndex> {
+        self.find_brightest_around_within(vector, max_angle, k, f32::INFINITY)
+    }
+
+    //mp find_stars_around_brighter_than
+    /// Find all the stars within a certain angle around a vector that
+    /// are brighter than a magnitude
+    ///
+    /// The result is sorted by magnitude, brightest first
+    ///
+    /// As the stars in each subcube are sorted by magnitude, the scan
+    /// of a subcube stops at the first star that is not bright enough
+    ///
+    /// Needs data to have been derived for the Catalog
+    pub fn find_stars_around_brighter_than(
+        &self,
+        vector: &Vec3,
+        max_angle: f64,
+        max_mag: f32,
+    ) -> Vec<CatalogIndex> {
+        self.find_brightest_around_within(vector, max_angle, usize::MAX, max_mag)
+    }
+
+    //mi find_brightest_around_within
+    /// Find the brightest (up to) *k* stars within a certain angle
+    /// around a vector that are also brighter than a magnitude
+    ///
+    /// The result is sorted by magnitude, brightest first
+    ///
+    /// If *k* is at least the number of stars in the catalog then
+    /// all the stars found are kept, and they are sorted once at the
+    /// end rather than as they are found
+    fn find_brightest_around_within(
+        &self,
+        vector: &Vec3,
+        max_angle: f64,
+        k: usize,
+        max_mag: f32,
+    ) -> Vec<CatalogIndex> {
+        let max_cos = max_angle.cos();
+        let v = array_of_vec3(vector);
+
+        let mut result: Vec<CatalogIndex> = vec![];
+        if k == 0 {
+            return result;
+        }
+        let unbounded = k >= self.stars.len();
+        for sub in self.subcubes_around(vector, max_angle) {
+            for n in self.subcube_range(sub) {
+                let mag = self.star_vectors.mag(n);
+                if mag >= max_mag {
+                    break;
+                }
+                if result.len() >= k && mag >= self[result[k - 1]].mag {
+                    break;
+                }
+                if self.star_vectors.dot(n, &v) < max_cos {
                     continue;
                 }
-                if !self.filter.call(star, result.len()) {
+                let index = self.subcube_stars[n];
+                if !self.filter.call(&self[index], result.len()) {
                     continue;
                 }
-                result.push(*index);
+                if unbounded {
+                    result.push(index);
+                } else {
+                    let n = result.partition_point(|i| self[*i].mag <= mag);
+                    result.insert(n, index);
+                    result.truncate(k);
+                }
             }
         }
+        if unbounded {
+            result.sort_by(|a, b| self[*a].mag.total_cmp(&self[*b].mag));
+        }
         result
     }
 
     //mp find_star_triangles
-    /// Find
+    /// Find triangles of stars (s0, s1, s2) whose angles s0-s1, s0-s2
+    /// and s1-s2 match the three angles (in radians) to within a
+    /// delta
+    ///
+    /// The angles may be anything from 0 to 180 degrees; the first
+    /// star of each triangle is in one of the subcubes provided, and
+    /// the catalog filter is applied to it
+    ///
+    /// The subcubes to search for the first star of each triangle
+    /// must be subcubes of the catalog (such as from
+    /// [Catalog::iter_subcubes])
     ///
     /// Needs data to have been derived for the Catalog
     pub fn find_star_triangles<I>(
@@ -360,34 +815,365 @@ impl Catalog {
     ) -> Vec<(CatalogIndex, CatalogIndex, CatalogIndex)>
     where
         I: Iterator<Item = Subcube>,
+    {
+        let mut result = vec![];
+        self.search_triangles(subcube_iter, angles_to_find, max_angle_delta, |n, _| {
+            let i0 = self.subcube_stars[n[0]];
+            if self.filter.call(&self[i0], result.len()) {
+                result.push((i0, self.subcube_stars[n[1]], self.subcube_stars[n[2]]));
+            }
+            true
+        });
+        result
+    }
+
+    //mp find_star_triangles_with
+    /// Find triangles of stars (s0, s1, s2) whose angles s0-s1, s0-s2
+    /// and s1-s2 match the three angles (in radians) to within a
+    /// delta, invoking a callback with each as it is found
+    ///
+    /// Each [TriangleMatch] provides the angles and their residuals,
+    /// and a score for the match. The catalog filter is applied to
+    /// all three stars of each triangle; the count of matches passed
+    /// to the callback so far is provided to the filter.
+    ///
+    /// If two or more of the angles are similar then the same three
+    /// stars may match in more than one order; if *dedup* is true
+    /// then only the best of these is provided.
+    ///
+    /// The callback should return false to stop the search.
+    ///
+    /// The subcubes to search for the first star of each triangle
+    /// must be subcubes of the catalog (such as from
+    /// [Catalog::iter_subcubes])
+    ///
+    /// Needs data to have been derived for the Catalog
+    pub fn find_star_triangles_with<I, F>(
+        &self,
+        subcube_iter: I,
+        angles_to_find: &[f64; 3],
+        max_angle_delta: f64,
+        dedup: bool,
+        mut f: F,
+    ) where
+        I: Iterator<Item = Subcube>,
+        F: FnMut(&TriangleMatch) -> bool,
+    {
+        let cos_ranges = triangle::cos_angle_ranges(angles_to_find, max_angle_delta);
+        let mut count = 0;
+        self.search_triangles(subcube_iter, angles_to_find, max_angle_delta, |n, cos| {
+            let stars = n.map(|n| self.subcube_stars[n]);
+            let m = TriangleMatch::new(stars, cos, angles_to_find);
+            if dedup && !m.is_canonical(&cos, angles_to_find, &cos_ranges) {
+                return true;
+            }
+            if !stars.iter().all(|i| self.filter.call(&self[*i], count)) {
+                return true;
+            }
+            count += 1;
+            f(&m)
+        });
+    }
+
+    //mp find_star_triangles_ranked
+    /// Find triangles of stars (s0, s1, s2) whose angles s0-s1, s0-s2
+    /// and s1-s2 match the three angles (in radians) to within a
+    /// delta, sorted by score (best first)
+    ///
+    /// See [Catalog::find_star_triangles_with] for details
+    pub fn find_star_triangles_ranked<I>(
+        &self,
+        subcube_iter: I,
+        angles_to_find: &[f64; 3],
+        max_angle_delta: f64,
+        dedup: bool,
+    ) -> Vec<TriangleMatch>
+    where
+        I: Iterator<Item = Subcube>,
+    {
+        let mut result = vec![];
+        self.find_star_triangles_with(subcube_iter, angles_to_find, max_angle_delta, dedup, |m| {
+            result.push(*m);
+            true
+        });
+        result.sort_by(|a, b| a.score.total_cmp(&b.score));
+        result
+    }
+
+    //mp triangle_uniqueness
+    /// Determine how unique the triangles of a set of (three or more)
+    /// stars are, given a tolerance (in radians) for measuring the
+    /// angles between them
+    ///
+    /// For every triangle of the stars, the catalog is searched for
+    /// other triangles of stars with the same angles; the number of
+    /// these and (up to) *max_nearest* of the best matching are
+    /// reported
+    ///
+    /// Needs data to have been derived for the Catalog
+    pub fn triangle_uniqueness(
+        &self,
+        stars: &[CatalogIndex],
+        max_angle_delta: f64,
+        max_nearest: usize,
+    ) -> Vec<TriangleUniqueness> {
+        let mut result = vec![];
+        for k in 2..stars.len() {
+            for j in 1..k {
+                for i in 0..j {
+                    let tri = [stars[i], stars[j], stars[k]];
+                    let angles = [(0, 1), (0, 2), (1, 2)].map(|(a, b)| {
+                        self[tri[a]]
+                            .cos_angle_between(&self[tri[b]])
+                            .clamp(-1., 1.)
+                            .acos()
+                    });
+                    let mut sorted_tri = tri;
+                    sorted_tri.sort();
+                    let mut competing = self.find_star_triangles_ranked(
+                        self.iter_subcubes(),
+                        &angles,
+                        max_angle_delta,
+                        true,
+                    );
+                    competing.retain(|m| {
+                        let mut s = m.stars;
+                        s.sort();
+                        s != sorted_tri
+                    });
+                    let num_competing = competing.len();
+                    competing.truncate(max_nearest);
+                    result.push(TriangleUniqueness {
+                        stars: tri,
+                        angles,
+                        num_competing,
+                        nearest: competing,
+                    });
+                }
+            }
+        }
+        result
+    }
+
+    //mp find_asterisms_with
+    /// Find all the assignments of stars in the catalog to the stars
+    /// of an [AsterismPattern] that match its known angles to within a
+    /// delta, invoking a callback with each as it is found
+    ///
+    /// The search places star 0 of the pattern at each star of the
+    /// supplied subcubes in turn, and then places the other stars of
+    /// the pattern one at a time (backtracking when no star fits);
+    /// the candidates for each are found in the subcubes around a
+    /// star already placed, and checked against the known angles to
+    /// the other stars already placed.
+    ///
+    /// The catalog filter is applied to every star of each
+    /// assignment; the count of matches passed to the callback so far
+    /// is provided to the filter. The callback should return false to
+    /// stop the search.
+    ///
+    /// If the pattern is symmetric then the same stars will be found
+    /// in more than one order.
+    ///
+    /// The subcubes to search for star 0 of the pattern must be
+    /// subcubes of the catalog (such as from [Catalog::iter_subcubes])
+    ///
+    /// Returns an error if the stars of the pattern are not all
+    /// connected by known angles
+    ///
+    /// Needs data to have been derived for the Catalog
+    pub fn find_asterisms_with<I, F>(
+        &self,
+        subcube_iter: I,
+        pattern: &AsterismPattern,
+        max_angle_delta: f64,
+        mut f: F,
+    ) -> Result<(), Error>
+    where
+        I: Iterator<Item = Subcube>,
+        F: FnMut(&AsterismMatch) -> bool,
+    {
+        let steps = pattern.search_order()?;
+        if pattern.num_stars() == 0 {
+            return Ok(());
+        }
+        let mut positions = vec![0; pattern.num_stars()];
+        let mut placed = vec![];
+        let mut count = 0;
+        for sub0 in subcube_iter {
+            assert_eq!(
+                sub0.ele_per_side(),
+                self.subcube_index.ele_per_side(),
+                "Subcube supplied to find_asterisms is not from the catalog's subcubes"
+            );
+            for n0 in self.subcube_range(sub0) {
+                if !self.filter.call(&self[self.subcube_stars[n0]], count) {
+                    continue;
+                }
+                positions[0] = n0;
+                placed.push(n0);
+                let more = self.search_asterism(
+                    pattern,
+                    &steps,
+                    max_angle_delta,
+                    &mut positions,
+                    &mut placed,
+                    &mut count,
+                    &mut f,
+                );
+                placed.pop();
+                if !more {
+                    return Ok(());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    //mp find_asterisms
+    /// Find all the assignments of stars in the catalog to the stars
+    /// of an [AsterismPattern] that match its known angles to within a
+    /// delta, sorted by score (best first)
+    ///
+    /// See [Catalog::find_asterisms_with] for details
+    pub fn find_asterisms<I>(
+        &self,
+        subcube_iter: I,
+        pattern: &AsterismPattern,
+        max_angle_delta: f64,
+    ) -> Result<Vec<AsterismMatch>, Error>
+    where
+        I: Iterator<Item = Subcube>,
+    {
+        let mut result = vec![];
+        self.find_asterisms_with(subcube_iter, pattern, max_angle_delta, |m| {
+            result.push(m.clone());
+            true
+        })?;
+        result.sort_by(|a, b| a.score.total_cmp(&b.score));
+        Ok(result)
+    }
+
+    //mi search_asterism
+    /// Place the stars of the remaining steps of an asterism search,
+    /// given the positions (in the subcube ordering of the stars) of
+    /// the stars already placed
+    ///
+    /// Returns false if the search should stop
+    #[allow(clippy::too_many_arguments)]
+    fn search_asterism<F>(
+        &self,
+        pattern: &AsterismPattern,
+        steps: &[PatternStep],
+        max_angle_delta: f64,
+        positions: &mut [usize],
+        placed: &mut Vec<usize>,
+        count: &mut usize,
+        f: &mut F,
+    ) -> bool
+    where
+        F: FnMut(&AsterismMatch) -> bool,
+    {
+        let Some((step, steps)) = steps.split_first() else {
+            let stars: Vec<CatalogIndex> =
+                positions.iter().map(|n| self.subcube_stars[*n]).collect();
+            let mut sum_sq = 0.;
+            let mut num_angles = 0;
+            for (a, b, angle) in pattern.iter_angles() {
+                let c = self
+                    .star_vectors
+                    .dot(positions[a], &self.star_vectors.vector(positions[b]));
+                let residual = c.clamp(-1., 1.).acos() - angle;
+                sum_sq += residual * residual;
+                num_angles += 1;
+            }
+            let score = if num_angles > 0 {
+                (sum_sq / num_angles as f64).sqrt()
+            } else {
+                0.
+            };
+            *count += 1;
+            return f(&AsterismMatch { stars, score });
+        };
+
+        let anchor = positions[step.anchor];
+        let anchor_vector = self.star_vectors.vector(anchor);
+        let (min_cos, max_cos) = triangle::cos_angle_range(step.angle, max_angle_delta);
+        let mut candidates = vec![];
+        let around = (step.angle + max_angle_delta).min(std::f64::consts::PI);
+        for sub in self.subcubes_around(&self[self.subcube_stars[anchor]].vector, around) {
+            self.star_vectors.for_each_within(
+                self.subcube_range(sub),
+                &anchor_vector,
+                min_cos,
+                max_cos,
+                |n, _| candidates.push(n),
+            );
+        }
+
+        for n in candidates {
+            if placed.contains(&n) {
+                continue;
+            }
+            let fits = step.checks.iter().all(|(s, angle)| {
+                let (min_cos, max_cos) = triangle::cos_angle_range(*angle, max_angle_delta);
+                let c = self
+                    .star_vectors
+                    .dot(n, &self.star_vectors.vector(positions[*s]));
+                c >= min_cos && c <= max_cos
+            });
+            if !fits || !self.filter.call(&self[self.subcube_stars[n]], *count) {
+                continue;
+            }
+            positions[step.star] = n;
+            placed.push(n);
+            let more =
+                self.search_asterism(pattern, steps, max_angle_delta, positions, placed, count, f);
+            placed.pop();
+            if !more {
+                return false;
+            }
+        }
+        true
+    }
+
+    //mi search_triangles
+    /// Search for triangles of stars, invoking a callback with the
+    /// positions of the three stars (in the subcube ordering of the
+    /// stars) and the cosines of the angles between them
+    ///
+    /// The callback should return false to stop the search
+    fn search_triangles<I, F>(
+        &self,
+        subcube_iter: I,
+        angles_to_find: &[f64; 3],
+        max_angle_delta: f64,
+        mut f: F,
+    ) where
+        I: Iterator<Item = Subcube>,
+        F: FnMut([usize; 3], [f64; 3]) -> bool,
     {
         // Find the range of cosines for the angles that we will accept
-        //
-        // Note cos(0) > cos(0.1) so min cos is cos(angle + max)
-        let cos_angle_ranges: Vec<(f64, f64)> = angles_to_find
-            .iter()
-            .map(|a| {
-                (
-                    (*a + max_angle_delta).cos(),
-                    (*a - max_angle_delta).max(0.).cos(),
-                )
-            })
-            .collect();
+        let cos_angle_ranges = triangle::cos_angle_ranges(angles_to_find, max_angle_delta);
 
         // Find the range of subcube centre angles that are allowed for each of the triangle angles
-        let subcube_max_angle = 2.0 * (Subcube::SUBCUBE_RADIUS).asin();
+        let subcube_max_angle = self.subcube_index.subcube_max_angle();
         let subcube_angle_ranges: Vec<(f64, f64)> = angles_to_find
             .iter()
             .map(|a| {
                 (
                     (*a - max_angle_delta - subcube_max_angle).max(0.),
-                    (*a + max_angle_delta + subcube_max_angle).min(std::f64::consts::PI / 2.),
+                    (*a + max_angle_delta + subcube_max_angle).min(std::f64::consts::PI),
                 )
             })
             .collect();
+        // The cosine ranges are widened slightly, as (for example) the
+        // cosine of the angle between a subcube centre and itself may
+        // come out as just over 1 (or, for opposite subcubes, just
+        // under -1)
         let subcube_cos_angle_ranges: Vec<(f64, f64)> = subcube_angle_ranges
             .iter()
-            .map(|(min, max)| (max.cos(), min.cos()))
+            .map(|(min, max)| (max.cos() - 1.0E-9, min.cos() + 1.0E-9))
             .collect();
 
         // Determine the delta to the subcube for each angle (subcubes
@@ -402,13 +1188,20 @@ impl Catalog {
         // For max 15.71 degrees (mag 5.0)  needs range = 3
         // let range = Subcube::ELE_PER_SIDE / 2;
         let max_angle = angles_to_find.iter().fold(0.0, |acc: f64, b| acc.max(*b));
-        let subcube_range = (max_angle / subcube_max_angle).trunc() as usize + 3;
+        let subcube_range = self
+            .subcube_index
+            .range_for_angle(max_angle + max_angle_delta);
 
         // Run through all the supplied subcubes
-        let mut result = vec![];
+        let mut stop = false;
         let mut subcubes_to_search = vec![];
 
         for sub0 in subcube_iter {
+            assert_eq!(
+                sub0.ele_per_side(),
+                self.subcube_index.ele_per_side(),
+                "Subcube supplied to find_star_triangles is not from the catalog's subcubes"
+            );
             if self[sub0].is_empty() {
                 continue;
             }
@@ -429,10 +1222,7 @@ impl Catalog {
             let max_cos = subcube_cos_angle_ranges[0]
                 .1
                 .max(subcube_cos_angle_ranges[1].1);
-            for s12 in sub0.iter_range(subcube_range) {
-                if self[s12].is_empty() {
-                    continue;
-                }
+            for s12 in self.occupied_subcubes_in_range(sub0, subcube_range) {
                 let Some(c) = s12.cos_angle_on_sphere(&sub0_center) else {
                     continue;
                 };
@@ -442,67 +1232,69 @@ impl Catalog {
                 subcubes_to_search.push(s12)
             }
 
-            for i0 in self[sub0].iter() {
-                let s0 = &self[*i0];
+            for n0 in self.subcube_range(sub0) {
+                let v0 = self.star_vectors.vector(n0);
                 // iterate through subcubes_to_search, skipping those that are nowhere near angles_to_find[0] away
                 let subcubes_for_s0 = subcubes_to_search
                     .iter()
                     .filter(|s| {
                         let c = s.center().normalize().dot(&sub0_center);
-                        c > subcube_cos_angle_ranges[0].0 && c < subcube_cos_angle_ranges[0].1
+                        c >= subcube_cos_angle_ranges[0].0 && c <= subcube_cos_angle_ranges[0].1
                     })
                     .copied();
                 for sub1 in subcubes_for_s0 {
-                    for i1 in self[sub1].iter() {
-                        if *i0 == *i1 {
-                            continue;
-                        }
-                        let s1 = &self[*i1];
-
-                        let c_s01 = s0.cos_angle_between(s1);
-                        if c_s01 < cos_angle_ranges[0].0 || c_s01 > cos_angle_ranges[0].1 {
-                            continue;
-                        }
-
-                        let sub1_center = sub1.center().normalize();
-                        let subcubes_for_s1 = subcubes_to_search
-                            .iter()
-                            .filter(|s| {
-                                let c = s.center().normalize().dot(&sub1_center);
-                                c > subcube_cos_angle_ranges[2].0
-                                    && c < subcube_cos_angle_ranges[2].1
-                            })
-                            .filter(|s| {
-                                let c = s.center().normalize().dot(&sub0_center);
-                                c > subcube_cos_angle_ranges[1].0
-                                    && c < subcube_cos_angle_ranges[1].1
-                            })
-                            .copied();
-                        for sub2 in subcubes_for_s1 {
-                            for i2 in self[sub2].iter() {
-                                if *i0 == *i2 || *i1 == *i2 {
-                                    continue;
-                                }
-                                let s2 = &self[*i2];
-                                let c_s02 = s0.cos_angle_between(s2);
-                                if c_s02 < cos_angle_ranges[1].0 || c_s02 > cos_angle_ranges[1].1 {
-                                    continue;
-                                }
-                                let c_s12 = s1.cos_angle_between(s2);
-                                if c_s12 < cos_angle_ranges[2].0 || c_s12 > cos_angle_ranges[2].1 {
-                                    continue;
-                                }
-                                if !self.filter.call(s0, result.len()) {
-                                    continue;
-                                }
-                                result.push((*i0, *i1, *i2));
+                    let sub1_center = sub1.center().normalize();
+                    self.star_vectors.for_each_within(
+                        self.subcube_range(sub1),
+                        &v0,
+                        cos_angle_ranges[0].0,
+                        cos_angle_ranges[0].1,
+                        |n1, c_s01| {
+                            if stop || n0 == n1 {
+                                return;
                             }
-                        }
+                            let v1 = self.star_vectors.vector(n1);
+                            let subcubes_for_s1 = subcubes_to_search
+                                .iter()
+                                .filter(|s| {
+                                    let c = s.center().normalize().dot(&sub1_center);
+                                    c >= subcube_cos_angle_ranges[2].0
+                                        && c <= subcube_cos_angle_ranges[2].1
+                                })
+                                .filter(|s| {
+                                    let c = s.center().normalize().dot(&sub0_center);
+                                    c >= subcube_cos_angle_ranges[1].0
+                                        && c <= subcube_cos_angle_ranges[1].1
+                                })
+                                .copied();
+                            for sub2 in subcubes_for_s1 {
+                                self.star_vectors.for_each_within(
+                                    self.subcube_range(sub2),
+                                    &v0,
+                                    cos_angle_ranges[1].0,
+                                    cos_angle_ranges[1].1,
+                                    |n2, c_s02| {
+                                        if stop || n0 == n2 || n1 == n2 {
+                                            return;
+                                        }
+                                        let c_s12 = self.star_vectors.dot(n2, &v1);
+                                        if c_s12 < cos_angle_ranges[2].0
+                                            || c_s12 > cos_angle_ranges[2].1
+                                        {
+                                            return;
+                                        }
+                                        stop = !f([n0, n1, n2], [c_s01, c_s02, c_s12]);
+                                    },
+                                );
+                            }
+                        },
+                    );
+                    if stop {
+                        return;
                     }
                 }
             }
         }
-        result
     }
 }
 
@@ -514,16 +1306,27 @@ impl std::ops::Index<CatalogIndex> for Catalog {
 }
 
 impl std::ops::Index<Subcube> for Catalog {
-    type Output = Vec<CatalogIndex>;
-    fn index(&self, q: Subcube) -> &Vec<CatalogIndex> {
-        &self.subcubes[q.as_usize()]
+    type Output = [CatalogIndex];
+    fn index(&self, q: Subcube) -> &[CatalogIndex] {
+        &self.subcube_stars[self.subcube_range(q)]
+    }
+}
+
+//tp SubcubeIter
+/// An iterator over the subcubes of a [Catalog] that contain stars
+pub struct SubcubeIter<'a> {
+    subcube_index: SubcubeIndex,
+    ids: std::slice::Iter<'a, u32>,
+}
+impl std::iter::Iterator for SubcubeIter<'_> {
+    type Item = Subcube;
+    fn next(&mut self) -> Option<Subcube> {
+        self.ids
+            .next()
+            .map(|id| self.subcube_index.subcube_of_usize(*id as usize))
     }
 }
-// impl std::ops::IndexMut<Subcube> for Catalog {
-//     fn index_mut(&mut self, q: Subcube) -> &mut Vec<usize> {
-//         &mut self.subcubes[q.as_usize()]
-//     }
-// }
+
 pub struct StarIter<'a> {
     catalog: &'a Catalog,
     i: usize,
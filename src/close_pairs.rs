@@ -0,0 +1,116 @@
+//a Imports
+use std::collections::HashSet;
+
+use crate::{Catalog, CatalogIndex, Star, StarFilter};
+
+//a Useful functions
+//fp combined_magnitude
+/// Get the magnitude of two stars that are not resolved, as the sum of
+/// their fluxes
+pub fn combined_magnitude(mag0: f32, mag1: f32) -> f32 {
+    let flux = 10.0_f32.powf(-0.4 * mag0) + 10.0_f32.powf(-0.4 * mag1);
+    -2.5 * flux.log10()
+}
+
+//fp position_angle
+/// Get the position angle of one star from another, in radians from
+/// 0 to 2*PI, measured from north through east
+pub fn position_angle(from: &Star, to: &Star) -> f64 {
+    let d_ra = to.ra - from.ra;
+    let y = d_ra.sin() * to.de.cos();
+    let x = from.de.cos() * to.de.sin() - from.de.sin() * to.de.cos() * d_ra.cos();
+    y.atan2(x).rem_euclid(std::f64::consts::TAU)
+}
+
+//a ClosePair
+//tp ClosePair
+/// A pair of stars that are close together on the sky, found by
+/// [Catalog::close_pairs]
+///
+/// Such pairs may not be resolved by a sensor, and can then confuse
+/// the centroiding and matching of stars
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClosePair {
+    /// The two stars, the brighter (the primary) first
+    pub stars: [CatalogIndex; 2],
+    /// The angle between the stars, in radians
+    pub separation: f64,
+    /// The position angle of the fainter star from the brighter, in
+    /// radians from 0 to 2*PI measured from north through east
+    pub position_angle: f64,
+    /// The magnitude of the two stars combined (if unresolved)
+    pub combined_magnitude: f32,
+}
+
+//tp ClosePairPolicy
+/// How a [StarFilter] from [StarFilter::close_pairs] treats the stars
+/// of close pairs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClosePairPolicy {
+    /// Reject both stars of every pair
+    Drop,
+    /// Reject the fainter star of every pair, keeping the brighter
+    ///
+    /// Unlike [Catalog::merge_close_pairs] the brighter star is not
+    /// moved or brightened to account for the fainter one
+    KeepBrighter,
+}
+
+//ip StarFilter
+impl StarFilter {
+    //cp close_pairs
+    /// Create a new filter that returns false for stars of close
+    /// pairs (from [Catalog::close_pairs]), according to a policy
+    ///
+    /// The stars are identified by their ids, so the filter remains
+    /// valid if the catalog is sorted again
+    pub fn close_pairs(catalog: &Catalog, pairs: &[ClosePair], policy: ClosePairPolicy) -> Self {
+        let mut ids = HashSet::new();
+        for p in pairs {
+            if policy == ClosePairPolicy::Drop {
+                ids.insert(catalog[p.stars[0]].id);
+            }
+            ids.insert(catalog[p.stars[1]].id);
+        }
+        (move |s: &Star, _n: usize| !ids.contains(&s.id)).into()
+    }
+}
+
+//ip Catalog
+impl Catalog {
+    //mp close_pairs
+    /// Find all the pairs of stars that are within an angle (in
+    /// radians) of each other, sorted by separation (closest first)
+    ///
+    /// The catalog filter is applied to both stars of each pair
+    ///
+    /// Needs data to have been derived for the Catalog
+    pub fn close_pairs(&self, max_angle: f64) -> Vec<ClosePair> {
+        let mut result = vec![];
+        let mut accepted = 0;
+        for i in 0..self.len() {
+            let i = CatalogIndex::from(i);
+            let s0 = &self[i];
+            if !self.filter().call(s0, accepted) {
+                continue;
+            }
+            accepted += 1;
+            for j in self.find_stars_around(&s0.vector, max_angle) {
+                if j <= i {
+                    continue;
+                }
+                let s1 = &self[j];
+                let (a, b) = if s1.mag < s0.mag { (j, i) } else { (i, j) };
+                let (primary, secondary) = (&self[a], &self[b]);
+                result.push(ClosePair {
+                    stars: [a, b],
+                    separation: s0.cos_angle_between(s1).clamp(-1., 1.).acos(),
+                    position_angle: position_angle(primary, secondary),
+                    combined_magnitude: combined_magnitude(primary.mag, secondary.mag),
+                });
+            }
+        }
+        result.sort_by(|a, b| a.separation.total_cmp(&b.separation));
+        result
+    }
+}
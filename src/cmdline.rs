@@ -29,6 +29,29 @@ pub fn catalog(matches: &ArgMatches) -> String {
     matches.get_one::<String>("catalog").unwrap().to_string()
 }
 
+//fp add_optional_catalog_arg
+/// Add an argument to a clap [Command] to specify a catalog to load;
+/// this is an *optional* *positional* argument, for commands whose
+/// subcommands do not all need a catalog
+pub fn add_optional_catalog_arg(
+    cmd: Command,
+    long_help: impl IntoResettable<StyledStr>,
+) -> Command {
+    cmd.arg(
+        Arg::new("catalog")
+            .required(false)
+            .long_help(long_help)
+            .action(ArgAction::Set),
+    )
+}
+
+//fp optional_catalog
+/// Retrieve the value of the catalog to load from the clap
+/// [ArgMatches], if it was given
+pub fn optional_catalog(matches: &ArgMatches) -> Option<String> {
+    matches.get_one::<String>("catalog").cloned()
+}
+
 //fp add_names_arg
 /// Add an optional argument to a clap [Command] to specify a names file to load
 pub fn add_names_arg(cmd: Command, long_help: impl IntoResettable<StyledStr>) -> Command {
@@ -110,6 +133,27 @@ pub fn fov(matches: &ArgMatches, default: f64) -> f64 {
     matches.get_one::<f64>("fov").unwrap_or(&default) * std::f64::consts::PI / 180.0
 }
 
+//fp add_vfov_arg
+/// Add an optional argument to a clap [Command] to specify a vertical
+/// field-of-view in degrees
+pub fn add_vfov_arg(cmd: Command, long_help: impl IntoResettable<StyledStr>) -> Command {
+    cmd.arg(
+        Arg::new("vfov")
+            .long("vfov")
+            .value_parser(value_parser!(f64))
+            .long_help(long_help)
+            .action(ArgAction::Set),
+    )
+}
+
+//fp vfov
+/// Retrieve the value of the vertical field-of-view argument, if given
+pub fn vfov(matches: &ArgMatches) -> Option<f64> {
+    matches
+        .get_one::<f64>("vfov")
+        .map(|v| v * std::f64::consts::PI / 180.0)
+}
+
 //fp add_up_arg
 /// Add an optional argument to a clap [Command] to specify an up
 /// direction - a star name or id
@@ -256,6 +300,399 @@ pub fn angles(matches: &ArgMatches) -> Option<ValuesRef<'_, f64>> {
     matches.get_many::<f64>("angles")
 }
 
+//fp add_dedup_arg
+/// Add an optional flag to a clap [Command] to request that
+/// equivalent results be removed
+pub fn add_dedup_arg(cmd: Command, long_help: impl IntoResettable<StyledStr>) -> Command {
+    cmd.arg(
+        Arg::new("dedup")
+            .long("dedup")
+            .long_help(long_help)
+            .action(ArgAction::SetTrue),
+    )
+}
+
+//fp dedup
+/// Retrieve the value of the dedup flag
+pub fn dedup(matches: &ArgMatches) -> bool {
+    matches.get_flag("dedup")
+}
+
+//a Coverage arguments
+//fp add_min_stars_arg
+/// Add an optional argument to a clap [Command] to specify a minimum
+/// number of stars
+pub fn add_min_stars_arg(cmd: Command, long_help: impl IntoResettable<StyledStr>) -> Command {
+    cmd.arg(
+        Arg::new("min_stars")
+            .long("min-stars")
+            .value_parser(value_parser!(usize))
+            .long_help(long_help)
+            .action(ArgAction::Set),
+    )
+}
+
+//fp min_stars
+/// Retrieve the value of the minimum number of stars argument or a
+/// default value
+pub fn min_stars(matches: &ArgMatches, default: usize) -> usize {
+    *matches.get_one::<usize>("min_stars").unwrap_or(&default)
+}
+
+//fp add_order_arg
+/// Add an optional argument to a clap [Command] to specify a HEALPix
+/// order
+pub fn add_order_arg(cmd: Command, long_help: impl IntoResettable<StyledStr>) -> Command {
+    cmd.arg(
+        Arg::new("order")
+            .long("order")
+            .value_parser(value_parser!(u32).range(0..=12))
+            .long_help(long_help)
+            .action(ArgAction::Set),
+    )
+}
+
+//fp order
+/// Retrieve the value of the HEALPix order argument or a default value
+pub fn order(matches: &ArgMatches, default: u32) -> u32 {
+    *matches.get_one::<u32>("order").unwrap_or(&default)
+}
+
+//a Thinning arguments
+//fp add_max_per_cell_arg
+/// Add an optional argument to a clap [Command] to specify a maximum
+/// number of stars per cell
+pub fn add_max_per_cell_arg(cmd: Command, long_help: impl IntoResettable<StyledStr>) -> Command {
+    cmd.arg(
+        Arg::new("max_per_cell")
+            .long("max-per-cell")
+            .value_parser(value_parser!(usize))
+            .long_help(long_help)
+            .action(ArgAction::Set),
+    )
+}
+
+//fp max_per_cell
+/// Retrieve the value of the maximum number of stars per cell
+/// argument, if given
+pub fn max_per_cell(matches: &ArgMatches) -> Option<usize> {
+    matches.get_one::<usize>("max_per_cell").copied()
+}
+
+//fp add_min_separation_arg
+/// Add an optional argument to a clap [Command] to specify a minimum
+/// separation of stars in degrees
+pub fn add_min_separation_arg(cmd: Command, long_help: impl IntoResettable<StyledStr>) -> Command {
+    cmd.arg(
+        Arg::new("min_separation")
+            .long("min-separation")
+            .value_parser(value_parser!(f64))
+            .long_help(long_help)
+            .action(ArgAction::Set),
+    )
+}
+
+//fp min_separation
+/// Retrieve the value of the minimum separation argument (in
+/// radians) or a default value (in degrees)
+pub fn min_separation(matches: &ArgMatches, default: f64) -> f64 {
+    matches.get_one::<f64>("min_separation").unwrap_or(&default) * std::f64::consts::PI / 180.0
+}
+
+//fp add_cells_per_side_arg
+/// Add an optional argument to a clap [Command] to specify the number
+/// of subcubes per side of the cube
+pub fn add_cells_per_side_arg(cmd: Command, long_help: impl IntoResettable<StyledStr>) -> Command {
+    cmd.arg(
+        Arg::new("cells_per_side")
+            .long("cells-per-side")
+            .value_parser(value_parser!(usize))
+            .long_help(long_help)
+            .action(ArgAction::Set),
+    )
+}
+
+//fp cells_per_side
+/// Retrieve the value of the number of subcubes per side argument, if
+/// given
+pub fn cells_per_side(matches: &ArgMatches) -> Option<usize> {
+    matches.get_one::<usize>("cells_per_side").copied()
+}
+
+//a Constellation arguments
+//fp add_boundaries_arg
+/// Add an optional argument to a clap [Command] to specify a file of
+/// constellation boundaries
+pub fn add_boundaries_arg(cmd: Command, long_help: impl IntoResettable<StyledStr>) -> Command {
+    cmd.arg(
+        Arg::new("boundaries")
+            .long("boundaries")
+            .long_help(long_help)
+            .action(ArgAction::Set),
+    )
+}
+
+//fp boundaries
+/// Retrieve the value of the constellation boundaries file, if
+/// specified
+pub fn boundaries(matches: &ArgMatches) -> Option<&String> {
+    matches.get_one::<String>("boundaries")
+}
+
+//fp add_draw_boundaries_arg
+/// Add a flag to a clap [Command] to draw the boundaries between
+/// constellations
+pub fn add_draw_boundaries_arg(cmd: Command, long_help: impl IntoResettable<StyledStr>) -> Command {
+    cmd.arg(
+        Arg::new("draw_boundaries")
+            .long("draw-boundaries")
+            .long_help(long_help)
+            .action(ArgAction::SetTrue),
+    )
+}
+
+//fp draw_boundaries
+/// Retrieve the value of the draw boundaries flag
+pub fn draw_boundaries(matches: &ArgMatches) -> bool {
+    matches.get_flag("draw_boundaries")
+}
+
+//fp add_constellation_arg
+/// Add an optional argument to a clap [Command] to specify a
+/// constellation by its abbreviation
+pub fn add_constellation_arg(cmd: Command, long_help: impl IntoResettable<StyledStr>) -> Command {
+    cmd.arg(
+        Arg::new("constellation")
+            .long("constellation")
+            .long_help(long_help)
+            .action(ArgAction::Set),
+    )
+}
+
+//fp constellation
+/// Retrieve the value of the constellation argument
+pub fn constellation(matches: &ArgMatches) -> Option<&String> {
+    matches.get_one::<String>("constellation")
+}
+
+//fp add_constellations_arg
+/// Add an optional argument to a clap [Command] to draw constellation
+/// figures, optionally given a file of the figures
+pub fn add_constellations_arg(cmd: Command, long_help: impl IntoResettable<StyledStr>) -> Command {
+    cmd.arg(
+        Arg::new("constellations")
+            .long("constellations")
+            .long_help(long_help)
+            .num_args(0..=1)
+            .default_missing_value("western")
+            .action(ArgAction::Set),
+    )
+}
+
+//fp constellations
+/// Retrieve the value of the constellations argument, if specified;
+/// this is 'western' if no file was given
+pub fn constellations(matches: &ArgMatches) -> Option<&String> {
+    matches.get_one::<String>("constellations")
+}
+
+//a Deep-sky arguments
+//fp add_deep_sky_arg
+/// Add an optional argument to a clap [Command] to draw deep-sky
+/// objects, optionally given a file of the objects
+pub fn add_deep_sky_arg(cmd: Command, long_help: impl IntoResettable<StyledStr>) -> Command {
+    cmd.arg(
+        Arg::new("deep_sky")
+            .long("deep-sky")
+            .long_help(long_help)
+            .num_args(0..=1)
+            .default_missing_value("builtin")
+            .action(ArgAction::Set),
+    )
+}
+
+//fp deep_sky
+/// Retrieve the value of the deep-sky argument, if specified; this is
+/// 'builtin' if no file was given
+pub fn deep_sky(matches: &ArgMatches) -> Option<&String> {
+    matches.get_one::<String>("deep_sky")
+}
+
+//a Time arguments
+//fp add_date_arg
+/// Add an optional argument to a clap [Command] to specify a date and
+/// time (UT), as accepted by [crate::time::parse_date]
+pub fn add_date_arg(cmd: Command, long_help: impl IntoResettable<StyledStr>) -> Command {
+    cmd.arg(
+        Arg::new("date")
+            .long("date")
+            .long_help(long_help)
+            .value_parser(|s: &str| {
+                crate::time::parse_date(s).ok_or_else(|| format!("Bad date '{s}'"))
+            })
+            .action(ArgAction::Set),
+    )
+}
+
+//fp date
+/// Retrieve the value of the date argument as a Julian Date, if
+/// specified
+pub fn date(matches: &ArgMatches) -> Option<f64> {
+    matches.get_one::<f64>("date").copied()
+}
+
+//fp add_planets_arg
+/// Add an optional flag to a clap [Command] to request that the Sun,
+/// Moon and planets be drawn
+pub fn add_planets_arg(cmd: Command, long_help: impl IntoResettable<StyledStr>) -> Command {
+    cmd.arg(
+        Arg::new("planets")
+            .long("planets")
+            .long_help(long_help)
+            .action(ArgAction::SetTrue),
+    )
+}
+
+//fp planets
+/// Retrieve the value of the planets flag
+pub fn planets(matches: &ArgMatches) -> bool {
+    matches.get_flag("planets")
+}
+
+//a Minor body arguments
+//fp add_minor_bodies_arg
+/// Add an optional argument to a clap [Command] to specify a file of
+/// comet or asteroid orbital elements, in the formats of the Minor
+/// Planet Center
+pub fn add_minor_bodies_arg(cmd: Command, long_help: impl IntoResettable<StyledStr>) -> Command {
+    cmd.arg(
+        Arg::new("minor_bodies")
+            .long("minor-bodies")
+            .long_help(long_help)
+            .action(ArgAction::Set),
+    )
+}
+
+//fp minor_bodies
+/// Retrieve the value of the minor bodies file, if specified
+pub fn minor_bodies(matches: &ArgMatches) -> Option<&String> {
+    matches.get_one::<String>("minor_bodies")
+}
+
+//fp add_end_date_arg
+/// Add an optional argument to a clap [Command] to specify the date
+/// and time (UT) of the end of a range, as accepted by
+/// [crate::time::parse_date]
+pub fn add_end_date_arg(cmd: Command, long_help: impl IntoResettable<StyledStr>) -> Command {
+    cmd.arg(
+        Arg::new("end_date")
+            .long("end-date")
+            .long_help(long_help)
+            .value_parser(|s: &str| {
+                crate::time::parse_date(s).ok_or_else(|| format!("Bad date '{s}'"))
+            })
+            .action(ArgAction::Set),
+    )
+}
+
+//fp end_date
+/// Retrieve the value of the end date argument as a Julian Date, if
+/// specified
+pub fn end_date(matches: &ArgMatches) -> Option<f64> {
+    matches.get_one::<f64>("end_date").copied()
+}
+
+//fp add_step_arg
+/// Add an optional argument to a clap [Command] to specify the step
+/// between positions over a range of dates
+///
+/// The step is in days, unless it ends with 's', 'm', 'h' or 'd' for
+/// seconds, minutes, hours or days
+pub fn add_step_arg(cmd: Command, long_help: impl IntoResettable<StyledStr>) -> Command {
+    cmd.arg(
+        Arg::new("step")
+            .long("step")
+            .long_help(long_help)
+            .value_parser(|s: &str| {
+                let (value, scale) = match s.char_indices().last() {
+                    Some((n, 's')) => (&s[..n], 1.0 / 86400.0),
+                    Some((n, 'm')) => (&s[..n], 1.0 / 1440.0),
+                    Some((n, 'h')) => (&s[..n], 1.0 / 24.0),
+                    Some((n, 'd')) => (&s[..n], 1.0),
+                    _ => (s, 1.0),
+                };
+                match value.trim().parse::<f64>() {
+                    Ok(step) if step > 0. => Ok(step * scale),
+                    _ => Err(format!("Bad step '{s}'")),
+                }
+            })
+            .action(ArgAction::Set),
+    )
+}
+
+//fp step
+/// Retrieve the value of the step argument (in days) or a default
+/// value
+pub fn step(matches: &ArgMatches, default: f64) -> f64 {
+    *matches.get_one::<f64>("step").unwrap_or(&default)
+}
+
+//fp add_site_arg
+/// Add an optional argument to a clap [Command] to specify the site
+/// of an observer, as `LATITUDE,LONGITUDE[,HEIGHT]` with the latitude
+/// and longitude (east positive) in degrees and the height in meters
+pub fn add_site_arg(cmd: Command, long_help: impl IntoResettable<StyledStr>) -> Command {
+    cmd.arg(
+        Arg::new("site")
+            .long("site")
+            .long_help(long_help)
+            .allow_hyphen_values(true)
+            .value_parser(|s: &str| {
+                let values: Vec<f64> = s
+                    .split(',')
+                    .map(|v| v.trim().parse::<f64>())
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| format!("Bad site '{s}'"))?;
+                if !(2..=3).contains(&values.len()) || values[0].abs() > 90. {
+                    return Err(format!("Bad site '{s}'"));
+                }
+                Ok(crate::solar_system::Observer::new(
+                    values[0].to_radians(),
+                    values[1].to_radians(),
+                    values.get(2).copied().unwrap_or(0.),
+                ))
+            })
+            .action(ArgAction::Set),
+    )
+}
+
+//fp site
+/// Retrieve the value of the site argument, if specified
+pub fn site(matches: &ArgMatches) -> Option<crate::solar_system::Observer> {
+    matches
+        .get_one::<crate::solar_system::Observer>("site")
+        .copied()
+}
+
+//a Satellite arguments
+//fp add_satellites_arg
+/// Add an optional argument to a clap [Command] to specify a file of
+/// satellite two-line elements
+pub fn add_satellites_arg(cmd: Command, long_help: impl IntoResettable<StyledStr>) -> Command {
+    cmd.arg(
+        Arg::new("satellites")
+            .long("satellites")
+            .long_help(long_help)
+            .action(ArgAction::Set),
+    )
+}
+
+//fp satellites
+/// Retrieve the value of the satellites file, if specified
+pub fn satellites(matches: &ArgMatches) -> Option<&String> {
+    matches.get_one::<String>("satellites")
+}
+
 //a Image arguments
 //fp add_output_arg
 /// Add a required argument to a clap [Command] to specify the output file
@@ -276,6 +713,24 @@ pub fn output(matches: &ArgMatches) -> String {
     matches.get_one::<String>("output").unwrap().to_string()
 }
 
+//fp add_heatmap_arg
+/// Add an optional argument to a clap [Command] to specify a heat-map
+/// output file
+pub fn add_heatmap_arg(cmd: Command, long_help: impl IntoResettable<StyledStr>) -> Command {
+    cmd.arg(
+        Arg::new("heatmap")
+            .long("heatmap")
+            .long_help(long_help)
+            .action(ArgAction::Set),
+    )
+}
+
+//fp heatmap
+/// Retrieve the value of the heat-map argument
+pub fn heatmap(matches: &ArgMatches) -> Option<&String> {
+    matches.get_one::<String>("heatmap")
+}
+
 //fp add_stars_arg
 /// Add a positional argument to a clap [Command] to specify a list of stars
 pub fn add_stars_arg(cmd: Command, long_help: impl IntoResettable<StyledStr>) -> Command {
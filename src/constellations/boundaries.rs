@@ -0,0 +1,341 @@
+//a Imports
+use crate::{Catalog, CatalogIndex, Error, Star, Vec3};
+
+use super::{CONSTELLATIONS, ROMAN_1987};
+
+//a Constants
+/// The Julian centuries from J2000.0 to B1875.0, the equinox of the
+/// IAU constellation boundaries
+const B1875_CENTURIES: f64 = (2405889.258550475 - 2451545.0) / 36525.0;
+
+//a Precession
+//fi precession_angles
+/// Get the IAU 1976 precession angles (zeta, z, theta) in radians from
+/// J2000.0 to a date, given in Julian centuries from J2000.0
+pub(crate) fn precession_angles(t: f64) -> (f64, f64, f64) {
+    let arcsec = std::f64::consts::PI / (180.0 * 3600.0);
+    let zeta = (2306.2181 + (0.30188 + 0.017998 * t) * t) * t * arcsec;
+    let z = (2306.2181 + (1.09468 + 0.018203 * t) * t) * t * arcsec;
+    let theta = (2004.3109 - (0.42665 + 0.041833 * t) * t) * t * arcsec;
+    (zeta, z, theta)
+}
+
+//fi rotate_z
+/// Rotate a vector about the Z axis, increasing its right ascension
+/// by an angle
+pub(crate) fn rotate_z(v: [f64; 3], angle: f64) -> [f64; 3] {
+    let (s, c) = angle.sin_cos();
+    [c * v[0] - s * v[1], s * v[0] + c * v[1], v[2]]
+}
+
+//fi rotate_y
+/// Rotate a vector about the Y axis, moving +X towards +Z by an angle
+pub(crate) fn rotate_y(v: [f64; 3], angle: f64) -> [f64; 3] {
+    let (s, c) = angle.sin_cos();
+    [c * v[0] - s * v[2], v[1], s * v[0] + c * v[2]]
+}
+
+//fp b1875_of_j2000
+/// Precess a unit vector (as used for [Star::vector]) from the J2000
+/// equinox to the B1875 equinox of the IAU constellation boundaries
+pub fn b1875_of_j2000(v: &Vec3) -> Vec3 {
+    let (zeta, z, theta) = precession_angles(B1875_CENTURIES);
+    let v = rotate_z([v[0], v[1], v[2]], zeta);
+    let v = rotate_y(v, theta);
+    rotate_z(v, z).into()
+}
+
+//fp j2000_of_b1875
+/// Precess a unit vector from the B1875 equinox of the IAU
+/// constellation boundaries to the J2000 equinox
+pub fn j2000_of_b1875(v: &Vec3) -> Vec3 {
+    let (zeta, z, theta) = precession_angles(B1875_CENTURIES);
+    let v = rotate_z([v[0], v[1], v[2]], -z);
+    let v = rotate_y(v, -theta);
+    rotate_z(v, -zeta).into()
+}
+
+//fi ra_de_of_vec
+/// Get the right ascension (0 to 2*PI) and declination of a unit
+/// vector
+fn ra_de_of_vec(v: &Vec3) -> (f64, f64) {
+    let ra = v[1].atan2(v[0]).rem_euclid(std::f64::consts::TAU);
+    let de = v[2].clamp(-1., 1.).asin();
+    (ra, de)
+}
+
+//a BoundaryEdge
+//tp BoundaryEdge
+/// A section of the boundary between two constellations, from
+/// [ConstellationBoundaries::edges]
+///
+/// The boundaries run along lines of constant right ascension or
+/// declination for the B1875 equinox; the start and end are given as
+/// (right ascension, declination) in radians for that equinox.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundaryEdge {
+    /// The abbreviations of the constellations on either side
+    pub constellations: [&'static str; 2],
+    /// The start of the edge, B1875
+    pub start: (f64, f64),
+    /// The end of the edge, B1875
+    pub end: (f64, f64),
+}
+
+//ip BoundaryEdge
+impl BoundaryEdge {
+    //mp points
+    /// Get unit vectors (J2000, as for [Star::vector]) along the
+    /// edge, no more than an angle (in radians) apart, including both
+    /// ends
+    pub fn points(&self, max_step: f64) -> Vec<Vec3> {
+        let d_ra = self.end.0 - self.start.0;
+        let d_de = self.end.1 - self.start.1;
+        let length = (d_ra * self.start.1.cos()).abs() + d_de.abs();
+        let n = ((length / max_step).ceil() as usize).max(1);
+        (0..=n)
+            .map(|i| {
+                let f = i as f64 / n as f64;
+                let v = Star::vec_of_ra_de(self.start.0 + f * d_ra, self.start.1 + f * d_de);
+                j2000_of_b1875(&v)
+            })
+            .collect()
+    }
+}
+
+//a ConstellationBoundaries
+//ti BoundaryRow
+/// A row of the boundary table: the band of right ascension (in
+/// radians, B1875) from ra.0 to ra.1, north of the declination de
+/// (and south of any earlier row), is in the constellation
+#[derive(Debug, Clone, Copy)]
+struct BoundaryRow {
+    ra: (f64, f64),
+    de: f64,
+    abbrev: &'static str,
+}
+
+//tp ConstellationBoundaries
+/// The IAU constellation boundaries (Delporte, 1930), which are lines
+/// of constant right ascension and declination for the B1875 equinox
+///
+/// These are held as the table of Roman (1987), 'Identification of a
+/// constellation from a position' (catalog VI/42 at the CDS), whose
+/// lines are the lower and upper right ascension (in hours), the
+/// lower declination (in degrees) and the constellation
+/// abbreviation; the table is in order of decreasing declination, and
+/// the first line that contains a position gives its constellation.
+///
+/// The table is included with this crate as [ROMAN_1987], and
+/// [ConstellationBoundaries::builtin] parses it; other tables (such
+/// as the 'data.dat' file of the CDS catalog) may be read with
+/// [std::str::FromStr].
+#[derive(Debug, Clone, Default)]
+pub struct ConstellationBoundaries {
+    /// The rows of the table, in order
+    rows: Vec<BoundaryRow>,
+}
+
+//ip FromStr for ConstellationBoundaries
+impl std::str::FromStr for ConstellationBoundaries {
+    type Err = Error;
+
+    /// Parse the table of Roman (1987); blank lines and lines
+    /// starting with '#' are ignored
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let mut rows = vec![];
+        for (n, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let parse = |f: &str| f.parse::<f64>().map_err(|_| Error::BadBoundaryLine(n + 1));
+            if fields.len() != 4 {
+                return Err(Error::BadBoundaryLine(n + 1));
+            }
+            let ra_lo = parse(fields[0])?;
+            let ra_hi = parse(fields[1])?;
+            let de = parse(fields[2])?;
+            let abbrev = constellation_abbrev(fields[3])
+                .ok_or_else(|| Error::UnknownConstellation(fields[3].into()))?;
+            rows.push(BoundaryRow {
+                ra: ((ra_lo * 15.0).to_radians(), (ra_hi * 15.0).to_radians()),
+                de: de.to_radians(),
+                abbrev,
+            });
+        }
+        Ok(Self { rows })
+    }
+}
+
+//fi constellation_abbrev
+/// Find the IAU abbreviation of a constellation, ignoring case
+fn constellation_abbrev(abbrev: &str) -> Option<&'static str> {
+    CONSTELLATIONS
+        .iter()
+        .find(|(a, _, _)| a.eq_ignore_ascii_case(abbrev))
+        .map(|(a, _, _)| *a)
+}
+
+//ip ConstellationBoundaries
+impl ConstellationBoundaries {
+    //cp builtin
+    /// Create the IAU constellation boundaries from the table of
+    /// Roman (1987) included with the crate
+    pub fn builtin() -> Self {
+        ROMAN_1987
+            .parse()
+            .expect("The built-in boundary table is valid")
+    }
+
+    //ap len
+    /// Get the number of rows in the boundary table
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    //ap is_empty
+    /// Return true if the boundary table is empty
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    //mp constellation_of_b1875
+    /// Get the abbreviation of the constellation containing a right
+    /// ascension and declination (in radians) for the B1875 equinox
+    ///
+    /// This returns None only if the table does not cover the whole
+    /// sky
+    pub fn constellation_of_b1875(&self, ra: f64, de: f64) -> Option<&'static str> {
+        self.rows
+            .iter()
+            .find(|r| de >= r.de && ra >= r.ra.0 && ra < r.ra.1)
+            .map(|r| r.abbrev)
+    }
+
+    //mp constellation_of
+    /// Get the abbreviation of the constellation containing a unit
+    /// vector (J2000, as for [Star::vector])
+    pub fn constellation_of(&self, v: &Vec3) -> Option<&'static str> {
+        let (ra, de) = ra_de_of_vec(&b1875_of_j2000(v));
+        self.constellation_of_b1875(ra, de)
+    }
+
+    //mp edges
+    /// Get all of the edges between constellations
+    ///
+    /// The table divides the sky into cells, between the right
+    /// ascensions and declinations that it uses; an edge is
+    /// generated wherever neighboring cells are in different
+    /// constellations, and edges continuing along the same line
+    /// between the same constellations are joined
+    pub fn edges(&self) -> Vec<BoundaryEdge> {
+        let mut ras: Vec<f64> = self.rows.iter().flat_map(|r| [r.ra.0, r.ra.1]).collect();
+        ras.push(0.);
+        ras.push(std::f64::consts::TAU);
+        let mut des: Vec<f64> = self.rows.iter().map(|r| r.de).collect();
+        des.push(-std::f64::consts::FRAC_PI_2);
+        des.push(std::f64::consts::FRAC_PI_2);
+        for v in [&mut ras, &mut des] {
+            v.retain(|x| x.is_finite());
+            v.sort_by(|a, b| a.total_cmp(b));
+            v.dedup_by(|a, b| (*a - *b).abs() < 1E-9);
+        }
+        let (n_ra, n_de) = (ras.len() - 1, des.len() - 1);
+        let mut cells = Vec::with_capacity(n_ra * n_de);
+        for i in 0..n_ra {
+            let ra = (ras[i] + ras[i + 1]) / 2.0;
+            for j in 0..n_de {
+                let de = (des[j] + des[j + 1]) / 2.0;
+                cells.push(self.constellation_of_b1875(ra, de));
+            }
+        }
+        let cell = |i: usize, j: usize| cells[(i % n_ra) * n_de + j];
+
+        let mut edges: Vec<BoundaryEdge> = vec![];
+        let mut add_edge = |a: Option<&'static str>,
+                            b: Option<&'static str>,
+                            start: (f64, f64),
+                            end: (f64, f64)| {
+            let (Some(a), Some(b)) = (a, b) else {
+                return;
+            };
+            if a == b {
+                return;
+            }
+            if let Some(last) = edges.last_mut() {
+                if last.constellations == [a, b] && last.end == start {
+                    last.end = end;
+                    return;
+                }
+            }
+            edges.push(BoundaryEdge {
+                constellations: [a, b],
+                start,
+                end,
+            });
+        };
+        // Edges along lines of constant declination, between the
+        // cells south and north of the line
+        for (j, de) in des.iter().enumerate().take(n_de).skip(1) {
+            for i in 0..n_ra {
+                add_edge(cell(i, j - 1), cell(i, j), (ras[i], *de), (ras[i + 1], *de));
+            }
+        }
+        // Edges along lines of constant right ascension, between the
+        // cells west and east of the line
+        for i in 0..n_ra {
+            for j in 0..n_de {
+                add_edge(
+                    cell(i, j),
+                    cell(i + 1, j),
+                    (ras[i + 1], des[j]),
+                    (ras[i + 1], des[j + 1]),
+                );
+            }
+        }
+        edges
+    }
+
+    //mp edges_of
+    /// Get the edges of the boundary of one constellation, given its
+    /// abbreviation (ignoring case)
+    pub fn edges_of(&self, abbrev: &str) -> Vec<BoundaryEdge> {
+        self.edges()
+            .into_iter()
+            .filter(|e| {
+                e.constellations
+                    .iter()
+                    .any(|a| a.eq_ignore_ascii_case(abbrev))
+            })
+            .collect()
+    }
+}
+
+//ip Catalog
+impl Catalog {
+    //mp stars_in_constellation
+    /// Find all the stars that are within a constellation, given its
+    /// abbreviation (ignoring case), such as "Ori"
+    ///
+    /// The catalog filter is also applied to the stars
+    pub fn stars_in_constellation(
+        &self,
+        boundaries: &ConstellationBoundaries,
+        abbrev: &str,
+    ) -> Result<Vec<CatalogIndex>, Error> {
+        let abbrev = constellation_abbrev(abbrev)
+            .ok_or_else(|| Error::UnknownConstellation(abbrev.into()))?;
+        let mut result = vec![];
+        for (i, s) in self.iter_stars().enumerate() {
+            if boundaries.constellation_of(&s.vector) != Some(abbrev) {
+                continue;
+            }
+            if self.filter().call(s, result.len()) {
+                result.push(i.into());
+            }
+        }
+        Ok(result)
+    }
+}
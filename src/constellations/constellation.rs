@@ -0,0 +1,320 @@
+//a Imports
+use geo_nd::Vector;
+use serde::{Deserialize, Serialize};
+
+use crate::{Catalog, CatalogIndex, Error, Star, Vec3};
+
+use super::{CONSTELLATIONS, GENITIVES};
+
+//a Constellation
+//tp Constellation
+/// A constellation (or asterism) of a sky culture, drawn as
+/// polylines between stars given by their Hipparcos numbers
+///
+/// The built-in figures of the 88 IAU constellations are given by
+/// [Constellation::western]; other sky cultures can be read from the
+/// files of Stellarium with [Constellation::read_stellarium_fab] or
+/// [Constellation::read_stellarium_json], or from JSON with serde
+/// (as a `Vec<Constellation>`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Constellation {
+    /// The abbreviation of the constellation, such as "Ori"
+    pub abbrev: String,
+    /// The name of the constellation, such as "Orion"
+    pub name: String,
+    /// The genitive of the name, such as "Orionis"; this may be
+    /// empty
+    #[serde(default)]
+    pub genitive: String,
+    /// The lines of the figure, each a list of the Hipparcos numbers
+    /// of the stars to draw the line through
+    #[serde(default)]
+    pub polylines: Vec<Vec<usize>>,
+    /// The right ascension and declination (in radians) at which to
+    /// place a label, if not at the middle of the stars
+    #[serde(default)]
+    pub label: Option<(f64, f64)>,
+}
+
+//ip Constellation
+impl Constellation {
+    //cp new
+    /// Create a new [Constellation] with no lines
+    pub fn new(abbrev: &str, name: &str) -> Self {
+        Self {
+            abbrev: abbrev.into(),
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    //cp from_ids
+    /// Create a new [Constellation] from a list of Hipparcos numbers,
+    /// where 0 implies a break in the drawing (as used for
+    /// [CONSTELLATIONS])
+    pub fn from_ids(abbrev: &str, name: &str, ids: &[usize]) -> Self {
+        let mut c = Self::new(abbrev, name);
+        for line in ids.split(|id| *id == 0) {
+            c.add_polyline(line);
+        }
+        c
+    }
+
+    //cp with_genitive
+    /// Set the genitive of the name, returning the constellation
+    pub fn with_genitive(mut self, genitive: &str) -> Self {
+        self.genitive = genitive.into();
+        self
+    }
+
+    //cp with_polyline
+    /// Add a polyline to the figure, returning the constellation
+    pub fn with_polyline(mut self, ids: &[usize]) -> Self {
+        self.add_polyline(ids);
+        self
+    }
+
+    //cp with_label
+    /// Set the right ascension and declination (in radians) of the
+    /// label, returning the constellation
+    pub fn with_label(mut self, ra: f64, de: f64) -> Self {
+        self.label = Some((ra, de));
+        self
+    }
+
+    //mp add_polyline
+    /// Add a polyline to the figure; lines of fewer than two stars are
+    /// ignored
+    pub fn add_polyline(&mut self, ids: &[usize]) {
+        if ids.len() > 1 {
+            self.polylines.push(ids.to_vec());
+        }
+    }
+
+    //fp western
+    /// Get the figures of the 88 IAU constellations, from
+    /// [CONSTELLATIONS] and [GENITIVES]
+    pub fn western() -> Vec<Self> {
+        CONSTELLATIONS
+            .iter()
+            .zip(GENITIVES.iter())
+            .map(|((abbrev, name, ids), (_, genitive))| {
+                Self::from_ids(abbrev, name, ids).with_genitive(genitive)
+            })
+            .collect()
+    }
+
+    //ap iter_lines
+    /// Iterate over the line segments of the figure, as pairs of
+    /// Hipparcos numbers
+    pub fn iter_lines(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.polylines
+            .iter()
+            .flat_map(|p| p.windows(2).map(|w| (w[0], w[1])))
+    }
+
+    //ap star_ids
+    /// Get the Hipparcos numbers of the stars of the figure, sorted
+    /// and without duplicates
+    pub fn star_ids(&self) -> Vec<usize> {
+        let mut ids: Vec<usize> = self.polylines.iter().flatten().copied().collect();
+        ids.sort();
+        ids.dedup();
+        ids
+    }
+
+    //mp missing_ids
+    /// Get the Hipparcos numbers of the stars of the figure that are
+    /// not in a catalog; an empty result means the whole figure can
+    /// be drawn
+    ///
+    /// The catalog must have been sorted beforehand
+    pub fn missing_ids(&self, catalog: &Catalog) -> Vec<usize> {
+        self.star_ids()
+            .into_iter()
+            .filter(|id| catalog.find_sorted(*id).is_none())
+            .collect()
+    }
+
+    //mp iter_star_lines
+    /// Iterate over the line segments of the figure as pairs of stars
+    /// of a catalog, skipping any segment with a star that is not in
+    /// the catalog
+    ///
+    /// The catalog must have been sorted beforehand
+    pub fn iter_star_lines<'a>(
+        &'a self,
+        catalog: &'a Catalog,
+    ) -> impl Iterator<Item = (&'a Star, &'a Star)> + 'a {
+        self.iter_lines().filter_map(|(a, b)| {
+            let a: CatalogIndex = catalog.find_sorted(a)?;
+            let b: CatalogIndex = catalog.find_sorted(b)?;
+            Some((&catalog[a], &catalog[b]))
+        })
+    }
+
+    //mp label_vector
+    /// Get the unit vector at which to place a label: the label
+    /// position if there is one, otherwise the middle of the stars of
+    /// the figure that are in a catalog
+    ///
+    /// The catalog must have been sorted beforehand
+    pub fn label_vector(&self, catalog: &Catalog) -> Option<Vec3> {
+        if let Some((ra, de)) = self.label {
+            return Some(Star::vec_of_ra_de(ra, de));
+        }
+        let mut sum: Vec3 = [0., 0., 0.].into();
+        for id in self.star_ids() {
+            if let Some(index) = catalog.find_sorted(id) {
+                sum += catalog[index].vector;
+            }
+        }
+        if sum.length() < 1E-9 {
+            return None;
+        }
+        Some(sum.normalize())
+    }
+
+    //fp read_stellarium_fab
+    /// Read constellations from the 'constellationship.fab' file of a
+    /// Stellarium sky culture
+    ///
+    /// Each line is an abbreviation, a number of line segments, and
+    /// then the pairs of Hipparcos numbers of the segments; segments
+    /// that continue from the previous one are joined into a
+    /// polyline, and lines with the same abbreviation are combined.
+    /// Blank lines and lines starting with '#' are ignored.
+    ///
+    /// Abbreviations of the IAU constellations are given their names
+    /// and genitives; other abbreviations (such as those of the
+    /// Chinese asterisms) are used as the name, which may be set from
+    /// the names file with [Constellation::apply_stellarium_names]
+    pub fn read_stellarium_fab(s: &str) -> Result<Vec<Self>, Error> {
+        let mut result: Vec<Self> = vec![];
+        for (n, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let abbrev = fields.next().unwrap();
+            let num_segments: usize = fields
+                .next()
+                .and_then(|f| f.parse().ok())
+                .ok_or(Error::BadConstellationLine(n + 1))?;
+            let ids = fields
+                .map(|f| f.parse::<usize>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| Error::BadConstellationLine(n + 1))?;
+            if ids.len() != 2 * num_segments {
+                return Err(Error::BadConstellationLine(n + 1));
+            }
+            let c = match result.iter().position(|c| c.abbrev == abbrev) {
+                Some(i) => &mut result[i],
+                None => {
+                    result.push(Self::of_abbrev(abbrev));
+                    result.last_mut().unwrap()
+                }
+            };
+            c.add_segments(ids.chunks(2).map(|p| (p[0], p[1])));
+        }
+        Ok(result)
+    }
+
+    //fp read_stellarium_json
+    /// Read constellations from the 'index.json' file of a Stellarium
+    /// sky culture (as used by Stellarium from version 24)
+    ///
+    /// The abbreviation is the last word of the id of each
+    /// constellation; entries of a line that are not Hipparcos
+    /// numbers break the line
+    pub fn read_stellarium_json(s: &str) -> Result<Vec<Self>, Error> {
+        let bad = |e: &str| Error::BadConstellationJson(e.into());
+        let json: serde_json::Value = serde_json::from_str(s).map_err(|e| bad(&e.to_string()))?;
+        let constellations = json["constellations"]
+            .as_array()
+            .ok_or_else(|| bad("no constellations array"))?;
+        let mut result = vec![];
+        for c in constellations {
+            let id = c["id"]
+                .as_str()
+                .ok_or_else(|| bad("constellation without id"))?;
+            let abbrev = id.split_whitespace().last().unwrap_or(id);
+            let mut constellation = Self::of_abbrev(abbrev);
+            let common_name = &c["common_name"];
+            if let Some(name) = common_name["english"]
+                .as_str()
+                .or(common_name["native"].as_str())
+            {
+                constellation.name = name.into();
+            }
+            for line in c["lines"].as_array().into_iter().flatten() {
+                let mut ids = vec![];
+                for id in line.as_array().into_iter().flatten() {
+                    if let Some(id) = id.as_u64() {
+                        ids.push(id as usize);
+                    } else {
+                        constellation.add_polyline(&ids);
+                        ids.clear();
+                    }
+                }
+                constellation.add_polyline(&ids);
+            }
+            result.push(constellation);
+        }
+        Ok(result)
+    }
+
+    //fp apply_stellarium_names
+    /// Set the names of constellations from the
+    /// 'constellation_names.eng.fab' file of a Stellarium sky culture,
+    /// returning the number of constellations named
+    ///
+    /// Each line is an abbreviation followed by the name in double
+    /// quotes; lines for other abbreviations are ignored
+    pub fn apply_stellarium_names(constellations: &mut [Self], s: &str) -> usize {
+        let mut count = 0;
+        for line in s.lines() {
+            let line = line.trim();
+            let Some((abbrev, rest)) = line.split_once(char::is_whitespace) else {
+                continue;
+            };
+            let mut quoted = rest.split('"');
+            let (Some(_), Some(name)) = (quoted.next(), quoted.next()) else {
+                continue;
+            };
+            for c in constellations.iter_mut().filter(|c| c.abbrev == abbrev) {
+                c.name = name.into();
+                count += 1;
+            }
+        }
+        count
+    }
+
+    //fi of_abbrev
+    /// Create a new [Constellation] for an abbreviation, with the name
+    /// and genitive of the IAU constellation if there is one
+    fn of_abbrev(abbrev: &str) -> Self {
+        let c = Self::new(abbrev, abbrev);
+        match CONSTELLATIONS.iter().position(|(a, _, _)| *a == abbrev) {
+            Some(i) => Self {
+                name: CONSTELLATIONS[i].1.into(),
+                genitive: GENITIVES[i].1.into(),
+                ..c
+            },
+            None => c,
+        }
+    }
+
+    //mi add_segments
+    /// Add line segments to the figure, extending the last polyline
+    /// when a segment continues from its end
+    fn add_segments<I: Iterator<Item = (usize, usize)>>(&mut self, segments: I) {
+        for (a, b) in segments {
+            match self.polylines.last_mut() {
+                Some(p) if p.last() == Some(&a) => p.push(b),
+                _ => self.polylines.push(vec![a, b]),
+            }
+        }
+    }
+}
@@ -1,4 +1,23 @@
-//! Constants that represent some common constellations, using Hipparcos numbers for the stars
+//! Constants that represent the constellations, using Hipparcos numbers for the stars
+//!
+//! The IAU constellation boundaries are provided by
+//! [ConstellationBoundaries], from the table of Roman (1987) which is
+//! included as [ROMAN_1987]
+//!
+//! The figures of constellations are provided as [Constellation]s,
+//! either from these constants (with [Constellation::western]) or read
+//! from the files of a Stellarium sky culture
+
+mod boundaries;
+mod constellation;
+pub use boundaries::{b1875_of_j2000, j2000_of_b1875, BoundaryEdge, ConstellationBoundaries};
+pub(crate) use boundaries::{precession_angles, rotate_y, rotate_z};
+pub use constellation::Constellation;
+
+/// The IAU constellation boundaries as the table of Roman (1987), as
+/// text for [ConstellationBoundaries] (see
+/// [ConstellationBoundaries::builtin])
+pub const ROMAN_1987: &str = include_str!("roman_1987.txt");
 
 /// Constellations in the norhern hemisphere (Hipparcos numbers)
 ///
@@ -146,9 +165,9 @@ pub const NORTHERN_HEMISPHERE: &[(&str, &[usize])] = &[
     (
         "Hercules",
         &[
-            88794, 87933, 86974, 85693, 83207, 84380, 851122, 87808, 86414, 0, //
+            88794, 87933, 86974, 85693, 83207, 84380, 85112, 87808, 86414, 0, //
             84379, 85693, 0, //
-            80170, 80816, 150680, 81833, 81126, 79992, 79101, 77760, 0, //
+            80170, 80816, 81693, 81833, 81126, 79992, 79101, 77760, 0, //
             84380, 81833, 0, //
             83207, 81693,
         ],
@@ -164,9 +183,511 @@ pub const NORTHERN_HEMISPHERE: &[(&str, &[usize])] = &[
     (
         "Cancer",
         &[
-            43105, 42806, 42911, 44066, 0, //
+            43103, 42806, 42911, 44066, 0, //
             40843, 42806, 0, //
             40526, 42911, 0,
         ],
     ),
 ];
+
+/// Stick figures for all 88 IAU constellations, as (abbreviation,
+/// name, Hipparcos numbers), in order of name
+///
+/// As with [NORTHERN_HEMISPHERE], 0 implies a break in a drawing.
+/// Every star is in the Hipparcos catalog and brighter than
+/// magnitude 6.5 (Mira, a variable, is the faintest).
+pub const CONSTELLATIONS: &[(&str, &str, &[usize])] = &[
+    (
+        "And",
+        "Andromeda",
+        &[677, 3092, 5447, 9640, 0, 3881, 4436, 5447, 0, 3092, 2912],
+    ),
+    ("Ant", "Antlia", &[46515, 51172, 53502]),
+    ("Aps", "Apus", &[72370, 80047, 81852, 81065]),
+    (
+        "Aqr",
+        "Aquarius",
+        &[
+            115438, 114855, 112961, 111497, 110960, 110395, 109074, 106278, 102618, 0, 114341,
+            113136, 112716, 111123, 110003, 109074, 0, 109139, 109472, 110003,
+        ],
+    ),
+    (
+        "Aql",
+        "Aquila",
+        &[
+            97278, 97649, 98036, 97804, 99473, 0, 97649, 95501, 93805, 0, 95501, 93747, 93244,
+        ],
+    ),
+    (
+        "Ara",
+        "Ara",
+        &[
+            88714, 85792, 83153, 83081, 82363, 85727, 85267, 85258, 85792,
+        ],
+    ),
+    ("Ari", "Aries", &[13209, 9884, 8903, 8832]),
+    (
+        "Aur",
+        "Auriga",
+        &[24608, 23453, 23015, 25428, 28380, 28360, 24608],
+    ),
+    (
+        "Boo",
+        "Bootes",
+        &[
+            71795, 69673, 74666, 73555, 71075, 71053, 69673, 67927, 67459,
+        ],
+    ),
+    ("Cae", "Caelum", &[21060, 21770, 21861, 23595]),
+    (
+        "Cam",
+        "Camelopardalis",
+        &[25110, 17959, 16228, 18505, 22783, 17959],
+    ),
+    (
+        "Cnc",
+        "Cancer",
+        &[43103, 42806, 42911, 44066, 0, 40843, 42806, 0, 40526, 42911],
+    ),
+    ("CVn", "Canes Venatici", &[63125, 61317]),
+    (
+        "CMa",
+        "Canis Major",
+        &[
+            33160, 34045, 33347, 32349, 30324, 0, 32349, 33977, 34444, 35904, 0, 34444, 33579,
+            32759, 0, 30324, 30122,
+        ],
+    ),
+    ("CMi", "Canis Minor", &[37279, 36188]),
+    (
+        "Cap",
+        "Capricornus",
+        &[
+            107556, 106985, 105515, 104139, 100345, 100027, 0, 105515, 105881, 104139, 102978, 0,
+            100345, 102485,
+        ],
+    ),
+    (
+        "Car",
+        "Carina",
+        &[
+            30438, 38827, 41037, 45556, 50371, 51576, 52419, 50099, 45238, 48002, 45556,
+        ],
+    ),
+    ("Cas", "Cassiopeia", &[746, 3179, 4427, 6686, 8886]),
+    (
+        "Cen",
+        "Centaurus",
+        &[
+            71683, 68702, 66657, 61932, 60823, 59196, 55425, 0, 66657, 68002, 71352, 73334, 0,
+            68002, 67472, 67464, 68933, 65109, 0, 60823, 56561,
+        ],
+    ),
+    (
+        "Cep",
+        "Cepheus",
+        &[106032, 112724, 116727, 106032, 105199, 109492, 112724],
+    ),
+    (
+        "Cet",
+        "Cetus",
+        &[
+            14135, 13954, 12828, 11484, 12093, 12706, 14135, 0, 12706, 12387, 10826, 8645, 6537,
+            5364, 3419, 1562, 0, 3419, 8102, 8645,
+        ],
+    ),
+    ("Cha", "Chamaeleon", &[40702, 51839, 52633, 60000, 51839]),
+    ("Cir", "Circinus", &[74824, 71908, 75323]),
+    (
+        "Col",
+        "Columba",
+        &[25859, 26634, 27628, 28199, 30277, 0, 27628, 28328],
+    ),
+    ("Com", "Coma Berenices", &[64241, 64394, 60742]),
+    (
+        "CrA",
+        "Corona Australis",
+        &[90982, 93542, 94005, 94160, 94114, 93825, 93174],
+    ),
+    (
+        "CrB",
+        "Corona Borealis",
+        &[78493, 78159, 77512, 76267, 75695, 76127],
+    ),
+    ("Crv", "Corvus", &[59199, 59316, 59803, 60965, 61359, 59316]),
+    (
+        "Crt",
+        "Crater",
+        &[
+            53740, 55282, 55705, 54682, 53740, 0, 55282, 55687, 56633, 0, 55705, 57283, 58188,
+        ],
+    ),
+    ("Cru", "Crux", &[60718, 61084, 0, 62434, 59747]),
+    (
+        "Cyg",
+        "Cygnus",
+        &[
+            102098, 100453, 98110, 95947, 0, 107310, 104732, 102488, 100453, 97165, 95853, 94779,
+        ],
+    ),
+    (
+        "Del",
+        "Delphinus",
+        &[101769, 102281, 102532, 101958, 101769, 101421],
+    ),
+    ("Dor", "Dorado", &[19893, 21281, 23693, 26069, 27100]),
+    (
+        "Dra",
+        "Draco",
+        &[
+            56211, 61281, 68756, 75458, 78527, 80331, 83895, 89937, 94648, 97433, 94376, 87585,
+            87833, 85670, 85819, 87585,
+        ],
+    ),
+    ("Equ", "Equuleus", &[104987, 105570, 104858, 104521, 104987]),
+    (
+        "Eri",
+        "Eridanus",
+        &[
+            23875, 22109, 21444, 19587, 18543, 17593, 17378, 16537, 13701, 12843, 13288, 14146,
+            15474, 16611, 17651, 18673, 21248, 21393, 20535, 20042, 17797, 13847, 12486, 11407,
+            10602, 9007, 7588,
+        ],
+    ),
+    ("For", "Fornax", &[14879, 13147, 9677]),
+    (
+        "Gem",
+        "Gemini",
+        &[
+            32362, 35350, 35550, 36962, 36046, 34693, 32246, 30883, 0, 37740, 36962, 37826, 0,
+            36850, 34693, 33018, 0, 35550, 34088, 31681, 0, 32246, 30343, 28734,
+        ],
+    ),
+    (
+        "Gru",
+        "Grus",
+        &[
+            108085, 109111, 109908, 110997, 112122, 112623, 113638, 0, 109268, 112122, 114421,
+            114131,
+        ],
+    ),
+    (
+        "Her",
+        "Hercules",
+        &[
+            88794, 87933, 86974, 85693, 83207, 84380, 85112, 87808, 86414, 0, 84379, 85693, 0,
+            80170, 80816, 81693, 81833, 81126, 79992, 79101, 77760, 0, 84380, 81833, 0, 83207,
+            81693,
+        ],
+    ),
+    (
+        "Hor",
+        "Horologium",
+        &[19747, 12653, 12225, 12484, 14240, 13884],
+    ),
+    (
+        "Hya",
+        "Hydra",
+        &[
+            42313, 42402, 42799, 43234, 43109, 42313, 0, 43109, 43813, 45336, 47431, 46390, 48356,
+            49841, 51069, 52943, 56343, 64962, 68895, 72571,
+        ],
+    ),
+    ("Hyi", "Hydrus", &[2021, 9236, 11001, 12394, 17678, 2021]),
+    ("Ind", "Indus", &[101772, 105319, 103227, 0, 105319, 108431]),
+    (
+        "Lac",
+        "Lacerta",
+        &[109937, 111104, 111022, 111169, 110538, 110609, 111022],
+    ),
+    (
+        "Leo",
+        "Leo",
+        &[
+            49669, 54879, 57632, 54872, 50583, 49583, 49669, 0, 50583, 50335, 48455, 47908, 0,
+            54872, 54879,
+        ],
+    ),
+    ("LMi", "Leo Minor", &[49593, 51233, 53229]),
+    (
+        "Lep",
+        "Lepus",
+        &[
+            24327, 24305, 25985, 27288, 28103, 0, 24305, 23685, 25606, 27072, 27654, 28103,
+        ],
+    ),
+    (
+        "Lib",
+        "Libra",
+        &[73714, 72622, 74785, 76333, 76470, 0, 72622, 76333],
+    ),
+    (
+        "Lup",
+        "Lupus",
+        &[
+            74395, 71860, 73273, 75141, 75177, 0, 75141, 76297, 78384, 0, 76297, 75264, 74376,
+            74395,
+        ],
+    ),
+    ("Lyn", "Lynx", &[45860, 45688, 41075, 36145, 33449, 30060]),
+    ("Lyr", "Lyra", &[91262, 91971, 92791, 93194, 92420, 91971]),
+    ("Men", "Mensa", &[29271, 25918, 22871, 23467]),
+    ("Mic", "Microscopium", &[102831, 103738, 105140, 105382]),
+    (
+        "Mon",
+        "Monoceros",
+        &[
+            29651, 30867, 34769, 37447, 0, 34769, 39863, 0, 34769, 32578, 31216,
+        ],
+    ),
+    (
+        "Mus",
+        "Musca",
+        &[57363, 59929, 61585, 62322, 63613, 61199, 61585],
+    ),
+    ("Nor", "Norma", &[80000, 80582, 78914, 78639, 80000]),
+    ("Oct", "Octans", &[107089, 112405, 70638, 107089]),
+    (
+        "Oph",
+        "Ophiuchus",
+        &[
+            86032, 83000, 79593, 79882, 81377, 84012, 86742, 86032, 0, 84012, 88048, 0, 84012,
+            84970,
+        ],
+    ),
+    (
+        "Ori",
+        "Orion",
+        &[
+            27366, 26727, 27989, 26207, 25336, 25930, 26311, 24436, 0, 26727, 26311, 25930, 0,
+            27989, 25336,
+        ],
+    ),
+    (
+        "Pav",
+        "Pavo",
+        &[
+            100751, 102395, 105858, 0, 102395, 99240, 92609, 90098, 88866, 86929, 0, 99240, 98495,
+            91792, 93015, 99240,
+        ],
+    ),
+    (
+        "Peg",
+        "Pegasus",
+        &[
+            107315, 109427, 112029, 113963, 1067, 677, 0, 109410, 112158, 113881, 677, 0, 107354,
+            109176, 112440, 112748, 113881, 113963,
+        ],
+    ),
+    (
+        "Per",
+        "Perseus",
+        &[
+            13268, 14328, 15863, 17358, 18532, 18614, 18246, 17448, 0, 13254, 14354, 14576, 15863,
+        ],
+    ),
+    (
+        "Phe",
+        "Phoenix",
+        &[765, 2072, 2081, 5165, 6867, 7083, 5348, 5165],
+    ),
+    ("Pic", "Pictor", &[27321, 27530, 32607]),
+    (
+        "Psc",
+        "Pisces",
+        &[
+            5742, 6193, 4889, 5742, 7097, 8198, 9487, 8833, 7884, 7007, 4906, 1645, 118268, 116771,
+            115830, 114971, 115738, 116928, 116771,
+        ],
+    ),
+    (
+        "PsA",
+        "Piscis Austrinus",
+        &[
+            107380, 109285, 111188, 112948, 113246, 113368, 111954, 107608, 107380,
+        ],
+    ),
+    (
+        "Pup",
+        "Puppis",
+        &[
+            38170, 39757, 39429, 37819, 35264, 31685, 32768, 36377, 39429,
+        ],
+    ),
+    ("Pyx", "Pyxis", &[42515, 42828, 43409]),
+    ("Ret", "Reticulum", &[19780, 17440, 18597, 19921, 19780]),
+    (
+        "Sge",
+        "Sagitta",
+        &[98920, 98337, 97365, 96837, 0, 97365, 96757],
+    ),
+    (
+        "Sgr",
+        "Sagittarius",
+        &[
+            88635, 89931, 90185, 93506, 92041, 90496, 89931, 0, 90496, 89341, 0, 92041, 92855,
+            93864, 93506, 0, 92855, 93085, 93683, 94141, 95168, 0, 90185, 89642,
+        ],
+    ),
+    (
+        "Sco",
+        "Scorpius",
+        &[
+            79374, 78820, 78401, 78265, 78104, 0, 78401, 80112, 80763, 81266, 82396, 82514, 82729,
+            84143, 86228, 87073, 86670, 85927, 85696,
+        ],
+    ),
+    ("Scl", "Sculptor", &[4577, 117452, 115102, 116231]),
+    ("Sct", "Scutum", &[90595, 91117, 92175, 91726, 90595]),
+    (
+        "Ser",
+        "Serpens",
+        &[
+            77516, 77622, 77070, 76276, 77233, 76852, 77450, 78072, 77233, 0, 84880, 86263, 89962,
+            92946,
+        ],
+    ),
+    ("Sex", "Sextans", &[48437, 49641, 51437]),
+    (
+        "Tau",
+        "Taurus",
+        &[
+            15900, 18724, 20205, 20455, 20889, 21881, 25428, 0, 26451, 21421, 20894, 20205, 0,
+            21421, 20889, 20455, 17847,
+        ],
+    ),
+    ("Tel", "Telescopium", &[89112, 90422, 90568]),
+    ("Tri", "Triangulum", &[10064, 8796, 10670, 10064]),
+    ("TrA", "Triangulum Australe", &[82273, 77952, 74946, 82273]),
+    (
+        "Tuc",
+        "Tucana",
+        &[110130, 114996, 2484, 1599, 118322, 110838, 110130],
+    ),
+    (
+        "UMa",
+        "Ursa Major",
+        &[
+            59774, 54061, 53910, 58001, 59774, 62956, 65378, 67301, 0, 54061, 46733, 41704, 48319,
+            53910, 0, 48319, 46853, 44471, 0, 58001, 54539, 50801, 0, 54539, 50372,
+        ],
+    ),
+    (
+        "UMi",
+        "Ursa Minor",
+        &[11767, 85822, 82080, 77055, 72607, 75097, 79822],
+    ),
+    (
+        "Vel",
+        "Vela",
+        &[39953, 42913, 45941, 48774, 52727, 46651, 44816, 39953],
+    ),
+    (
+        "Vir",
+        "Virgo",
+        &[
+            57380, 57757, 60129, 61941, 63090, 63608, 0, 61941, 64238, 65474, 0, 65474, 66249,
+            68520, 72220, 0, 66249, 69701, 71957,
+        ],
+    ),
+    (
+        "Vol",
+        "Volans",
+        &[44382, 41312, 39794, 35228, 34481, 37504, 39794],
+    ),
+    ("Vul", "Vulpecula", &[94703, 95771, 97886]),
+];
+
+/// The genitives of the names of the 88 IAU constellations, as
+/// (abbreviation, genitive), in the same order as [CONSTELLATIONS]
+pub const GENITIVES: &[(&str, &str)] = &[
+    ("And", "Andromedae"),
+    ("Ant", "Antliae"),
+    ("Aps", "Apodis"),
+    ("Aqr", "Aquarii"),
+    ("Aql", "Aquilae"),
+    ("Ara", "Arae"),
+    ("Ari", "Arietis"),
+    ("Aur", "Aurigae"),
+    ("Boo", "Bootis"),
+    ("Cae", "Caeli"),
+    ("Cam", "Camelopardalis"),
+    ("Cnc", "Cancri"),
+    ("CVn", "Canum Venaticorum"),
+    ("CMa", "Canis Majoris"),
+    ("CMi", "Canis Minoris"),
+    ("Cap", "Capricorni"),
+    ("Car", "Carinae"),
+    ("Cas", "Cassiopeiae"),
+    ("Cen", "Centauri"),
+    ("Cep", "Cephei"),
+    ("Cet", "Ceti"),
+    ("Cha", "Chamaeleontis"),
+    ("Cir", "Circini"),
+    ("Col", "Columbae"),
+    ("Com", "Comae Berenices"),
+    ("CrA", "Coronae Australis"),
+    ("CrB", "Coronae Borealis"),
+    ("Crv", "Corvi"),
+    ("Crt", "Crateris"),
+    ("Cru", "Crucis"),
+    ("Cyg", "Cygni"),
+    ("Del", "Delphini"),
+    ("Dor", "Doradus"),
+    ("Dra", "Draconis"),
+    ("Equ", "Equulei"),
+    ("Eri", "Eridani"),
+    ("For", "Fornacis"),
+    ("Gem", "Geminorum"),
+    ("Gru", "Gruis"),
+    ("Her", "Herculis"),
+    ("Hor", "Horologii"),
+    ("Hya", "Hydrae"),
+    ("Hyi", "Hydri"),
+    ("Ind", "Indi"),
+    ("Lac", "Lacertae"),
+    ("Leo", "Leonis"),
+    ("LMi", "Leonis Minoris"),
+    ("Lep", "Leporis"),
+    ("Lib", "Librae"),
+    ("Lup", "Lupi"),
+    ("Lyn", "Lyncis"),
+    ("Lyr", "Lyrae"),
+    ("Men", "Mensae"),
+    ("Mic", "Microscopii"),
+    ("Mon", "Monocerotis"),
+    ("Mus", "Muscae"),
+    ("Nor", "Normae"),
+    ("Oct", "Octantis"),
+    ("Oph", "Ophiuchi"),
+    ("Ori", "Orionis"),
+    ("Pav", "Pavonis"),
+    ("Peg", "Pegasi"),
+    ("Per", "Persei"),
+    ("Phe", "Phoenicis"),
+    ("Pic", "Pictoris"),
+    ("Psc", "Piscium"),
+    ("PsA", "Piscis Austrini"),
+    ("Pup", "Puppis"),
+    ("Pyx", "Pyxidis"),
+    ("Ret", "Reticuli"),
+    ("Sge", "Sagittae"),
+    ("Sgr", "Sagittarii"),
+    ("Sco", "Scorpii"),
+    ("Scl", "Sculptoris"),
+    ("Sct", "Scuti"),
+    ("Ser", "Serpentis"),
+    ("Sex", "Sextantis"),
+    ("Tau", "Tauri"),
+    ("Tel", "Telescopii"),
+    ("Tri", "Trianguli"),
+    ("TrA", "Trianguli Australis"),
+    ("Tuc", "Tucanae"),
+    ("UMa", "Ursae Majoris"),
+    ("UMi", "Ursae Minoris"),
+    ("Vel", "Velorum"),
+    ("Vir", "Virginis"),
+    ("Vol", "Volantis"),
+    ("Vul", "Vulpeculae"),
+];
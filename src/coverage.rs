@@ -0,0 +1,227 @@
+//a Imports
+use geo_nd::Quaternion;
+
+use crate::{Catalog, Error, HealpixIndex, Quat, Vec3};
+
+//a Constants
+/// The golden ratio conjugate, used to spread the roll of the field
+/// of view evenly over the samples
+const GOLDEN_RATIO_CONJUGATE: f64 = 0.618_033_988_749_895;
+
+//a FieldOfView
+//tp FieldOfView
+/// The field of view of a sensor (such as a camera or star tracker)
+/// for [Catalog::field_coverage]
+///
+/// The angles are the full angles across the field of view, in
+/// radians; a rectangular field of view is that of a rectilinear lens
+///
+/// The angles must be more than 0 and less than 180 degrees (as
+/// checked by [FieldOfView::validate]); a rectilinear lens cannot
+/// cover 180 degrees
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldOfView {
+    /// A circular field of view, given its diameter
+    Circular(f64),
+    /// A rectangular field of view, given its width and height
+    Rectangular(f64, f64),
+}
+
+//ip FieldOfView
+impl FieldOfView {
+    //mp validate
+    /// Check that the angles of the field of view are more than 0
+    /// and less than 180 degrees
+    pub fn validate(&self) -> Result<(), Error> {
+        let valid = |a: &f64| *a > 0. && *a < std::f64::consts::PI;
+        let ok = match self {
+            Self::Circular(d) => valid(d),
+            Self::Rectangular(w, h) => valid(w) && valid(h),
+        };
+        if ok {
+            Ok(())
+        } else {
+            Err(Error::BadFieldOfView)
+        }
+    }
+
+    //ap max_angle
+    /// Get the largest angle from the center of the field of view to
+    /// its edge (for a rectangle, to a corner)
+    pub fn max_angle(&self) -> f64 {
+        match self {
+            Self::Circular(d) => d / 2.0,
+            Self::Rectangular(w, h) => (w / 2.0).tan().hypot((h / 2.0).tan()).atan(),
+        }
+    }
+
+    //mp contains
+    /// Return true if a unit vector is within the field of view for a
+    /// view orientation
+    ///
+    /// The orientation is as used for `ImageView` (with the image
+    /// feature), such as from `Quat::look_at(direction, up)`; the view
+    /// direction maps to -Z
+    pub fn contains(&self, orient: &Quat, v: &Vec3) -> bool {
+        let v = orient.apply3(v);
+        if v[2] >= 0. {
+            return false;
+        }
+        match self {
+            Self::Circular(d) => -v[2] >= (d / 2.0).cos(),
+            Self::Rectangular(w, h) => {
+                (v[0] / v[2]).abs() <= (w / 2.0).tan() && (v[1] / v[2]).abs() <= (h / 2.0).tan()
+            }
+        }
+    }
+}
+
+//a FieldCoverage
+//tp FieldCoverage
+/// The number of stars visible in a [FieldOfView] for orientations
+/// sampled uniformly over the whole sky, from
+/// [Catalog::field_coverage]
+///
+/// The view directions are the centers of the pixels of a
+/// [HealpixIndex], which all have the same area; the roll of the
+/// view about each direction is spread evenly over all angles.
+/// Statistics over the samples are hence statistics over the sky.
+#[derive(Debug, Clone)]
+pub struct FieldCoverage {
+    /// The field of view
+    fov: FieldOfView,
+    /// The limiting magnitude
+    magnitude: f32,
+    /// The pixels providing the view directions
+    index: HealpixIndex,
+    /// The number of stars visible for each pixel
+    counts: Vec<usize>,
+    /// The counts sorted, for the statistics
+    sorted_counts: Vec<usize>,
+}
+
+//ip FieldCoverage
+impl FieldCoverage {
+    //fp orient_of_sample
+    /// Get the view orientation used for a sample (a pixel of a
+    /// [HealpixIndex])
+    ///
+    /// This maps the center of the pixel to -Z, then rolls the view
+    /// about Z by an angle that depends on the pixel
+    pub fn orient_of_sample(index: &HealpixIndex, pixel: u64) -> Quat {
+        let direction = index.vector_of_pixel(pixel);
+        let to_view = Quat::rotation_of_vec_to_vec(&direction, &[0., 0., -1.].into());
+        let roll = (pixel as f64 * GOLDEN_RATIO_CONJUGATE).fract() * std::f64::consts::TAU;
+        Quat::of_axis_angle(&[0., 0., 1.].into(), roll) * to_view
+    }
+
+    //ap fov
+    /// Get the field of view
+    pub fn fov(&self) -> FieldOfView {
+        self.fov
+    }
+
+    //ap magnitude
+    /// Get the limiting magnitude
+    pub fn magnitude(&self) -> f32 {
+        self.magnitude
+    }
+
+    //ap index
+    /// Get the [HealpixIndex] whose pixels are the samples
+    pub fn index(&self) -> &HealpixIndex {
+        &self.index
+    }
+
+    //ap counts
+    /// Get the number of stars visible for each sample, indexed by
+    /// pixel
+    pub fn counts(&self) -> &[usize] {
+        &self.counts
+    }
+
+    //mp count_at
+    /// Get the number of stars visible for the sample nearest a
+    /// direction
+    pub fn count_at(&self, v: &Vec3) -> usize {
+        self.counts[self.index.pixel_of_vector(v) as usize]
+    }
+
+    //ap min
+    /// Get the minimum number of stars visible
+    pub fn min(&self) -> usize {
+        self.sorted_counts[0]
+    }
+
+    //ap max
+    /// Get the maximum number of stars visible
+    pub fn max(&self) -> usize {
+        self.sorted_counts[self.sorted_counts.len() - 1]
+    }
+
+    //ap median
+    /// Get the median number of stars visible
+    pub fn median(&self) -> usize {
+        self.sorted_counts[self.sorted_counts.len() / 2]
+    }
+
+    //ap mean
+    /// Get the mean number of stars visible
+    pub fn mean(&self) -> f64 {
+        self.sorted_counts.iter().sum::<usize>() as f64 / self.sorted_counts.len() as f64
+    }
+
+    //mp fraction_below
+    /// Get the fraction of the sky (from 0 to 1) where fewer than *n*
+    /// stars are visible
+    pub fn fraction_below(&self, n: usize) -> f64 {
+        self.sorted_counts.partition_point(|c| *c < n) as f64 / self.sorted_counts.len() as f64
+    }
+}
+
+//ip Catalog
+impl Catalog {
+    //mp field_coverage
+    /// Determine the number of stars brighter than a magnitude that
+    /// are visible in a field of view, for view orientations sampled
+    /// uniformly over the sky
+    ///
+    /// The samples are the 12 * 4^order pixels of a [HealpixIndex]
+    /// (order 5 gives 12,288 samples, about 1.8 degrees apart). The
+    /// catalog filter is also applied to the stars.
+    ///
+    /// Returns an error if the field of view is not valid (see
+    /// [FieldOfView::validate])
+    ///
+    /// Needs data to have been derived for the Catalog
+    pub fn field_coverage(
+        &self,
+        fov: FieldOfView,
+        magnitude: f32,
+        order: u32,
+    ) -> Result<FieldCoverage, Error> {
+        fov.validate()?;
+        let index = HealpixIndex::new(order);
+        let max_angle = fov.max_angle();
+        let mut counts = Vec::with_capacity(index.num_pixels() as usize);
+        for pixel in 0..index.num_pixels() {
+            let direction = index.vector_of_pixel(pixel);
+            let orient = FieldCoverage::orient_of_sample(&index, pixel);
+            let count = self
+                .find_stars_around_brighter_than(&direction, max_angle, magnitude)
+                .into_iter()
+                .filter(|i| fov.contains(&orient, &self[*i].vector))
+                .count();
+            counts.push(count);
+        }
+        let mut sorted_counts = counts.clone();
+        sorted_counts.sort();
+        Ok(FieldCoverage {
+            fov,
+            magnitude,
+            index,
+            counts,
+            sorted_counts,
+        })
+    }
+}
@@ -0,0 +1,154 @@
+//a Imports
+use std::collections::HashMap;
+
+use geo_nd::Vector;
+
+use crate::{Error, SpatialIndex, SubcubeIndex, Vec3};
+
+use super::{DeepSkyObject, CALDWELL, MESSIER, NGC_IC};
+
+//a DeepSkyCatalog
+//tp DeepSkyCatalog
+/// A catalog of [DeepSkyObject]s, placed into the [crate::Subcube]s
+/// of a [SubcubeIndex] for spatial searching
+///
+/// The built-in objects are given by [DeepSkyCatalog::builtin]; other
+/// lists (in the same text format) are read with [std::str::FromStr]
+#[derive(Debug, Clone)]
+pub struct DeepSkyCatalog {
+    /// The objects, in the order given
+    objects: Vec<DeepSkyObject>,
+    /// The subcube index used to divide up the sphere
+    index: SubcubeIndex,
+    /// Object indices within each occupied subcube, keyed by subcube
+    /// id
+    subcubes: HashMap<usize, Vec<usize>>,
+    /// The largest radius of any object, in radians
+    max_radius: f64,
+}
+
+//ip Default for DeepSkyCatalog
+impl Default for DeepSkyCatalog {
+    fn default() -> Self {
+        Self::new(vec![])
+    }
+}
+
+//ip FromStr for DeepSkyCatalog
+impl std::str::FromStr for DeepSkyCatalog {
+    type Err = Error;
+
+    /// Parse a text table in the format of [MESSIER]; blank lines and
+    /// lines starting with '#' are ignored
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let mut objects = vec![];
+        for (n, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            objects.push(DeepSkyObject::parse_line(line).ok_or(Error::BadDeepSkyLine(n + 1))?);
+        }
+        Ok(Self::new(objects))
+    }
+}
+
+//ip Index<usize> for DeepSkyCatalog
+impl std::ops::Index<usize> for DeepSkyCatalog {
+    type Output = DeepSkyObject;
+    fn index(&self, index: usize) -> &DeepSkyObject {
+        &self.objects[index]
+    }
+}
+
+//ip DeepSkyCatalog
+impl DeepSkyCatalog {
+    //cp new
+    /// Create a new [DeepSkyCatalog] from a list of objects
+    pub fn new(objects: Vec<DeepSkyObject>) -> Self {
+        let index = SubcubeIndex::for_num_stars(objects.len());
+        let mut subcubes: HashMap<usize, Vec<usize>> = HashMap::new();
+        let mut max_radius: f64 = 0.;
+        for (i, o) in objects.iter().enumerate() {
+            let cell = index.cell_id(index.cell_of_vector(&o.vector));
+            subcubes.entry(cell).or_default().push(i);
+            max_radius = max_radius.max(o.radius());
+        }
+        Self {
+            objects,
+            index,
+            subcubes,
+            max_radius,
+        }
+    }
+
+    //cp builtin
+    /// Create a [DeepSkyCatalog] of the built-in objects: the Messier
+    /// catalog, the Caldwell catalog and then the bright NGC and IC
+    /// objects
+    pub fn builtin() -> Self {
+        let objects = [MESSIER, CALDWELL, NGC_IC]
+            .iter()
+            .flat_map(|s| s.parse::<Self>().unwrap().objects)
+            .collect();
+        Self::new(objects)
+    }
+
+    //ap len
+    /// Get the number of objects in the catalog
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    //ap is_empty
+    /// Return true if the catalog has no objects
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    //ap objects
+    /// Get the objects of the catalog
+    pub fn objects(&self) -> &[DeepSkyObject] {
+        &self.objects
+    }
+
+    //mp iter
+    /// Iterate over the objects of the catalog
+    pub fn iter(&self) -> impl Iterator<Item = &DeepSkyObject> {
+        self.objects.iter()
+    }
+
+    //mp find_designation
+    /// Find an object by its designation or other designation,
+    /// ignoring case and spaces (so "m31" and "NGC 224" both find
+    /// "M 31")
+    pub fn find_designation(&self, designation: &str) -> Option<usize> {
+        self.objects
+            .iter()
+            .position(|o| o.has_designation(designation))
+    }
+
+    //mp find_around
+    /// Find the objects that are (at least partly) within an angle
+    /// (in radians) of a unit vector, in the order of the catalog
+    ///
+    /// An object is included if its center is within the angle plus
+    /// its radius
+    pub fn find_around(&self, v: &Vec3, angle: f64) -> Vec<usize> {
+        let mut result = vec![];
+        for cell in self.index.cells_in_cone(v, angle + self.max_radius) {
+            let Some(objects) = self.subcubes.get(&self.index.cell_id(cell)) else {
+                continue;
+            };
+            for i in objects {
+                let o = &self.objects[*i];
+                if o.vector.dot(v) >= (angle + o.radius()).min(std::f64::consts::PI).cos() {
+                    result.push(*i);
+                }
+            }
+        }
+        result.sort();
+        result.dedup();
+        result
+    }
+}
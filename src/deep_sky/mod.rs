@@ -0,0 +1,38 @@
+//! Deep-sky objects - galaxies, clusters and nebulae
+//!
+//! A [DeepSkyObject] has a catalog designation, a [DeepSkyKind], a
+//! position, a magnitude and an angular size and position angle; a
+//! [DeepSkyCatalog] holds a set of these, indexed with a
+//! [crate::SubcubeIndex] for spatial searching.
+//!
+//! The Messier and Caldwell catalogs, and some bright NGC and IC
+//! objects that are in neither, are included as text tables
+//! ([MESSIER], [CALDWELL] and [NGC_IC]); these are parsed with
+//! [std::str::FromStr] for [DeepSkyCatalog], and
+//! [DeepSkyCatalog::builtin] combines all three. Positions are J2000
+//! to the nearest 0.1 minute of right ascension and minute of
+//! declination; sizes and position angles are approximate.
+
+mod catalog;
+mod object;
+pub use catalog::DeepSkyCatalog;
+pub use object::{DeepSkyKind, DeepSkyObject};
+
+/// The Messier catalog (110 objects), as a text table for
+/// [DeepSkyCatalog]
+///
+/// Each line is the designation, other designation (such as the NGC
+/// number), type, constellation, right ascension (hours and minutes),
+/// declination (degrees and minutes), visual magnitude, major and
+/// minor axes (in arcminutes), position angle of the major axis (in
+/// degrees, north through east) and common name, separated by '|'
+pub const MESSIER: &str = include_str!("messier.txt");
+
+/// The Caldwell catalog (109 objects), as a text table for
+/// [DeepSkyCatalog] in the format of [MESSIER]
+pub const CALDWELL: &str = include_str!("caldwell.txt");
+
+/// Bright NGC and IC objects that are not in the Messier or Caldwell
+/// catalogs, as a text table for [DeepSkyCatalog] in the format of
+/// [MESSIER]
+pub const NGC_IC: &str = include_str!("ngc_ic.txt");
@@ -0,0 +1,258 @@
+//a Imports
+use crate::{Star, Vec3};
+
+//a DeepSkyKind
+//tp DeepSkyKind
+/// The type of a [DeepSkyObject]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DeepSkyKind {
+    /// A galaxy ("Gx")
+    Galaxy,
+    /// An open cluster ("OC")
+    OpenCluster,
+    /// A globular cluster ("GC")
+    GlobularCluster,
+    /// A planetary nebula ("PN")
+    PlanetaryNebula,
+    /// An emission nebula ("EN")
+    EmissionNebula,
+    /// A reflection nebula ("RN")
+    ReflectionNebula,
+    /// A dark nebula ("DN")
+    DarkNebula,
+    /// A supernova remnant ("SNR")
+    SupernovaRemnant,
+    /// A cluster with nebulosity ("C+N")
+    ClusterNebula,
+    /// A star cloud ("SC")
+    StarCloud,
+    /// An asterism ("Ast")
+    Asterism,
+    /// A double star ("D*")
+    DoubleStar,
+}
+
+//ip DeepSkyKind
+impl DeepSkyKind {
+    /// All of the kinds, in order
+    pub const ALL: [Self; 12] = [
+        Self::Galaxy,
+        Self::OpenCluster,
+        Self::GlobularCluster,
+        Self::PlanetaryNebula,
+        Self::EmissionNebula,
+        Self::ReflectionNebula,
+        Self::DarkNebula,
+        Self::SupernovaRemnant,
+        Self::ClusterNebula,
+        Self::StarCloud,
+        Self::Asterism,
+        Self::DoubleStar,
+    ];
+
+    //ap abbrev
+    /// Get the abbreviation used for the kind in the text tables, such
+    /// as "Gx"
+    pub fn abbrev(&self) -> &'static str {
+        match self {
+            Self::Galaxy => "Gx",
+            Self::OpenCluster => "OC",
+            Self::GlobularCluster => "GC",
+            Self::PlanetaryNebula => "PN",
+            Self::EmissionNebula => "EN",
+            Self::ReflectionNebula => "RN",
+            Self::DarkNebula => "DN",
+            Self::SupernovaRemnant => "SNR",
+            Self::ClusterNebula => "C+N",
+            Self::StarCloud => "SC",
+            Self::Asterism => "Ast",
+            Self::DoubleStar => "D*",
+        }
+    }
+
+    //fp of_abbrev
+    /// Get the kind given its abbreviation (ignoring case)
+    pub fn of_abbrev(abbrev: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|k| k.abbrev().eq_ignore_ascii_case(abbrev))
+    }
+
+    //ap is_nebula
+    /// Return true if the kind is a nebula of some sort (including a
+    /// supernova remnant or a cluster with nebulosity), with an
+    /// irregular outline
+    pub fn is_nebula(&self) -> bool {
+        matches!(
+            self,
+            Self::EmissionNebula
+                | Self::ReflectionNebula
+                | Self::DarkNebula
+                | Self::SupernovaRemnant
+                | Self::ClusterNebula
+        )
+    }
+}
+
+//a DeepSkyObject
+//tp DeepSkyObject
+/// A deep-sky object, such as a galaxy, cluster or nebula
+///
+/// The angular size is given as the major and minor axes of an
+/// ellipse, with the position angle of the major axis measured from
+/// north through east
+#[derive(Debug, Clone)]
+pub struct DeepSkyObject {
+    /// The catalog designation, such as "M 31"
+    pub designation: String,
+    /// Another designation, such as "NGC 224"; this may be empty
+    pub alternate: String,
+    /// The type of the object
+    pub kind: DeepSkyKind,
+    /// The abbreviation of the constellation containing the object
+    pub constellation: String,
+    /// The right ascension (J2000) in radians
+    pub ra: f64,
+    /// The declination (J2000) in radians
+    pub de: f64,
+    /// The unit vector for the position, as for [Star::vector]
+    pub vector: Vec3,
+    /// The visual magnitude, if known
+    pub mag: Option<f32>,
+    /// The major axis in radians
+    pub major: f64,
+    /// The minor axis in radians
+    pub minor: f64,
+    /// The position angle of the major axis in radians
+    pub position_angle: f64,
+    /// The common name, such as "Andromeda Galaxy"; this may be empty
+    pub name: String,
+}
+
+//ip DeepSkyObject
+impl DeepSkyObject {
+    //cp new
+    /// Create a new [DeepSkyObject] at a right ascension and
+    /// declination (in radians), with no size
+    pub fn new(designation: &str, kind: DeepSkyKind, ra: f64, de: f64) -> Self {
+        Self {
+            designation: designation.into(),
+            alternate: String::new(),
+            kind,
+            constellation: String::new(),
+            ra,
+            de,
+            vector: Star::vec_of_ra_de(ra, de),
+            mag: None,
+            major: 0.,
+            minor: 0.,
+            position_angle: 0.,
+            name: String::new(),
+        }
+    }
+
+    //cp with_size
+    /// Set the major and minor axes and the position angle (all in
+    /// radians), returning the object
+    pub fn with_size(mut self, major: f64, minor: f64, position_angle: f64) -> Self {
+        self.major = major;
+        self.minor = minor;
+        self.position_angle = position_angle;
+        self
+    }
+
+    //cp with_mag
+    /// Set the visual magnitude, returning the object
+    pub fn with_mag(mut self, mag: f32) -> Self {
+        self.mag = Some(mag);
+        self
+    }
+
+    //cp with_name
+    /// Set the common name, returning the object
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    //ap radius
+    /// Get the angular radius of the object (half the major axis) in
+    /// radians
+    pub fn radius(&self) -> f64 {
+        self.major / 2.0
+    }
+
+    //mp has_designation
+    /// Return true if the designation or other designation of the
+    /// object is the same as a string, ignoring case and spaces (so
+    /// "m31" matches "M 31")
+    pub fn has_designation(&self, designation: &str) -> bool {
+        let d = compact(designation);
+        !d.is_empty() && (compact(&self.designation) == d || compact(&self.alternate) == d)
+    }
+
+    //ap north_east
+    /// Get the unit vectors pointing north and east in the tangent
+    /// plane at the object
+    pub fn north_east(&self) -> (Vec3, Vec3) {
+        let (sr, cr) = self.ra.sin_cos();
+        let (sd, cd) = self.de.sin_cos();
+        let north = [-sd * cr, -sd * sr, cd].into();
+        let east = [-sr, cr, 0.].into();
+        (north, east)
+    }
+
+    //fp parse_line
+    /// Parse a line of a text table (in the format of
+    /// [super::MESSIER]), returning None if it is not valid
+    pub(crate) fn parse_line(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line.split('|').map(|f| f.trim()).collect();
+        if fields.len() != 11 || fields[0].is_empty() {
+            return None;
+        }
+        let kind = DeepSkyKind::of_abbrev(fields[2])?;
+        let ra = parse_sexagesimal(fields[4])? * 15.0;
+        let de = parse_sexagesimal(fields[5])?;
+        let mag = match fields[6] {
+            "" => None,
+            m => Some(m.parse::<f32>().ok()?),
+        };
+        let arcmin = |f: &str| f.parse::<f64>().ok().map(|a| (a / 60.0).to_radians());
+        let major = arcmin(fields[7])?;
+        let minor = arcmin(fields[8])?;
+        let position_angle = fields[9].parse::<f64>().ok()?.to_radians();
+        let mut object = Self::new(fields[0], kind, ra.to_radians(), de.to_radians())
+            .with_size(major, minor, position_angle)
+            .with_name(fields[10]);
+        object.alternate = fields[1].into();
+        object.constellation = fields[3].into();
+        object.mag = mag;
+        Some(object)
+    }
+}
+
+//fi compact
+/// Remove the whitespace from a designation and make it upper case
+fn compact(s: &str) -> String {
+    s.chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+//fi parse_sexagesimal
+/// Parse a (possibly signed) value given as whole units and minutes,
+/// such as "-00 49", to a value in units
+fn parse_sexagesimal(s: &str) -> Option<f64> {
+    let (sign, s) = match s.strip_prefix('-') {
+        Some(s) => (-1.0, s),
+        None => (1.0, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let mut parts = s.split_whitespace();
+    let units: f64 = parts.next()?.parse().ok()?;
+    let minutes: f64 = parts.next()?.parse().ok()?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(sign * (units + minutes / 60.0))
+}
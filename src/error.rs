@@ -12,6 +12,41 @@ pub enum Error {
     /// of stars
     #[error("Failed to find star name in the catalog")]
     FailedToFindName,
+    /// A star of an asterism pattern has no known angles connecting
+    /// it to star 0 of the pattern
+    #[error("Star {0} of the asterism pattern is not connected to the other stars")]
+    UnconstrainedPatternStar(usize),
+    /// A field of view is not between 0 and 180 degrees across
+    #[error("The field of view must be more than 0 and less than 180 degrees across")]
+    BadFieldOfView,
+    /// A line of a constellation boundary table could not be parsed
+    #[error("Failed to parse line {0} of the constellation boundaries")]
+    BadBoundaryLine(usize),
+    /// A constellation abbreviation is not one of the 88 IAU
+    /// constellations
+    #[error("Unknown constellation {0}")]
+    UnknownConstellation(String),
+    /// A line of a constellation figure file could not be parsed
+    #[error("Failed to parse line {0} of the constellation figures")]
+    BadConstellationLine(usize),
+    /// A JSON file of constellation figures could not be understood
+    #[error("Bad constellation figures JSON: {0}")]
+    BadConstellationJson(String),
+    /// A line of an IAU star names file could not be parsed (0 if
+    /// there is no header line naming the columns)
+    #[error("Failed to parse line {0} of the IAU star names")]
+    BadIauNamesLine(usize),
+    /// A line of a deep-sky object table could not be parsed
+    #[error("Failed to parse line {0} of the deep-sky objects")]
+    BadDeepSkyLine(usize),
+    /// A line of a file of minor body orbital elements could not be
+    /// parsed
+    #[error("Failed to parse line {0} of the orbital elements")]
+    BadMpcLine(usize),
+    /// A line of a file of satellite two-line elements could not be
+    /// parsed
+    #[error("Failed to parse line {0} of the two-line elements")]
+    BadTleLine(usize),
     /// Failed to read a CSV file for a catalog
     #[error("Failed to read CSV file")]
     #[from(csv::Error)]
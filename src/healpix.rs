@@ -0,0 +1,426 @@
+//a Imports
+use geo_nd::{Vector, Vector3};
+
+use crate::{SpatialIndex, Vec3};
+
+//a Constants
+/// Ring number (in units of nside) of the southernmost corner of each base pixel
+const JRLL: [i64; 12] = [2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4];
+
+/// Longitude (in units of pi/4) of the southernmost corner of each base pixel
+const JPLL: [i64; 12] = [1, 3, 5, 7, 0, 2, 4, 6, 1, 3, 5, 7];
+
+/// X offsets within a face of the eight neighbors of a pixel
+const NB_XOFFSET: [i64; 8] = [-1, -1, 0, 1, 1, 1, 0, -1];
+
+/// Y offsets within a face of the eight neighbors of a pixel
+const NB_YOFFSET: [i64; 8] = [0, 1, 1, 1, 0, -1, -1, -1];
+
+/// The base pixel that a neighbor falls within, given the direction
+/// the neighbor has wrapped off the face and the original base pixel
+const NB_FACEARRAY: [[i64; 12]; 9] = [
+    [8, 9, 10, 11, -1, -1, -1, -1, 10, 11, 8, 9], // S
+    [5, 6, 7, 4, 8, 9, 10, 11, 9, 10, 11, 8],     // SE
+    [-1, -1, -1, -1, 5, 6, 7, 4, -1, -1, -1, -1], // E
+    [4, 5, 6, 7, 11, 8, 9, 10, 11, 8, 9, 10],     // SW
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],       // center
+    [1, 2, 3, 0, 0, 1, 2, 3, 5, 6, 7, 4],         // NE
+    [-1, -1, -1, -1, 7, 4, 5, 6, -1, -1, -1, -1], // W
+    [3, 0, 1, 2, 3, 0, 1, 2, 4, 5, 6, 7],         // NW
+    [2, 3, 0, 1, -1, -1, -1, -1, 0, 1, 2, 3],     // N
+];
+
+/// How the coordinates of a neighbor must be flipped (bit 0 for x,
+/// bit 1 for y) or swapped (bit 2) when it wraps onto another base
+/// pixel; indexed by wrap direction and then by row of base pixel
+const NB_SWAPARRAY: [[u32; 3]; 9] = [
+    [0, 0, 3], // S
+    [0, 0, 6], // SE
+    [0, 0, 0], // E
+    [0, 0, 5], // SW
+    [0, 0, 0], // center
+    [5, 0, 0], // NE
+    [0, 0, 0], // W
+    [6, 0, 0], // NW
+    [3, 0, 0], // N
+];
+
+//a Bit interleaving
+//fi spread_bits
+/// Spread the bottom 32 bits of a value to the even bits of a u64
+fn spread_bits(v: u64) -> u64 {
+    let mut v = v & 0xffff_ffff;
+    v = (v | (v << 16)) & 0x0000_ffff_0000_ffff;
+    v = (v | (v << 8)) & 0x00ff_00ff_00ff_00ff;
+    v = (v | (v << 4)) & 0x0f0f_0f0f_0f0f_0f0f;
+    v = (v | (v << 2)) & 0x3333_3333_3333_3333;
+    (v | (v << 1)) & 0x5555_5555_5555_5555
+}
+
+//fi compress_bits
+/// Compress the even bits of a u64 into the bottom 32 bits
+fn compress_bits(v: u64) -> u64 {
+    let mut v = v & 0x5555_5555_5555_5555;
+    v = (v | (v >> 1)) & 0x3333_3333_3333_3333;
+    v = (v | (v >> 2)) & 0x0f0f_0f0f_0f0f_0f0f;
+    v = (v | (v >> 4)) & 0x00ff_00ff_00ff_00ff;
+    v = (v | (v >> 8)) & 0x0000_ffff_0000_ffff;
+    (v | (v >> 16)) & 0xffff_ffff
+}
+
+//a HealpixIndex
+//tp HealpixIndex
+/// A HEALPix tessellation of the unit sphere, using the *nested*
+/// pixel numbering scheme
+///
+/// HEALPix (Hierarchical Equal Area isoLatitude Pixelization)
+/// divides the sphere into 12 base pixels, each of which is
+/// subdivided into nside x nside pixels; nside is 2^order. All of the
+/// pixels have exactly the same area, and they are of a similar
+/// shape, so (unlike [crate::Subcube]) every cell subtends roughly the
+/// same solid angle.
+///
+/// In the nested scheme the four children of pixel *p* at one order
+/// are pixels 4p to 4p+3 at the next order. Pixel ids are those used
+/// by other HEALPix tools (e.g. healpy with nest=True), and so they
+/// can be exchanged with external datasets.
+///
+/// # Choice of order
+///
+/// The number of pixels is 12 * 4^order:
+///
+/// * order 3 has 768 pixels of about 7.3 degrees across
+/// * order 4 has 3,072 pixels of about 3.7 degrees across
+/// * order 5 has 12,288 pixels of about 1.8 degrees across
+///
+/// Order 4 is roughly comparable with the default Subcube division.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HealpixIndex {
+    /// The order of the tessellation; nside is 2^order
+    order: u32,
+}
+
+//ip HealpixIndex
+impl HealpixIndex {
+    //cp MAX_ORDER
+    /// The maximum order supported
+    pub const MAX_ORDER: u32 = 29;
+
+    //cp new
+    /// Create a new [HealpixIndex] of a given order (nside = 2^order)
+    ///
+    /// Panics if the order is more than MAX_ORDER
+    pub fn new(order: u32) -> Self {
+        assert!(
+            order <= Self::MAX_ORDER,
+            "HEALPix order {order} is larger than the maximum supported"
+        );
+        Self { order }
+    }
+
+    //ap order
+    /// Get the order of the tessellation
+    pub fn order(&self) -> u32 {
+        self.order
+    }
+
+    //ap nside
+    /// Get the number of pixels along the side of each base pixel
+    pub fn nside(&self) -> u64 {
+        1 << self.order
+    }
+
+    //ap num_pixels
+    /// Get the total number of pixels on the sphere (12 * nside^2)
+    pub fn num_pixels(&self) -> u64 {
+        12 << (2 * self.order)
+    }
+
+    //ap pixel_area
+    /// Get the solid angle (in steradians) of every pixel
+    pub fn pixel_area(&self) -> f64 {
+        4.0 * std::f64::consts::PI / self.num_pixels() as f64
+    }
+
+    //ap max_pixel_radius
+    /// Get the maximum angle (in radians) between the center of any
+    /// pixel and any point within that pixel
+    pub fn max_pixel_radius(&self) -> f64 {
+        let nside = self.nside() as f64;
+        let va = Self::vec_of_z_phi(2.0 / 3.0, std::f64::consts::PI / (4.0 * nside));
+        let t1 = 1.0 - 1.0 / nside;
+        let vb = Self::vec_of_z_phi(1.0 - t1 * t1 / 3.0, 0.);
+        va.dot(&vb).clamp(-1., 1.).acos()
+    }
+
+    //fi vec_of_z_phi
+    /// Get a unit vector from a z coordinate and a longitude
+    fn vec_of_z_phi(z: f64, phi: f64) -> Vec3 {
+        let sth = (1.0 - z * z).max(0.).sqrt();
+        [sth * phi.cos(), sth * phi.sin(), z].into()
+    }
+
+    //mi xyf_to_pixel
+    /// Convert a face number and coordinates within the face to a pixel
+    fn xyf_to_pixel(&self, ix: u64, iy: u64, face: u64) -> u64 {
+        (face << (2 * self.order)) + spread_bits(ix) + (spread_bits(iy) << 1)
+    }
+
+    //mi pixel_to_xyf
+    /// Convert a pixel to its coordinates within a face, and the face number
+    fn pixel_to_xyf(&self, pixel: u64) -> (u64, u64, u64) {
+        let face = pixel >> (2 * self.order);
+        let p = pixel & ((1 << (2 * self.order)) - 1);
+        (compress_bits(p), compress_bits(p >> 1), face)
+    }
+
+    //mp pixel_of_vector
+    /// Get the pixel containing a vector (which need not be a unit vector)
+    pub fn pixel_of_vector(&self, v: &Vec3) -> u64 {
+        let v = v.normalize();
+        let z = v[2];
+        let za = z.abs();
+        let phi = v[1].atan2(v[0]);
+        let nside = self.nside();
+        let nside_f = nside as f64;
+        let tt = (phi / std::f64::consts::FRAC_PI_2).rem_euclid(4.0);
+        if za <= 2.0 / 3.0 {
+            // Equatorial region
+            let temp1 = nside_f * (0.5 + tt);
+            let temp2 = nside_f * (z * 0.75);
+            let jp = (temp1 - temp2) as u64;
+            let jm = (temp1 + temp2) as u64;
+            let ifp = jp >> self.order;
+            let ifm = jm >> self.order;
+            let face = {
+                if ifp == ifm {
+                    ifp | 4
+                } else if ifp < ifm {
+                    ifp
+                } else {
+                    ifm + 8
+                }
+            };
+            let ix = jm & (nside - 1);
+            let iy = nside - (jp & (nside - 1)) - 1;
+            self.xyf_to_pixel(ix, iy, face)
+        } else {
+            // Polar caps
+            let ntt = (tt as u64).min(3);
+            let tp = tt - ntt as f64;
+            let tmp = {
+                if za < 0.99 {
+                    nside_f * (3.0 * (1.0 - za)).sqrt()
+                } else {
+                    let sth = (v[0] * v[0] + v[1] * v[1]).sqrt();
+                    nside_f * sth / ((1.0 + za) / 3.0).sqrt()
+                }
+            };
+            let jp = ((tp * tmp) as u64).min(nside - 1);
+            let jm = (((1.0 - tp) * tmp) as u64).min(nside - 1);
+            if z >= 0. {
+                self.xyf_to_pixel(nside - jm - 1, nside - jp - 1, ntt)
+            } else {
+                self.xyf_to_pixel(jp, jm, ntt + 8)
+            }
+        }
+    }
+
+    //mp vector_of_pixel
+    /// Get the unit vector of the center of a pixel
+    pub fn vector_of_pixel(&self, pixel: u64) -> Vec3 {
+        let (ix, iy, face) = self.pixel_to_xyf(pixel);
+        let (ix, iy, face) = (ix as i64, iy as i64, face as usize);
+        let nside = self.nside() as i64;
+        let fact2 = 4.0 / self.num_pixels() as f64;
+        let fact1 = (2 * nside) as f64 * fact2;
+
+        let jr = (JRLL[face] << self.order) - ix - iy - 1;
+        let (nr, z) = {
+            if jr < nside {
+                (jr, 1.0 - (jr * jr) as f64 * fact2)
+            } else if jr > 3 * nside {
+                let nr = 4 * nside - jr;
+                (nr, (nr * nr) as f64 * fact2 - 1.0)
+            } else {
+                (nside, (2 * nside - jr) as f64 * fact1)
+            }
+        };
+        let mut tmp = JPLL[face] * nr + ix - iy;
+        if tmp < 0 {
+            tmp += 8 * nr;
+        }
+        let phi = std::f64::consts::FRAC_PI_4 * tmp as f64 / nr as f64;
+        Self::vec_of_z_phi(z, phi)
+    }
+
+    //mp neighbors
+    /// Get the neighbors of a pixel
+    ///
+    /// Most pixels have eight neighbors; a few pixels at the corners
+    /// of the base pixels have only seven
+    pub fn neighbors(&self, pixel: u64) -> Vec<u64> {
+        let (ix, iy, face) = self.pixel_to_xyf(pixel);
+        let (ix, iy) = (ix as i64, iy as i64);
+        let nside = self.nside() as i64;
+        let mut result = Vec::with_capacity(8);
+        for i in 0..8 {
+            let mut x = ix + NB_XOFFSET[i];
+            let mut y = iy + NB_YOFFSET[i];
+            let mut nbnum = 4;
+            if x < 0 {
+                x += nside;
+                nbnum -= 1;
+            } else if x >= nside {
+                x -= nside;
+                nbnum += 1;
+            }
+            if y < 0 {
+                y += nside;
+                nbnum -= 3;
+            } else if y >= nside {
+                y -= nside;
+                nbnum += 3;
+            }
+            let f = NB_FACEARRAY[nbnum][face as usize];
+            if f < 0 {
+                continue;
+            }
+            let bits = NB_SWAPARRAY[nbnum][(face >> 2) as usize];
+            if bits & 1 != 0 {
+                x = nside - x - 1;
+            }
+            if bits & 2 != 0 {
+                y = nside - y - 1;
+            }
+            if bits & 4 != 0 {
+                std::mem::swap(&mut x, &mut y);
+            }
+            result.push(self.xyf_to_pixel(x as u64, y as u64, f as u64));
+        }
+        result
+    }
+
+    //mp pixels_in_cone
+    /// Get all the pixels that may contain points within an angle of
+    /// a vector
+    ///
+    /// This is conservative, in that some pixels returned may lie
+    /// just outside the cone; no pixel that overlaps the cone is
+    /// omitted. The result is sorted.
+    pub fn pixels_in_cone(&self, v: &Vec3, angle: f64) -> Vec<u64> {
+        let v = v.normalize();
+        self.pixels_where(|order_index, c| {
+            let a = c.dot(&v).clamp(-1., 1.).acos();
+            a <= angle + order_index.max_pixel_radius() + 1.0E-9
+        })
+    }
+
+    //mp pixels_in_polygon
+    /// Get all the pixels that may overlap a convex spherical polygon
+    ///
+    /// The polygon is described by its vertices as unit vectors, in
+    /// order around the polygon (clockwise or anticlockwise); the
+    /// edges are the great circle arcs between consecutive vertices.
+    ///
+    /// This is conservative, in that some pixels returned may lie
+    /// just outside the polygon; no pixel that overlaps the polygon
+    /// is omitted. The result is sorted.
+    pub fn pixels_in_polygon(&self, vertices: &[Vec3]) -> Vec<u64> {
+        let Some(normals) = polygon_edge_normals(vertices) else {
+            return vec![];
+        };
+        self.pixels_where(|order_index, c| {
+            let s = -(order_index.max_pixel_radius() + 1.0E-9)
+                .min(std::f64::consts::FRAC_PI_2)
+                .sin();
+            normals.iter().all(|n| c.dot(n) >= s)
+        })
+    }
+
+    //mi pixels_where
+    /// Find the pixels at this order by descending the hierarchy from
+    /// the base pixels, keeping only those whose center (at each
+    /// order) satisfies a predicate
+    fn pixels_where<F>(&self, f: F) -> Vec<u64>
+    where
+        F: Fn(&HealpixIndex, &Vec3) -> bool,
+    {
+        let mut result = vec![];
+        let mut to_do: Vec<(u32, u64)> = (0..12).map(|p| (0, p)).collect();
+        while let Some((order, pixel)) = to_do.pop() {
+            let order_index = HealpixIndex::new(order);
+            let c = order_index.vector_of_pixel(pixel);
+            if !f(&order_index, &c) {
+                continue;
+            }
+            if order == self.order {
+                result.push(pixel);
+            } else {
+                for i in 0..4 {
+                    to_do.push((order + 1, pixel * 4 + i));
+                }
+            }
+        }
+        result.sort();
+        result
+    }
+}
+
+//fp polygon_edge_normals
+/// Get the unit normals of the edges of a convex spherical polygon,
+/// pointing into the polygon
+///
+/// Returns None if the polygon has fewer than three vertices
+pub(crate) fn polygon_edge_normals(vertices: &[Vec3]) -> Option<Vec<Vec3>> {
+    if vertices.len() < 3 {
+        return None;
+    }
+    let n = vertices.len();
+    let mut normals: Vec<Vec3> = (0..n)
+        .map(|i| {
+            vertices[i]
+                .cross_product(&vertices[(i + 1) % n])
+                .normalize()
+        })
+        .collect();
+    let mut middle: Vec3 = [0., 0., 0.].into();
+    for v in vertices {
+        middle += *v;
+    }
+    if middle.dot(&normals[0]) < 0. {
+        for n in normals.iter_mut() {
+            *n = -*n;
+        }
+    }
+    Some(normals)
+}
+
+//ip SpatialIndex for HealpixIndex
+impl SpatialIndex for HealpixIndex {
+    type Cell = u64;
+    fn num_cells(&self) -> usize {
+        self.num_pixels() as usize
+    }
+    fn cell_of_vector(&self, v: &Vec3) -> u64 {
+        self.pixel_of_vector(v)
+    }
+    fn cell_id(&self, cell: u64) -> usize {
+        cell as usize
+    }
+    fn cell_center(&self, cell: u64) -> Vec3 {
+        self.vector_of_pixel(cell)
+    }
+    fn cell_max_angle(&self) -> f64 {
+        self.max_pixel_radius()
+    }
+    fn cell_neighbors(&self, cell: u64) -> Vec<u64> {
+        self.neighbors(cell)
+    }
+    fn cells_in_cone(&self, v: &Vec3, angle: f64) -> Vec<u64> {
+        self.pixels_in_cone(v, angle)
+    }
+    fn cells_in_polygon(&self, vertices: &[Vec3]) -> Vec<u64> {
+        self.pixels_in_polygon(vertices)
+    }
+}
@@ -85,6 +85,33 @@ struct Record {
     /// Blue-violet luminance delta
     #[serde(rename = "B-V")]
     b_v: Option<f32>,
+    /// CCDM identifier of a double or multiple system
+    #[serde(rename = "CCDM")]
+    ccdm: Option<String>,
+    /// Number of components of the system
+    #[serde(rename = "Ncomp")]
+    n_comp: Option<u32>,
+    /// Double or multiple system solution flag
+    #[serde(rename = "MultFlag")]
+    mult_flag: Option<String>,
+}
+
+//tp Multiplicity
+/// Multiplicity information for a star of the Hipparcos catalog,
+/// from `read_to_catalog_with_multiplicity` (with the csv feature)
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Multiplicity {
+    /// The Hipparcos id of the star
+    pub id: usize,
+    /// The CCDM identifier of the system (such as '11037+6145'), if
+    /// any
+    pub ccdm: Option<String>,
+    /// The number of components of the system in the Hipparcos
+    /// catalog, if known
+    pub num_components: Option<u32>,
+    /// The flag for the Hipparcos double or multiple system solution
+    /// (C, G, O, V or X), if any
+    pub mult_flag: Option<char>,
 }
 
 //fp read_to_catalog
@@ -106,38 +133,60 @@ pub fn read_to_catalog<R: std::io::Read>(
     reader: R,
     max_mag: f32,
 ) -> Result<(), csv::Error> {
+    read_to_catalog_with_multiplicity(catalog, reader, max_mag)?;
+    Ok(())
+}
+
+//fp read_to_catalog_with_multiplicity
+/// Read stars from a Hipparcos CSV file as [read_to_catalog] does,
+/// also returning the multiplicity information (CCDM identifier,
+/// number of components and MultFlag) of those stars added that are
+/// in double or multiple systems
+///
+/// This requires the 'csv' feature
+#[cfg(feature = "csv")]
+pub fn read_to_catalog_with_multiplicity<R: std::io::Read>(
+    catalog: &mut Catalog,
+    reader: R,
+    max_mag: f32,
+) -> Result<Vec<Multiplicity>, csv::Error> {
+    let mut multiplicity = vec![];
     let mut csv_reader = csv::Reader::from_reader(reader);
     for result in csv_reader.deserialize() {
         let record: Record = result?;
-        if record.hip.is_some()
-            && record.ra.is_some()
-            && record.de.is_some()
-            && record.plx.is_some()
-            && record.mag.is_some()
-            && record.b_v.is_some()
-        {
-            let mag = record.mag.unwrap();
-            if mag > max_mag {
-                continue;
-            }
-            let hip = record.hip.unwrap();
-            let ra = record.ra.unwrap() / 180.0 * std::f64::consts::PI;
-            let de = record.de.unwrap() / 180.0 * std::f64::consts::PI;
-            let ly = 3.26156E3 / record.plx.unwrap();
-            let ly = if ly.is_normal() { ly } else { 0.0 };
-            let b_v = record.b_v.unwrap();
-            let star = Star::new(hip, ra, de, ly, mag, b_v);
-            catalog.add_star(star);
+        let (Some(hip), Some(ra), Some(de), Some(plx), Some(mag), Some(b_v)) = (
+            record.hip, record.ra, record.de, record.plx, record.mag, record.b_v,
+        ) else {
+            continue;
+        };
+        if mag > max_mag {
+            continue;
+        }
+        let ra = ra / 180.0 * std::f64::consts::PI;
+        let de = de / 180.0 * std::f64::consts::PI;
+        let ly = 3.26156E3 / plx;
+        let ly = if ly.is_normal() { ly } else { 0.0 };
+        let star = Star::new(hip, ra, de, ly, mag, b_v);
+        catalog.add_star(star);
+
+        let ccdm = record.ccdm.filter(|c| !c.trim().is_empty());
+        let mult_flag = record.mult_flag.and_then(|m| m.trim().chars().next());
+        if ccdm.is_some() || mult_flag.is_some() {
+            multiplicity.push(Multiplicity {
+                id: hip,
+                ccdm,
+                num_components: record.n_comp,
+                mult_flag,
+            });
         }
     }
-    Ok(())
+    Ok(multiplicity)
 }
 
 //cp HIP_ALIASES
 /// Aliases of HIP identifiers to common names of stars
 ///
 /// Source: <https://www.cosmos.esa.int/web/hipparcos/common-star-names>
-
 pub const HIP_ALIASES: &[(usize, &str)] = &[
     (677, "Alpheratz"),
     (746, "Caph"),
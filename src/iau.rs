@@ -11,6 +11,22 @@
 //! Note that some stars are binaries, and as such should have more
 //! than one Hipparcos number.
 //!
+//! The names are applied to a [Catalog] with
+//! [Catalog::apply_iau_names], which uses the Hipparcos number if
+//! there is one, and otherwise matches the star by position. The
+//! list of the IAU Working Group on Star Names (WGSN), with the
+//! designations of the stars, is included as [IAU_CSN] (used by
+//! [IauName::builtin]); a newer 'IAU-CSN.txt' file of the IAU Catalog
+//! of Star Names can be read with [read_csn].
+//!
+
+//a Imports
+use crate::{Catalog, CatalogIndex, Error, Star};
+
+//cp IAU_CSN
+/// The IAU Catalog of Star Names, in the layout of 'IAU-CSN.txt' as
+/// read by [read_csn], with the designation of each star
+pub const IAU_CSN: &str = include_str!("iau_csn.txt");
 
 //cp NAMES_AND_RA_DE
 /// This is a list of IAU names (as approved by Jan 2021) and the
@@ -465,3 +481,198 @@ pub const NAMES_AND_RA_DE: &[(&str, Option<usize>, f64, f64)] = &[
     ("Ginan", Some(60260), 185.340039, -60.401147),
     ("Wurren", Some(5348), 17.096173, -55.245758),
 ];
+
+//a IauName
+//tp IauName
+/// An IAU star name, with the designation and position of the star
+/// and its Hipparcos number (if it has one)
+#[derive(Debug, Clone, PartialEq)]
+pub struct IauName {
+    /// The name, such as "Rigil Kentaurus"
+    pub name: String,
+    /// The designation of the star, such as "alf Cen A"; this may be
+    /// empty
+    pub designation: String,
+    /// The Hipparcos number of the star, if it has one
+    pub hip: Option<usize>,
+    /// The right ascension (J2000) in radians
+    pub ra: f64,
+    /// The declination (J2000) in radians
+    pub de: f64,
+}
+
+//ip IauName
+impl IauName {
+    //fp builtin
+    /// Get the names of the included IAU Catalog of Star Names,
+    /// [IAU_CSN], with their designations
+    pub fn builtin() -> Vec<Self> {
+        read_csn(IAU_CSN).expect("The included IAU Catalog of Star Names is valid")
+    }
+}
+
+//fp read_csn
+/// Read the IAU Catalog of Star Names ('IAU-CSN.txt', from the WGSN)
+///
+/// This is a fixed-width text file; the columns are found from the
+/// header line (starting with '#') that names them, of which
+/// 'Name/Diacritics' (or 'Name/ASCII'), 'Designation', 'HIP',
+/// 'RA(J2000)' and 'Dec(J2000)' (in degrees) are used. Other lines
+/// starting with '#', and blank lines, are ignored; '_' is an empty
+/// value.
+///
+/// The error gives the line that could not be parsed, or 0 if there
+/// is no header line
+pub fn read_csn(s: &str) -> Result<Vec<IauName>, Error> {
+    let mut columns: Option<Vec<(String, usize)>> = None;
+    let mut result = vec![];
+    for (n, line) in s.lines().enumerate() {
+        let chars: Vec<char> = line.chars().collect();
+        if line.trim().is_empty() {
+            continue;
+        }
+        if line.starts_with('#') {
+            if line.contains("HIP") && line.contains("RA(J2000)") {
+                columns = Some(header_columns(&chars));
+            }
+            continue;
+        }
+        let Some(columns) = &columns else {
+            return Err(Error::BadIauNamesLine(0));
+        };
+        let field = |label: &str| -> Option<String> {
+            let i = columns.iter().position(|(l, _)| l == label)?;
+            let start = columns[i].1.min(chars.len());
+            let end = columns
+                .get(i + 1)
+                .map_or(chars.len(), |(_, e)| (*e).min(chars.len()));
+            let f: String = chars[start..end].iter().collect();
+            let f = f.trim();
+            (!f.is_empty() && f != "_").then(|| f.to_string())
+        };
+        let bad = || Error::BadIauNamesLine(n + 1);
+        let name = field("Name/Diacritics")
+            .or_else(|| field("Name/ASCII"))
+            .ok_or_else(bad)?;
+        let designation = field("Designation").unwrap_or_default();
+        let hip = match field("HIP") {
+            Some(h) => Some(h.parse::<usize>().map_err(|_| bad())?),
+            None => None,
+        };
+        let ra: f64 = field("RA(J2000)")
+            .and_then(|f| f.parse().ok())
+            .ok_or_else(bad)?;
+        let de: f64 = field("Dec(J2000)")
+            .and_then(|f| f.parse().ok())
+            .ok_or_else(bad)?;
+        result.push(IauName {
+            name,
+            designation,
+            hip,
+            ra: ra.to_radians(),
+            de: de.to_radians(),
+        });
+    }
+    if columns.is_none() {
+        return Err(Error::BadIauNamesLine(0));
+    }
+    Ok(result)
+}
+
+//fi header_columns
+/// Get the labels of the columns of a header line, with the character
+/// position at which each starts
+fn header_columns(chars: &[char]) -> Vec<(String, usize)> {
+    let mut columns = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i].is_whitespace() || chars[i] == '#' {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < chars.len() && !chars[i].is_whitespace() {
+            i += 1;
+        }
+        columns.push((chars[start..i].iter().collect(), start));
+    }
+    // The first column starts at the start of the line, under the '#'
+    if let Some(first) = columns.first_mut() {
+        first.1 = 0;
+    }
+    columns
+}
+
+//a IauNameMatches
+//tp IauNameMatches
+/// The result of matching [IauName]s to the stars of a catalog, with
+/// [Catalog::match_iau_names]; entries are indices into the list of
+/// names
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IauNameMatches {
+    /// The names matched to a star
+    pub matched: Vec<(usize, CatalogIndex)>,
+    /// The names that could not be matched to a star
+    pub unmatched: Vec<usize>,
+    /// The names that are near more than one star, with those stars
+    pub ambiguous: Vec<(usize, Vec<CatalogIndex>)>,
+}
+
+//ip Catalog
+impl Catalog {
+    //mp match_iau_names
+    /// Match IAU names to the stars of the catalog
+    ///
+    /// A name with a Hipparcos number is matched to that star, and is
+    /// unmatched if the star is not in the catalog. Otherwise the
+    /// closest star (from [Catalog::closest_to]) is used if it is
+    /// within the tolerance (in radians); the name is unmatched if
+    /// there is no such star, and ambiguous if there is more than one
+    /// star (passing the catalog filter) within the tolerance.
+    ///
+    /// The catalog must have been sorted, and have had its data
+    /// derived, beforehand
+    pub fn match_iau_names(&self, names: &[IauName], tolerance: f64) -> IauNameMatches {
+        let mut result = IauNameMatches::default();
+        for (i, n) in names.iter().enumerate() {
+            if let Some(hip) = n.hip {
+                match self.find_sorted(hip) {
+                    Some(index) => result.matched.push((i, index)),
+                    None => result.unmatched.push(i),
+                }
+                continue;
+            }
+            let Some((c, index)) = self.closest_to(n.ra, n.de) else {
+                result.unmatched.push(i);
+                continue;
+            };
+            if c < tolerance.cos() {
+                result.unmatched.push(i);
+                continue;
+            }
+            let v = Star::vec_of_ra_de(n.ra, n.de);
+            let around = self.find_stars_around(&v, tolerance);
+            if around.len() > 1 {
+                result.ambiguous.push((i, around));
+            } else {
+                result.matched.push((i, index));
+            }
+        }
+        result
+    }
+
+    //mp apply_iau_names
+    /// Add the IAU names that match a star (from
+    /// [Catalog::match_iau_names]) to the catalog, returning the
+    /// matches so that unmatched and ambiguous names can be reported
+    ///
+    /// The catalog must have been sorted, and have had its data
+    /// derived, beforehand
+    pub fn apply_iau_names(&mut self, names: &[IauName], tolerance: f64) -> IauNameMatches {
+        let matches = self.match_iau_names(names, tolerance);
+        for (i, index) in &matches.matched {
+            self.add_name(*index, names[*i].name.as_str());
+        }
+        matches
+    }
+}
@@ -2,8 +2,12 @@
 use geo_nd::{Quaternion, Vector, Vector3};
 use image::{DynamicImage, GenericImage, Rgba};
 
+use super::font;
+use crate::constellations::{BoundaryEdge, Constellation};
+use crate::deep_sky::{DeepSkyCatalog, DeepSkyKind, DeepSkyObject};
+use crate::solar_system::BodyPosition;
 use crate::Star;
-use crate::{Quat, Vec2, Vec3};
+use crate::{Catalog, FieldCoverage, Quat, Vec2, Vec3};
 
 //a ImageView
 //tp StarDrawStyle
@@ -14,6 +18,143 @@ pub enum StarDrawStyle {
     Cross,
 }
 
+//tp ConstellationLabel
+/// The text of the label drawn for a constellation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConstellationLabel {
+    /// The abbreviation, such as "Ori"
+    Abbreviation,
+    /// The name, such as "Orion"
+    Name,
+}
+
+//tp ConstellationStyle
+/// Style for drawing constellations with
+/// [ImageView::draw_constellations]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConstellationStyle {
+    /// The color of the lines
+    pub color: Rgba<u8>,
+    /// The width of the lines in pixels
+    pub width: u32,
+    /// The gap in pixels to leave between the end of a line and its
+    /// star, so that the lines do not cover the stars
+    pub gap: f64,
+    /// The label to draw for each constellation, if any
+    pub label: Option<ConstellationLabel>,
+    /// The color of the labels
+    pub label_color: Rgba<u8>,
+    /// The scale of the label text; 1 is 7 pixels high
+    pub label_scale: u32,
+}
+
+//ip Default for ConstellationStyle
+impl Default for ConstellationStyle {
+    fn default() -> Self {
+        Self {
+            color: [155, 255, 255, 0].into(),
+            width: 1,
+            gap: 0.,
+            label: None,
+            label_color: [255, 255, 255, 0].into(),
+            label_scale: 1,
+        }
+    }
+}
+
+//tp DeepSkyStyle
+/// Style for drawing deep-sky objects with
+/// [ImageView::draw_deep_sky_objects]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DeepSkyStyle {
+    /// The color of the outlines
+    pub color: Rgba<u8>,
+    /// The smallest size in pixels to draw an outline; smaller objects
+    /// are enlarged to this
+    pub min_size: f64,
+    /// True if the designation of each object should be drawn
+    pub label: bool,
+    /// The color of the labels
+    pub label_color: Rgba<u8>,
+    /// The scale of the label text; 1 is 7 pixels high
+    pub label_scale: u32,
+}
+
+//ip Default for DeepSkyStyle
+impl Default for DeepSkyStyle {
+    fn default() -> Self {
+        Self {
+            color: [255, 160, 80, 0].into(),
+            min_size: 8.,
+            label: false,
+            label_color: [255, 160, 80, 0].into(),
+            label_scale: 1,
+        }
+    }
+}
+
+//tp SolarSystemStyle
+/// Style for drawing the Sun, Moon and planets with
+/// [ImageView::draw_solar_system]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SolarSystemStyle {
+    /// The smallest radius in pixels to draw a body; bodies are drawn
+    /// larger if their disc, or their magnitude, requires
+    pub min_radius: f64,
+    /// True if the name of each body should be drawn
+    pub label: bool,
+    /// The color of the labels
+    pub label_color: Rgba<u8>,
+    /// The scale of the label text; 1 is 7 pixels high
+    pub label_scale: u32,
+}
+
+//ip Default for SolarSystemStyle
+impl Default for SolarSystemStyle {
+    fn default() -> Self {
+        Self {
+            min_radius: 3.,
+            label: false,
+            label_color: [255, 255, 160, 0].into(),
+            label_scale: 1,
+        }
+    }
+}
+
+//tp TrackStyle
+/// Style for drawing the track of a moving body, such as a comet,
+/// asteroid or satellite, with [ImageView::draw_track]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrackStyle {
+    /// The color of the track
+    pub color: Rgba<u8>,
+    /// The width of the line in pixels
+    pub width: u32,
+    /// The radius in pixels of the dot drawn at each position of the
+    /// track; 0 for no dots
+    pub dot_radius: u32,
+    /// True if the label of the track should be drawn
+    pub label: bool,
+    /// The color of the labels
+    pub label_color: Rgba<u8>,
+    /// The scale of the label text; 1 is 7 pixels high
+    pub label_scale: u32,
+}
+
+//ip Default for TrackStyle
+impl Default for TrackStyle {
+    fn default() -> Self {
+        Self {
+            color: [255, 128, 0, 0].into(),
+            width: 1,
+            dot_radius: 2,
+            label: false,
+            label_color: [255, 192, 128, 0].into(),
+            label_scale: 1,
+        }
+    }
+}
+
 //tp ImageView
 /// This is a window onto a [DynamicImage] that allows a sky map to be
 /// drawn into it
@@ -67,6 +208,48 @@ impl ImageView {
         }
     }
 
+    //fp cubemap_face
+    /// Get the position of a face (0 to 5) of a cubemap, and the
+    /// orientation of the view for it
+    ///
+    /// The cubemap is laid out as 4 by 3 faces; the position is in
+    /// units of the width and height of a face, and the faces have a
+    /// horizontal field of view of 90 degrees (a tan_hfov of 1.0)
+    pub fn cubemap_face(face: u32) -> (u32, u32, Quat) {
+        match face {
+            0 => (
+                0,
+                1,
+                Quat::look_at(&[-1., 0., 0.].into(), &[0., 1., 0.].into()),
+            ),
+            1 => (
+                1,
+                1,
+                Quat::look_at(&[0., 0., -1.].into(), &[0., 1., 0.].into()),
+            ),
+            2 => (
+                2,
+                1,
+                Quat::look_at(&[1., 0., 0.].into(), &[0., 1., 0.].into()),
+            ),
+            3 => (
+                3,
+                1,
+                Quat::look_at(&[0., 0., 1.].into(), &[0., 1., 0.].into()),
+            ),
+            4 => (
+                1,
+                0,
+                Quat::look_at(&[0., 1., 0.].into(), &[0., 0., 1.].into()),
+            ),
+            _ => (
+                1,
+                2,
+                Quat::look_at(&[0., -1., 0.].into(), &[0., 0., -1.].into()),
+            ),
+        }
+    }
+
     //mp set_tan_hfov
     /// Set the horizontal field of view of the window
     ///
@@ -237,6 +420,52 @@ impl ImageView {
         self.draw_declination_lines(60 * 60 * 5, 60 * 60 * 10, 6 * 60);
     }
 
+    //mp vec_of_pxy
+    /// Return the unit vector in the direction of the center of a
+    /// pixel of the window, using the current transformation
+    pub fn vec_of_pxy(&self, x: u32, y: u32) -> Vec3 {
+        let tx = ((x as f64 + 0.5) / (self.width as f64) - 0.5) * self.tan_fov_x2;
+        let ty = (0.5 - (y as f64 + 0.5) / (self.height as f64)) * self.tan_fov_x2;
+        let v: Vec3 = [tx, ty, -1.].into();
+        self.orient.conjugate().apply3(&v.normalize())
+    }
+
+    //mp draw_heat_map
+    /// Fill the window with a color for the direction of each pixel
+    ///
+    /// This can be used to draw any function of direction on the sky,
+    /// such as the number of stars visible in a field of view
+    pub fn draw_heat_map<F>(&mut self, f: F)
+    where
+        F: Fn(&Vec3) -> Rgba<u8>,
+    {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let color = f(&self.vec_of_pxy(x, y));
+                self.put(x, y, color);
+            }
+        }
+    }
+
+    //mp draw_field_coverage
+    /// Fill the window with a heat map of a [FieldCoverage]
+    ///
+    /// Directions where fewer than *min_stars* stars are visible are
+    /// red; others are blue, brightening to white for the maximum
+    /// number of stars
+    pub fn draw_field_coverage(&mut self, coverage: &FieldCoverage, min_stars: usize) {
+        let max = coverage.max().max(1) as f64;
+        self.draw_heat_map(|v| {
+            let count = coverage.count_at(v);
+            if count < min_stars {
+                [255, 0, 0, 255].into()
+            } else {
+                let rg = (count as f64 / max * 255.9).floor() as u8;
+                [rg, rg, 255, 255].into()
+            }
+        });
+    }
+
     //mp draw_cross
     /// Draw a cross on the image
     fn draw_cross(&mut self, x: f64, y: f64, color: Rgba<u8>, size: u32) {
@@ -304,6 +533,294 @@ impl ImageView {
         self.draw_circle(c, q.conjugate(), angle);
     }
 
+    //mp draw_constellation_boundaries
+    /// Draw edges of the boundaries between the constellations, as
+    /// given by [crate::constellations::ConstellationBoundaries::edges]
+    pub fn draw_constellation_boundaries(&mut self, c: Rgba<u8>, edges: &[BoundaryEdge]) {
+        /// The maximum angle between points drawn along an edge, so
+        /// that lines of constant declination are drawn as curves
+        const MAX_STEP: f64 = 0.5 * std::f64::consts::PI / 180.0;
+        for edge in edges {
+            let mut last = None;
+            for v in edge.points(MAX_STEP) {
+                let xy = self.pxy_of_vec(&v, self.width as f64);
+                if let (Some(p0), Some(p1)) = (last, xy) {
+                    self.draw_line(c, &p0, &p1);
+                }
+                last = xy;
+            }
+        }
+    }
+
+    //mp draw_constellations
+    /// Draw the figures of constellations, with their stars from a
+    /// catalog, in a style
+    ///
+    /// Lines to stars that are not in the catalog are not drawn; the
+    /// catalog must have been sorted beforehand. The gap at the ends
+    /// of the lines is converted to an angle using the scale at the
+    /// center of the window.
+    pub fn draw_constellations(
+        &mut self,
+        catalog: &Catalog,
+        constellations: &[Constellation],
+        style: &ConstellationStyle,
+    ) {
+        let gap = style.gap * self.tan_fov_x2 / self.width as f64;
+        for c in constellations {
+            for (s0, s1) in c.iter_star_lines(catalog) {
+                self.draw_arc(style.color, style.width, &s0.vector, &s1.vector, gap);
+            }
+        }
+        let Some(label) = style.label else {
+            return;
+        };
+        for c in constellations {
+            let text = match label {
+                ConstellationLabel::Abbreviation => &c.abbrev,
+                ConstellationLabel::Name => &c.name,
+            };
+            if let Some(xy) = c
+                .label_vector(catalog)
+                .and_then(|v| self.pxy_of_vec(&v, 0.))
+            {
+                self.draw_text(style.label_color, &xy, style.label_scale, text);
+            }
+        }
+    }
+
+    //mp draw_deep_sky_objects
+    /// Draw the outlines of all the objects of a [DeepSkyCatalog]
+    /// (with [ImageView::draw_deep_sky_object])
+    pub fn draw_deep_sky_objects(&mut self, catalog: &DeepSkyCatalog, style: &DeepSkyStyle) {
+        for o in catalog.iter() {
+            self.draw_deep_sky_object(o, style);
+        }
+    }
+
+    //mp draw_deep_sky_object
+    /// Draw the outline of a deep-sky object, with its designation
+    /// below if the style requires
+    ///
+    /// The outline is an ellipse of the size and position angle of
+    /// the object, depending on its kind: solid for a galaxy; dotted
+    /// for a cluster, star cloud, asterism or double star; with a
+    /// cross for a globular cluster; with ticks outside for a
+    /// planetary nebula. Nebulae are drawn as a rectangle around the
+    /// ellipse.
+    pub fn draw_deep_sky_object(&mut self, o: &DeepSkyObject, style: &DeepSkyStyle) {
+        /// The number of points drawn around an ellipse
+        const NUM_POINTS: usize = 48;
+        let px_per_radian = self.width as f64 / self.tan_fov_x2;
+        let min_size = style.min_size / px_per_radian;
+        let (major, minor) = if o.major >= min_size {
+            (o.major, o.minor)
+        } else if o.major > 0. {
+            (min_size, o.minor * min_size / o.major)
+        } else {
+            (min_size, min_size)
+        };
+        let a = (major / 2.0).tan();
+        let b = (minor / 2.0).tan().max(a / 8.0);
+        let (north, east) = o.north_east();
+        let (s, c) = o.position_angle.sin_cos();
+        let u = north * c + east * s;
+        let w = east * c - north * s;
+        let pxy = |x: f64, y: f64| self.pxy_of_vec(&(o.vector + u * x + w * y), self.width as f64);
+
+        let ellipse: Vec<Option<Vec2>> = (0..=NUM_POINTS)
+            .map(|i| {
+                let t = std::f64::consts::TAU * (i as f64) / (NUM_POINTS as f64);
+                pxy(a * t.cos(), b * t.sin())
+            })
+            .collect();
+        let mut lines: Vec<(Option<Vec2>, Option<Vec2>)> = vec![];
+        match o.kind {
+            DeepSkyKind::Galaxy => {
+                lines.extend(ellipse.windows(2).map(|p| (p[0], p[1])));
+            }
+            DeepSkyKind::OpenCluster
+            | DeepSkyKind::StarCloud
+            | DeepSkyKind::Asterism
+            | DeepSkyKind::DoubleStar => {
+                lines.extend(ellipse.windows(2).step_by(2).map(|p| (p[0], p[1])));
+            }
+            DeepSkyKind::GlobularCluster => {
+                lines.extend(ellipse.windows(2).map(|p| (p[0], p[1])));
+                lines.push((pxy(-a, 0.), pxy(a, 0.)));
+                lines.push((pxy(0., -b), pxy(0., b)));
+            }
+            DeepSkyKind::PlanetaryNebula => {
+                lines.extend(ellipse.windows(2).map(|p| (p[0], p[1])));
+                lines.push((pxy(a, 0.), pxy(a * 1.6, 0.)));
+                lines.push((pxy(-a, 0.), pxy(-a * 1.6, 0.)));
+                lines.push((pxy(0., b), pxy(0., b + a * 0.6)));
+                lines.push((pxy(0., -b), pxy(0., -b - a * 0.6)));
+            }
+            _ => {
+                let corners = [pxy(a, b), pxy(-a, b), pxy(-a, -b), pxy(a, -b), pxy(a, b)];
+                lines.extend(corners.windows(2).map(|p| (p[0], p[1])));
+            }
+        }
+        for (p0, p1) in lines {
+            if let (Some(p0), Some(p1)) = (p0, p1) {
+                self.draw_line(style.color, &p0, &p1);
+            }
+        }
+
+        if !style.label {
+            return;
+        }
+        if let Some(xy) = self.pxy_of_vec(&o.vector, 0.) {
+            let dy = a * px_per_radian + (4 + 4 * style.label_scale) as f64;
+            let xy = xy + Vec2::from([0., dy]);
+            self.draw_text(style.label_color, &xy, style.label_scale, &o.designation);
+        }
+    }
+
+    //mp draw_solar_system
+    /// Draw the Sun, Moon and planets (from
+    /// [crate::solar_system::positions]) as discs, with their names
+    /// below if the style requires
+    ///
+    /// The radius of each disc is the larger of that of the body
+    /// itself, the size a star of the same magnitude would be drawn,
+    /// and the minimum radius of the style; the color is from the
+    /// color index of the body
+    pub fn draw_solar_system(&mut self, positions: &[BodyPosition], style: &SolarSystemStyle) {
+        let px_per_radian = self.width as f64 / self.tan_fov_x2;
+        for p in positions {
+            let star_radius = ((7.0 - p.mag).clamp(1.0, 6.).powi(2) / 36.0) * self.star_size as f32;
+            let radius = (p.angular_radius * px_per_radian)
+                .max(star_radius as f64)
+                .max(style.min_radius);
+            let Some(xy) = self.pxy_of_vec(&p.vector, radius) else {
+                continue;
+            };
+            let star = p.to_star(0);
+            let (r, g, b) = Star::temp_to_rgb(star.temp());
+            let color = [
+                (r.clamp(0., 1.) * 255.9).floor() as u8,
+                (g.clamp(0., 1.) * 255.9).floor() as u8,
+                (b.clamp(0., 1.) * 255.9).floor() as u8,
+                0,
+            ]
+            .into();
+            self.draw_round_star(xy[0], xy[1], color, radius.ceil() as u32);
+            if style.label {
+                let dy = radius + (4 + 4 * style.label_scale) as f64;
+                let xy = xy + Vec2::from([0., dy]);
+                self.draw_text(style.label_color, &xy, style.label_scale, p.body.name());
+            }
+        }
+    }
+
+    //mp draw_track
+    /// Draw the track of a moving body through a sequence of unit
+    /// vectors, as great circle arcs between them with a dot at each,
+    /// and with a label below the first position if the style
+    /// requires
+    pub fn draw_track(&mut self, track: &[Vec3], label: &str, style: &TrackStyle) {
+        for v in track.windows(2) {
+            self.draw_arc(style.color, style.width, &v[0], &v[1], 0.);
+        }
+        if style.dot_radius > 0 {
+            for v in track {
+                if let Some(xy) = self.pxy_of_vec(v, style.dot_radius as f64) {
+                    self.draw_round_star(xy[0], xy[1], style.color, style.dot_radius);
+                }
+            }
+        }
+        if !style.label || label.is_empty() {
+            return;
+        }
+        if let Some(xy) = track.first().and_then(|v| self.pxy_of_vec(v, 0.)) {
+            let dy = (style.dot_radius + 4 + 4 * style.label_scale) as f64;
+            let xy = xy + Vec2::from([0., dy]);
+            self.draw_text(style.label_color, &xy, style.label_scale, label);
+        }
+    }
+
+    //mi draw_arc
+    /// Draw the great circle arc between two unit vectors, leaving out
+    /// an angle (in radians) at each end, with lines of a width
+    fn draw_arc(&mut self, c: Rgba<u8>, width: u32, v0: &Vec3, v1: &Vec3, gap: f64) {
+        /// The maximum angle between points drawn along the arc
+        const MAX_STEP: f64 = 0.5 * std::f64::consts::PI / 180.0;
+        let cos_angle = v0.dot(v1).clamp(-1., 1.);
+        let angle = cos_angle.acos();
+        if angle <= 2.0 * gap || angle >= std::f64::consts::PI - 1E-9 {
+            return;
+        }
+        let w = (*v1 - *v0 * cos_angle).normalize();
+        let n = (((angle - 2.0 * gap) / MAX_STEP).ceil() as usize).max(1);
+        let mut last = None;
+        for i in 0..=n {
+            let t = gap + (angle - 2.0 * gap) * (i as f64) / (n as f64);
+            let v = *v0 * t.cos() + w * t.sin();
+            let xy = self.pxy_of_vec(&v, self.width as f64);
+            if let (Some(p0), Some(p1)) = (last, xy) {
+                self.draw_wide_line(c, width, &p0, &p1);
+            }
+            last = xy;
+        }
+    }
+
+    //mi draw_wide_line
+    /// Draw a straight line of a width in pixels between two
+    /// coordinates on the screen, as parallel lines half a pixel apart
+    fn draw_wide_line(&mut self, c: Rgba<u8>, width: u32, xy0: &Vec2, xy1: &Vec2) {
+        if width <= 1 {
+            self.draw_line(c, xy0, xy1);
+            return;
+        }
+        let d = *xy1 - *xy0;
+        if d.length() < 1E-9 {
+            return;
+        }
+        let normal: Vec2 = [-d[1] / d.length(), d[0] / d.length()].into();
+        for i in 0..(2 * width - 1) {
+            let offset = normal * (i as f64 * 0.5 - (width - 1) as f64 / 2.0);
+            self.draw_line(c, &(*xy0 + offset), &(*xy1 + offset));
+        }
+    }
+
+    //mp draw_text
+    /// Draw text centered on a point of the window, with a small
+    /// bitmap font at a scale (1 is 7 pixels high)
+    ///
+    /// Only letters (drawn as upper case), digits and a little
+    /// punctuation are drawn; other characters are left as spaces
+    pub fn draw_text(&mut self, c: Rgba<u8>, xy: &Vec2, scale: u32, text: &str) {
+        let scale = scale.max(1);
+        let advance = (font::GLYPH_WIDTH + 1) * scale;
+        let text_width = (text.chars().count() as u32 * advance).saturating_sub(scale);
+        let x0 = xy[0].round() as i64 - (text_width / 2) as i64;
+        let y0 = xy[1].round() as i64 - (font::GLYPH_HEIGHT * scale / 2) as i64;
+        for (i, ch) in text.chars().enumerate() {
+            let Some(rows) = font::glyph(ch) else {
+                continue;
+            };
+            let gx = x0 + (i as u32 * advance) as i64;
+            for (row, bits) in rows.iter().enumerate() {
+                for col in 0..font::GLYPH_WIDTH {
+                    if bits & (0x10 >> col) == 0 {
+                        continue;
+                    }
+                    for dy in 0..scale {
+                        for dx in 0..scale {
+                            let x = gx + (col * scale + dx) as i64;
+                            let y = y0 + (row as u32 * scale + dy) as i64;
+                            if x >= 0 && y >= 0 && x < self.width as i64 && y < self.height as i64 {
+                                self.put(x as u32, y as u32, c);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
     //mp draw_circle
     /// Draw part of a great circle that is defined applying `quat` to
     /// `[1.,0.,0.]` `[cos(angle), sin(angle), 0.]`.
@@ -1,5 +1,9 @@
 //! # Image creation libray (requires `image` feature)
 /// This library utilizes the [image] crate to provide the ability to
 /// create images of sky maps
+mod font;
 mod image_view;
-pub use image_view::{ImageView, StarDrawStyle};
+pub use image_view::{
+    ConstellationLabel, ConstellationStyle, DeepSkyStyle, ImageView, SolarSystemStyle,
+    StarDrawStyle, TrackStyle,
+};
@@ -24,6 +24,13 @@
 //! in the Hipparcos database. Note that some IAU named stars are
 //! *not* in the Hipparcos database.
 //!
+//! The IAU names are added to a catalog with
+//! [Catalog::apply_iau_names], matching by Hipparcos number or (for
+//! stars without one) by position, and reporting the names that
+//! match no star or more than one. The WGSN list, with designations,
+//! is included as [iau::IAU_CSN] for [iau::IauName::builtin]; a newer
+//! 'IAU-CSN.txt' file may be read with [iau::read_csn].
+//!
 //! If the `hipp_bright` feature is used then the Hipparcos catalog
 //! stars of magnitude 8.0 or brighter are included (41,013 stars) as
 //! a postcard string, as [hipparcos::HIPP_BRIGHT_PST]; also 430 'common'
@@ -63,6 +70,75 @@
 //! subcubes can then be used for the efficient geographical
 //! searching.
 //!
+//! Alternative divisions of the sphere are provided through the
+//! [SpatialIndex] trait, which is implemented for subcubes by
+//! [SubcubeIndex] and for HEALPix (nested) pixels by [HealpixIndex];
+//! a [SpatialCells] places the stars of a catalog into the cells of
+//! any [SpatialIndex] for searching.
+//!
+//! When the scale of an image is not known (for example, if the focal
+//! length of the lens is not known) a [TriangleShapeIndex] can be
+//! used to find triangles of stars from the shape of a triangle of
+//! points in the image; this provides the implied plate scale too.
+//! For blind solving a [QuadIndex] provides an astrometry.net style
+//! geometric hash of four-star asterisms, which may be serialized.
+//! Star tracker style identification is supported by a [PairIndex],
+//! a (serializable) table of star pairs sorted by angle, and
+//! [Catalog::lost_in_space] determines the attitude of a camera from
+//! observed star vectors with no prior knowledge of its pointing.
+//! For designing a sensor, [Catalog::field_coverage] determines how
+//! many stars are visible in a [FieldOfView] across the whole sky.
+//! Double stars and other close pairs (which a sensor may not
+//! resolve) are found with [Catalog::close_pairs], and may be
+//! filtered out with [StarFilter::close_pairs].
+//!
+//! The [constellations] module provides stick figures for the 88 IAU
+//! constellations, and [constellations::ConstellationBoundaries] (the
+//! boundary table of Roman, 1987, included with the crate) gives the
+//! constellation of any direction, and the stars within a
+//! constellation with [Catalog::stars_in_constellation].
+//!
+//! Constellation figures are [constellations::Constellation]s, which
+//! are polylines between stars given by their Hipparcos numbers; as
+//! well as the built-in IAU figures, the figures of other sky cultures
+//! can be read from Stellarium files or from JSON, and checked against
+//! a catalog with [constellations::Constellation::missing_ids].
+//! With the image feature they are drawn by
+//! `ImageView::draw_constellations`.
+//!
+//! The [deep_sky] module provides galaxies, clusters and nebulae as
+//! [deep_sky::DeepSkyObject]s, with their size and position angle;
+//! the Messier and Caldwell catalogs and some bright NGC and IC objects
+//! are included, in a [deep_sky::DeepSkyCatalog] that can be searched
+//! around a direction with [deep_sky::DeepSkyCatalog::find_around].
+//! With the image feature their outlines are drawn by
+//! `ImageView::draw_deep_sky_objects`.
+//!
+//! The [solar_system] module provides low-precision positions of the
+//! Sun, Moon and planets at a time (a Julian Date, from the [time]
+//! module), in the same frame as [Star::vector], with their apparent
+//! magnitudes and phases; [solar_system::BodyPosition::to_star]
+//! allows a bright planet to be added to a catalog for matching an
+//! image. With the image feature they are drawn by
+//! `ImageView::draw_solar_system`.
+//!
+//! The [minor_bodies] module reads comet and asteroid orbital elements
+//! in the formats of the Minor Planet Center, and propagates them to
+//! positions and magnitudes at a time, seen from the center of the
+//! Earth or an [solar_system::Observer] on its surface; with
+//! [minor_bodies::positions_around] the bodies in an image can be
+//! identified rather than rejected as false stars. With the image
+//! feature their tracks over a range of dates are drawn by
+//! `ImageView::draw_track`.
+//!
+//! The [satellites] module reads the two-line elements of artificial
+//! satellites and propagates them with the SGP4 model to positions
+//! seen by an [solar_system::Observer]; [satellites::crossings] finds
+//! the satellites that cross a solved field of view during an
+//! exposure, so that their trails can be masked before the image is
+//! matched to stars. Their tracks are also drawn with
+//! `ImageView::draw_track`.
+//!
 //! # Precision
 //!
 //! The naked eye has a resolution of the order of 1 arcminute; this
@@ -147,7 +223,36 @@
 //! Find possible sets of three stars (A, B C) where the three angles between A and B, A and C, and B and C are given - to within an angular tolerance of delta:
 //!
 //! ```rust,ignore
-//!   let candidate_tris = catalog.find_star_triangles(catalog.iter_all(), &[0.1, 0.15, 0.05], 0.003);
+//!   let candidate_tris = catalog.find_star_triangles(catalog.iter_subcubes(), &[0.1, 0.15, 0.05], 0.003);
+//! ```
+//!
+//! The same search can provide [TriangleMatch] results (with the
+//! error in each angle and an overall score), either sorted by score
+//! or to a callback as they are found, optionally with only one
+//! ordering of each set of three stars:
+//!
+//! ```rust,ignore
+//!   let best_tris = catalog.find_star_triangles_ranked(catalog.iter_subcubes(), &[0.1, 0.15, 0.05], 0.003, true);
+//! ```
+//!
+//! How distinctive the triangles of a set of stars are (for example,
+//! for choosing stars to identify an image) is given by
+//! [Catalog::triangle_uniqueness]:
+//!
+//! ```rust,ignore
+//!   let uniqueness = catalog.triangle_uniqueness(&stars, 0.002, 5);
+//! ```
+//!
+//! Patterns of more than three stars, where only some of the angles
+//! between them are known, are described by an [AsterismPattern]:
+//!
+//! ```rust,ignore
+//!   let pattern = AsterismPattern::new(4)
+//!       .with_angle(0, 1, 0.1)
+//!       .with_angle(1, 2, 0.15)
+//!       .with_angle(0, 2, 0.05)
+//!       .with_angle(2, 3, 0.08);
+//!   let asterisms = catalog.find_asterisms(catalog.iter_subcubes(), &pattern, 0.003)?;
 //! ```
 //!
 //! # A full-blown example
@@ -210,25 +315,54 @@ pub type Vec4 = geo_nd::FArray<f64, 4>;
 /// this includes the direction and 'up' for a camera, for example
 pub type Quat = geo_nd::QArray<f64, Vec3, Vec4>;
 
+mod asterism;
 mod catalog;
+mod close_pairs;
+mod coverage;
 mod error;
+mod healpix;
+mod lost_in_space;
+mod pair_index;
+mod quad_index;
+mod spatial_index;
 mod star;
 mod star_filter;
+mod star_vectors;
 mod subcube;
+mod triangle;
+mod triangle_shape;
 
 pub mod cmdline;
 pub mod constellations;
+pub mod deep_sky;
 pub mod hipparcos;
 pub mod iau;
+pub mod minor_bodies;
+pub mod satellites;
+pub mod solar_system;
+pub mod time;
 
 #[cfg(feature = "image")]
 mod image;
 
+pub use asterism::{AsterismMatch, AsterismPattern};
 pub use catalog::{Catalog, CatalogIndex};
+pub use close_pairs::{combined_magnitude, position_angle, ClosePair, ClosePairPolicy};
+pub use coverage::{FieldCoverage, FieldOfView};
 pub use error::Error;
+pub use healpix::HealpixIndex;
+pub use lost_in_space::LostInSpace;
+pub use pair_index::{PairIndex, StarPair};
+pub use quad_index::{QuadIndex, QuadMatch};
+pub use spatial_index::{SpatialCells, SpatialIndex};
 pub use star::Star;
 pub use star_filter::{StarFilter, StarFilterFn};
-pub use subcube::Subcube;
+pub use subcube::{Subcube, SubcubeIndex};
+pub use triangle::{TriangleMatch, TriangleUniqueness};
+pub use triangle_shape::{ShapeMatch, TriangleShapeIndex};
 
 #[cfg(feature = "image")]
-pub use image::{ImageView, StarDrawStyle};
+pub use image::{
+    ConstellationLabel, ConstellationStyle, DeepSkyStyle, ImageView, SolarSystemStyle,
+    StarDrawStyle, TrackStyle,
+};
@@ -0,0 +1,306 @@
+//a Imports
+use geo_nd::{Quaternion, Vector};
+
+use crate::{Catalog, CatalogIndex, Quat, Star, Vec3};
+
+//a Constants
+/// The maximum number of observations used to form the triangles
+/// that are searched for in the catalog
+const MAX_TRIANGLE_OBSERVATIONS: usize = 6;
+
+/// The maximum number of candidate triangles of stars to verify for
+/// each triangle of observations; any more are not verified, and the
+/// result is marked as truncated
+const MAX_CANDIDATES: usize = 2000;
+
+//a Useful functions
+//fi quat_mapping_vector_pairs
+/// Get the rotation that maps f0 to t0 and (as closely as possible)
+/// f1 to t1
+fn quat_mapping_vector_pairs(f0: &Vec3, f1: &Vec3, t0: &Vec3, t1: &Vec3) -> Quat {
+    let z_axis = [0., 0., 1.].into();
+    let f0_to_z = Quat::rotation_of_vec_to_vec(f0, &z_axis);
+    let t0_to_z = Quat::rotation_of_vec_to_vec(t0, &z_axis);
+    let f1_mapped = f0_to_z.apply3(f1);
+    let t1_mapped = t0_to_z.apply3(t1);
+    let angle_f = f1_mapped[1].atan2(f1_mapped[0]);
+    let angle_t = t1_mapped[1].atan2(t1_mapped[0]);
+    let rot_in_z = Quat::of_axis_angle(&z_axis, angle_t - angle_f);
+    t0_to_z.conjugate() * rot_in_z * f0_to_z
+}
+
+//fi average_quats
+/// Average a set of quaternions that are close to each other
+///
+/// As q and -q are the same rotation the quaternions are first made
+/// consistent with the first
+fn average_quats(quats: &[Quat]) -> Quat {
+    let first = quats[0];
+    Quat::weighted_average_many(quats.iter().map(|q| {
+        if q.dot(&first) < 0. {
+            (1.0, -*q)
+        } else {
+            (1.0, *q)
+        }
+    }))
+}
+
+//a LostInSpace
+//tp LostInSpace
+/// The result of [Catalog::lost_in_space]
+#[derive(Debug, Clone)]
+pub struct LostInSpace {
+    /// The attitude: the rotation that maps the vector of a star in
+    /// the catalog to its observed vector
+    pub attitude: Quat,
+    /// The star identified for each observation, if any
+    pub stars: Vec<Option<CatalogIndex>>,
+    /// The confidence in the result, from 0 to 1
+    ///
+    /// This is the fraction of the observations that were identified,
+    /// reduced if a different set of stars could explain almost as
+    /// many of the observations
+    ///
+    /// With exactly three observations there is no star to verify the
+    /// triangle with, so the confidence is instead reduced by the
+    /// error in the fit of the triangle (relative to the tolerance),
+    /// and divided between the different sets of stars that match it
+    /// (and it is zero if the search was truncated, as the number of
+    /// sets is then not known)
+    pub confidence: f64,
+    /// True if more triangles of stars matched a triangle of
+    /// observations than could be verified (2000), so that some
+    /// candidates were not considered
+    ///
+    /// This happens with a large tolerance or a dense catalog; the
+    /// result may then not be the best solution
+    pub truncated: bool,
+}
+
+//ti Candidate
+/// A candidate solution being verified
+struct Candidate {
+    /// The attitude of the candidate
+    attitude: Quat,
+    /// The star identified for each observation
+    stars: Vec<Option<CatalogIndex>>,
+    /// The number of observations identified
+    identified: usize,
+}
+
+//ip Catalog
+impl Catalog {
+    //mp lost_in_space
+    /// Determine the attitude of a camera (or star tracker) from the
+    /// unit vectors of observed stars, with no prior knowledge of
+    /// where it is pointing
+    ///
+    /// The observations should be ordered brightest first; *fov* is
+    /// the maximum angle (in radians) between any two observations,
+    /// and *tolerance* is the maximum error (in radians) in the angle
+    /// between two observations.
+    ///
+    /// Triangles of the brightest observations are found in the
+    /// catalog (with [Catalog::find_star_triangles_with]); each
+    /// candidate triangle gives an attitude, which is verified by
+    /// finding the stars for the remaining observations (as the
+    /// pyramid algorithm does, using a fourth or fifth star). The
+    /// candidate that identifies the most observations is returned,
+    /// with its attitude refined using all of the identified stars.
+    ///
+    /// At least three observations are required, and a triangle must
+    /// be verified by a fourth star (if there is one) to be accepted.
+    ///
+    /// At most 2000 candidate triangles of stars are verified for each
+    /// triangle of observations; if there are more then
+    /// [LostInSpace::truncated] is set in the result.
+    ///
+    /// Needs data to have been derived for the Catalog
+    pub fn lost_in_space(
+        &self,
+        observations: &[Vec3],
+        fov: f64,
+        tolerance: f64,
+    ) -> Option<LostInSpace> {
+        let n = observations.len();
+        if n < 3 {
+            return None;
+        }
+        let required = n.min(4);
+        let num_tri_obs = n.min(MAX_TRIANGLE_OBSERVATIONS);
+        let angle =
+            |a: usize, b: usize| observations[a].dot(&observations[b]).clamp(-1., 1.).acos();
+
+        // Candidates that identify enough observations to be accepted
+        let mut verified: Vec<Candidate> = vec![];
+        let mut truncated = false;
+        'search: for k in 2..num_tri_obs {
+            for j in 1..k {
+                for i in 0..j {
+                    let angles = [angle(i, j), angle(i, k), angle(j, k)];
+                    if angles.iter().any(|a| *a > fov || *a < 2.0 * tolerance) {
+                        continue;
+                    }
+                    let mut candidates = vec![];
+                    self.find_star_triangles_with(
+                        self.iter_subcubes(),
+                        &angles,
+                        tolerance,
+                        false,
+                        |m| {
+                            candidates.push(m.stars);
+                            candidates.len() < MAX_CANDIDATES
+                        },
+                    );
+                    truncated |= candidates.len() >= MAX_CANDIDATES;
+                    for stars in candidates {
+                        let c = self.verify_attitude(observations, [i, j, k], stars, tolerance);
+                        if c.identified >= required {
+                            verified.push(c);
+                        }
+                    }
+                    if verified.iter().any(|c| c.identified == n) {
+                        break 'search;
+                    }
+                }
+            }
+        }
+
+        // The best candidate, and the best of those that are a
+        // different solution (that is, they identify no observation
+        // with the same star)
+        let best = verified.iter().max_by_key(|c| c.identified)?;
+        let others: Vec<usize> = verified
+            .iter()
+            .filter(|c| {
+                !c.stars
+                    .iter()
+                    .zip(best.stars.iter())
+                    .any(|(x, y)| x.is_some() && x == y)
+            })
+            .map(|c| c.identified)
+            .collect();
+        let runner_up = others.iter().copied().max().unwrap_or(0);
+
+        let attitude = self.refine_attitude(observations, best);
+        let stars = self.identify_observations(observations, &attitude, tolerance);
+        let identified = stars.iter().filter(|s| s.is_some()).count();
+        // Three stars always match a candidate triangle, so only the
+        // verified stars beyond those count towards uniqueness
+        let extra = identified.saturating_sub(3) as f64;
+        let runner_up_extra = runner_up.saturating_sub(3) as f64;
+        let uniqueness = if extra > 0. {
+            (1.0 - runner_up_extra / extra).max(0.)
+        } else if truncated {
+            0.
+        } else {
+            // With only a triangle, use how well it fits and how
+            // many other triangles of stars also match
+            let error = stars
+                .iter()
+                .zip(observations.iter())
+                .filter_map(|(s, o)| {
+                    s.map(|s| {
+                        let v = attitude.apply3(&self[s].vector);
+                        v.dot(o).clamp(-1., 1.).acos()
+                    })
+                })
+                .fold(0., f64::max);
+            (1.0 - error / tolerance).max(0.) / (1 + others.len()) as f64
+        };
+        let confidence = identified as f64 / n as f64 * uniqueness;
+        Some(LostInSpace {
+            attitude,
+            stars,
+            confidence,
+            truncated,
+        })
+    }
+
+    //mi verify_attitude
+    /// Determine the attitude implied by three observations matching
+    /// three stars, and identify as many observations as possible
+    /// using it
+    fn verify_attitude(
+        &self,
+        observations: &[Vec3],
+        obs: [usize; 3],
+        stars: [CatalogIndex; 3],
+        tolerance: f64,
+    ) -> Candidate {
+        let quats: Vec<Quat> = [(0, 1), (0, 2), (1, 2)]
+            .iter()
+            .map(|(a, b)| {
+                quat_mapping_vector_pairs(
+                    &self[stars[*a]].vector,
+                    &self[stars[*b]].vector,
+                    &observations[obs[*a]],
+                    &observations[obs[*b]],
+                )
+            })
+            .collect();
+        let attitude = average_quats(&quats);
+        let mut stars_found = self.identify_observations(observations, &attitude, tolerance * 2.0);
+        for (o, s) in obs.iter().zip(stars.iter()) {
+            stars_found[*o] = Some(*s);
+        }
+        let identified = stars_found.iter().filter(|s| s.is_some()).count();
+        Candidate {
+            attitude,
+            stars: stars_found,
+            identified,
+        }
+    }
+
+    //mi identify_observations
+    /// Find the star (if any) for each observation given an attitude
+    fn identify_observations(
+        &self,
+        observations: &[Vec3],
+        attitude: &Quat,
+        tolerance: f64,
+    ) -> Vec<Option<CatalogIndex>> {
+        let inverse = attitude.conjugate();
+        let min_cos = tolerance.cos();
+        let mut result: Vec<Option<CatalogIndex>> = vec![];
+        for o in observations {
+            let v = inverse.apply3(o);
+            let (ra, de) = (v[1].atan2(v[0]), v[2].clamp(-1., 1.).asin());
+            let accepted = result.iter().flatten().count();
+            let star = self
+                .closest_to(ra, de)
+                .filter(|(c, i)| *c >= min_cos && self.filter().call(&self[*i], accepted))
+                .map(|(_, i)| i);
+            // A star can only be identified with one observation
+            let star = star.filter(|s| !result.contains(&Some(*s)));
+            result.push(star);
+        }
+        result
+    }
+
+    //mi refine_attitude
+    /// Refine the attitude of a candidate using all of its identified
+    /// stars
+    fn refine_attitude(&self, observations: &[Vec3], candidate: &Candidate) -> Quat {
+        let identified: Vec<(usize, CatalogIndex)> = candidate
+            .stars
+            .iter()
+            .enumerate()
+            .filter_map(|(o, s)| s.map(|s| (o, s)))
+            .collect();
+        let mut quats = vec![candidate.attitude];
+        for (n, (o0, s0)) in identified.iter().enumerate() {
+            for (o1, s1) in identified[n + 1..].iter() {
+                let v0: &Star = &self[*s0];
+                let v1: &Star = &self[*s1];
+                quats.push(quat_mapping_vector_pairs(
+                    &v0.vector,
+                    &v1.vector,
+                    &observations[*o0],
+                    &observations[*o1],
+                ));
+            }
+        }
+        average_quats(&quats)
+    }
+}
@@ -2,13 +2,21 @@ use std::path::PathBuf;
 
 use anyhow::anyhow;
 use clap::{ArgMatches, Command};
-use geo_nd::Vector;
-use star_catalog::{cmdline, Catalog, CatalogIndex, Star, Subcube};
+use geo_nd::{Quaternion, Vector};
+use star_catalog::constellations::ConstellationBoundaries;
+use star_catalog::minor_bodies::{self, MinorBody};
+use star_catalog::satellites::{self, Satellite};
+use star_catalog::{cmdline, solar_system, time};
+use star_catalog::{Catalog, CatalogIndex, FieldOfView, Quat, Star, Subcube};
 
 #[cfg(feature = "image")]
-use geo_nd::Quaternion;
+use star_catalog::constellations::Constellation;
 #[cfg(feature = "image")]
-use star_catalog::{ImageView, Quat};
+use star_catalog::deep_sky::DeepSkyCatalog;
+#[cfg(feature = "image")]
+use star_catalog::{
+    ConstellationLabel, ConstellationStyle, DeepSkyStyle, ImageView, SolarSystemStyle, TrackStyle,
+};
 
 fn find_id_or_name(
     catalog: &Catalog,
@@ -25,7 +33,7 @@ fn main() -> Result<(), anyhow::Error> {
         .about("Star catlog")
         .version("0.1.0");
 
-    let cmd = cmdline::add_catalog_arg(
+    let cmd = cmdline::add_optional_catalog_arg(
         cmd,
         "Which star catalog to load
 
@@ -34,7 +42,10 @@ feature csv) a '.csv' extension, or (if enabled with feature postcard)
 a '.pst' extension.
 
 Alterrnatively it can be a built-in catalog if no filename extensionis
-provide (if enabled with feature hipp_bright) 'hipp_bright'",
+provide (if enabled with feature hipp_bright) 'hipp_bright'
+
+A catalog is required except for the planets, ephemeris and
+satellites subcommands",
     );
 
     let cmd = cmdline::add_names_arg(
@@ -46,9 +57,17 @@ description.
 
 If a JSON file is specified, it is a list of pairs of (name, id)
 
-Built-in name lists provided are 'hipp' and 'collated'; the former is
-the list of common Hipparcos star names from the ESA website, the
-latter is a collation of various lists that is much larger
+A file with a '.txt' extension is read as the IAU Catalog of Star
+Names ('IAU-CSN.txt' from the IAU WGSN); its stars are found by
+Hipparcos number, or by position (within 1 arcminute) if they have
+none, and names that do not match a star are reported.
+
+Built-in name lists provided are 'hipp', 'collated' and 'iau'; the
+first is the list of common Hipparcos star names from the ESA
+website, the second is a collation of various lists that is much
+larger, and the last is the included IAU Catalog of Star Names,
+matched as for a '.txt' file (but without reporting the names that
+are not in the catalog, such as those of faint stars)
 ",
     );
 
@@ -112,6 +131,30 @@ declination arguments will be used, if required.
 ",
     );
 
+    let cmd = cmdline::add_boundaries_arg(
+        cmd,
+        "File of IAU constellation boundaries to load
+
+This is a table in the format of Roman (1987), 'Identification of a
+constellation from a position', such as the file 'data.dat' of catalog
+VI/42 at the CDS. If not provided then the table included with the
+crate is used.
+
+The boundaries give the constellation reported by 'find', 'planets'
+and 'ephemeris', and are drawn on images and cubemaps if
+'--draw-boundaries' is given.
+",
+    );
+
+    let cmd = cmdline::add_constellation_arg(
+        cmd,
+        "Constellation to select stars from
+
+If provided then only the stars of the catalog within the constellation, given by its IAU abbreviation such as
+'Ori', are kept.
+",
+    );
+
     let list_subcmd = Command::new("list").about("Lists the stars in the catalog");
 
     let find_subcmd = Command::new("find").about("Find stars in the catalog and display them");
@@ -137,6 +180,9 @@ The angle between every pair of stars is determined and written out
 
 The catalog is searched for all triplets of stars that match the
 specified angular separation to the tolerance provided.
+
+The results are sorted by how well they match, best first; the RMS
+error in degrees is reported for each.
 ",
     );
 
@@ -168,6 +214,293 @@ provided by the 'angle' option to the triangle command.
 ",
     );
 
+    let triangle_subcmd = cmdline::add_dedup_arg(
+        triangle_subcmd,
+        "Only report one ordering of each set of three stars
+
+If two or more of the angles are similar then the same three stars may
+match in more than one order; with this flag only the best-matching
+order is reported.
+",
+    );
+
+    let uniqueness_subcmd = Command::new("uniqueness").about(
+        "Determine how unique the triangles of a set of stars are
+
+For every triangle of the stars given the catalog is searched for
+other triangles of stars that match the angles between them to the
+tolerance provided; the number of these and the best matching are
+reported.
+",
+    );
+
+    let uniqueness_subcmd = cmdline::add_angle_arg(
+        uniqueness_subcmd,
+        "Tolerance for the angles between the stars
+
+This provides the angle, in degrees, within which the angles between
+other stars must match those between the stars given for them to be
+reported.
+
+The default is 0.1 degrees
+",
+    );
+
+    let uniqueness_subcmd = cmdline::add_stars_arg(
+        uniqueness_subcmd,
+        "A list of three or more star names/ids.
+",
+    );
+
+    let coverage_subcmd = Command::new("coverage").about(
+        "Report the number of stars visible in a field of view across the sky
+
+The field of view is pointed in directions spread uniformly over the
+sky (the centers of HEALPix pixels), and the number of stars within
+it counted; the minimum, median and maximum counts are reported, and
+the fraction of the sky where fewer than a minimum number of stars
+are visible.
+
+The limiting magnitude is that used to load the catalog.
+",
+    );
+
+    let coverage_subcmd = cmdline::add_fov_arg(
+        coverage_subcmd,
+        "Field of view in degrees
+
+This is the diameter of a circular field of view, or the width of a
+rectangular field of view (if the vertical field of view is given).
+
+The default is 20 degrees
+",
+    );
+
+    let coverage_subcmd = cmdline::add_vfov_arg(
+        coverage_subcmd,
+        "Vertical field of view in degrees
+
+If this is given the field of view is rectangular (for a rectilinear
+lens); otherwise it is circular
+",
+    );
+
+    let coverage_subcmd = cmdline::add_min_stars_arg(
+        coverage_subcmd,
+        "Minimum number of stars required in the field of view
+
+The fraction of the sky where fewer than this many stars are visible
+is reported.
+
+The default is 3
+",
+    );
+
+    let coverage_subcmd = cmdline::add_order_arg(
+        coverage_subcmd,
+        "HEALPix order of the sampling of the sky
+
+The sky is sampled at the centers of 12 * 4^order pixels.
+
+The default is 5 (12,288 samples)
+",
+    );
+
+    let coverage_subcmd = cmdline::add_heatmap_arg(
+        coverage_subcmd,
+        "Output filename for a heat-map cubemap of the star counts
+
+Directions where fewer than the minimum number of stars are visible
+are drawn in red, others from blue (few stars) to white (the most
+stars). Requires the image feature.
+",
+    );
+
+    let coverage_subcmd = cmdline::add_width_arg(
+        coverage_subcmd,
+        "The width in pixels of each face of the heat-map cubemap
+
+The default is 256
+",
+    );
+
+    let coverage_subcmd = cmdline::add_height_arg(
+        coverage_subcmd,
+        "The height in pixels of each face of the heat-map cubemap
+
+The default is 256
+",
+    );
+
+    let planets_subcmd = Command::new("planets").about(
+        "Report the positions of the Sun, Moon and planets
+
+The positions are geocentric, for the equator and equinox of J2000 (as
+for the stars of the catalog); the right ascension and declination are
+in degrees, and the distance in AU. The magnitude and the illuminated
+fraction of the disc are also given.
+
+No star catalog is needed.
+",
+    );
+    let planets_subcmd = cmdline::add_date_arg(
+        planets_subcmd,
+        "The date and time (UT) for the positions of solar system bodies
+
+This is 'YYYY-MM-DD', optionally followed by 'THH:MM' or
+'THH:MM:SS', or a Julian Date. The default is the current time.
+",
+    );
+
+    let ephemeris_subcmd = Command::new("ephemeris").about(
+        "Report the positions of comets and asteroids
+
+The positions are for the equator and equinox of J2000 (as for the
+stars of the catalog), from --date to --end-date; the right ascension
+and declination are in degrees, and the distances from the Earth (or
+the site) and the Sun in AU. Only bodies brighter than the limiting
+magnitude at the start (or without a magnitude) are reported.
+
+No star catalog is needed.
+",
+    );
+    let ephemeris_subcmd = cmdline::add_minor_bodies_arg(
+        ephemeris_subcmd,
+        "A file of comet or asteroid orbital elements to use
+
+The file is in the format of the Minor Planet Center's 'MPCORB.DAT' or
+'CometEls.txt' (or a mixture of the two).
+",
+    );
+    let ephemeris_subcmd = cmdline::add_date_arg(
+        ephemeris_subcmd,
+        "The date and time (UT) of the first position
+
+This is 'YYYY-MM-DD', optionally followed by 'THH:MM' or
+'THH:MM:SS', or a Julian Date. The default is the current time.
+",
+    );
+    let ephemeris_subcmd = cmdline::add_end_date_arg(
+        ephemeris_subcmd,
+        "The date and time (UT) of the last position
+
+This is as for --date; the default is the date.
+",
+    );
+    let ephemeris_subcmd = cmdline::add_step_arg(
+        ephemeris_subcmd,
+        "The step between positions along a track
+
+This is in days, or in seconds, minutes or hours with a suffix of
+'s', 'm' or 'h'. The default is 1 day.
+",
+    );
+    let ephemeris_subcmd = cmdline::add_site_arg(
+        ephemeris_subcmd,
+        "The site of the observer, as 'LATITUDE,LONGITUDE[,HEIGHT]'
+
+The latitude and longitude (positive to the east) are in degrees, and
+the height in meters. Positions are geocentric if this is not given.
+",
+    );
+
+    let satellites_subcmd = Command::new("satellites").about(
+        "Report the satellites that cross a field of view
+
+The satellites are propagated from their two-line elements with the
+SGP4 model, and those seen from the site to cross the field of view
+(above the horizon) between --date and --end-date are reported, with
+the times (UT) and the right ascension and declination (in degrees,
+J2000) at which each enters and leaves the field. A satellite lit by
+the Sun during a crossing may leave a trail in an image of the field.
+
+No star catalog is needed.
+",
+    );
+    let satellites_subcmd = cmdline::add_satellites_arg(
+        satellites_subcmd,
+        "A file of satellite two-line elements to use
+
+The file is in the two-line or three-line format published by
+Celestrak or Space-Track, with an optional line of the name of each
+satellite before its elements.
+",
+    );
+    let satellites_subcmd = cmdline::add_site_arg(
+        satellites_subcmd,
+        "The site of the observer, as 'LATITUDE,LONGITUDE[,HEIGHT]'
+
+The latitude and longitude (positive to the east) are in degrees, and
+the height in meters. This must be given.
+",
+    );
+    let satellites_subcmd = cmdline::add_date_arg(
+        satellites_subcmd,
+        "The date and time (UT) of the start of the exposure
+
+This is 'YYYY-MM-DD', optionally followed by 'THH:MM' or
+'THH:MM:SS', or a Julian Date. The default is the current time.
+",
+    );
+    let satellites_subcmd = cmdline::add_end_date_arg(
+        satellites_subcmd,
+        "The date and time (UT) of the end of the exposure
+
+This is as for --date; the default is one minute after the date.
+",
+    );
+    let satellites_subcmd = cmdline::add_step_arg(
+        satellites_subcmd,
+        "The step between positions when searching for crossings
+
+This is in days, or in seconds, minutes or hours with a suffix of
+'s', 'm' or 'h' (such as '10s'). Steps near the field are refined to
+an eighth of the size of the field.
+
+The default is 10 seconds.
+",
+    );
+    let satellites_subcmd = cmdline::add_right_ascension_arg(
+        satellites_subcmd,
+        "The right ascension of the center of the field of view
+
+This is in degrees, and defaults to 0.
+",
+    );
+    let satellites_subcmd = cmdline::add_declination_arg(
+        satellites_subcmd,
+        "The declination of the center of the field of view
+
+This is in degrees, and defaults to 0.
+",
+    );
+    let satellites_subcmd = cmdline::add_angle_arg(
+        satellites_subcmd,
+        "Angle to rotate 'up' by.
+
+The 'up' of the field of view is north, rotated anticlockwise by this
+angle, as for the image subcommand.
+
+This is in degrees, and defaults to 0.
+",
+    );
+    let satellites_subcmd = cmdline::add_fov_arg(
+        satellites_subcmd,
+        "The horizontal field of view in degrees
+
+If no vertical field of view is given then the field is circular with
+this diameter. The default is 20.
+",
+    );
+    let satellites_subcmd = cmdline::add_vfov_arg(
+        satellites_subcmd,
+        "The vertical field of view in degrees
+
+If this is given then the field is rectangular, with the horizontal
+field of view as its width.
+",
+    );
+
     let write_subcmd =
         Command::new("write").about("Write out the catalog (after star region selection)");
     let write_subcmd = cmdline::add_output_arg(
@@ -181,6 +514,42 @@ written (if the 'postcard' feature is enabled).
 ",
     );
 
+    let write_subcmd = cmdline::add_max_per_cell_arg(
+        write_subcmd,
+        "Thin the catalog to at most this many stars per cell
+
+If this is given then only the brightest stars in each cell (subcube)
+of the sky are written, giving a catalog of roughly uniform density;
+every cell with stars keeps at least one (unless its stars are all
+within the minimum separation of a brighter star in a neighboring
+cell), so there are no holes. The names of the stars that are written
+are kept.
+",
+    );
+
+    let write_subcmd = cmdline::add_min_separation_arg(
+        write_subcmd,
+        "Minimum separation of stars in degrees when thinning
+
+When thinning, any star within this angle of a brighter star that is
+kept is dropped, as (for example) a sensor could not resolve the two.
+
+The default is 0 (no stars are dropped for their separation)
+",
+    );
+
+    let write_subcmd = cmdline::add_cells_per_side_arg(
+        write_subcmd,
+        "Number of cells per side of the cube when thinning
+
+The sky is divided into cells by dividing the cube with corners at
++-1 into this many subcubes per side; this sets the size of the cells
+for the maximum number of stars per cell.
+
+The default is chosen from the number of stars in the catalog
+",
+    );
+
     let image_subcmd = Command::new("image").about("Generate an image of part of the sky");
     let image_subcmd = cmdline::add_output_arg(image_subcmd, "Specify the output image filaname.
 
@@ -265,6 +634,106 @@ This is in degrees, and defaults to 60.
 ",
     );
 
+    let image_subcmd = cmdline::add_deep_sky_arg(
+        image_subcmd,
+        "Draw deep-sky objects, optionally from a file
+
+Draw the outlines of deep-sky objects, with their designations.
+
+If no file is given then the built-in Messier and Caldwell objects and
+bright NGC and IC objects are drawn. Otherwise the objects are read
+from the file, which must be in the text format of the built-in
+tables.
+",
+    );
+
+    let image_subcmd = cmdline::add_draw_boundaries_arg(
+        image_subcmd,
+        "Draw the boundaries between the IAU constellations
+
+The boundaries are those given by --boundaries (or the table included
+with the crate).
+",
+    );
+
+    let image_subcmd = cmdline::add_planets_arg(
+        image_subcmd,
+        "Draw the Sun, Moon and planets, with their names
+
+The positions are for the time given by --date (or the current time).
+",
+    );
+
+    let image_subcmd = cmdline::add_date_arg(
+        image_subcmd,
+        "The date and time (UT) for the positions of solar system bodies
+
+This is 'YYYY-MM-DD', optionally followed by 'THH:MM' or
+'THH:MM:SS', or a Julian Date. The default is the current time.
+",
+    );
+
+    let image_subcmd = cmdline::add_minor_bodies_arg(
+        image_subcmd,
+        "Draw the tracks of comets and asteroids from a file of elements
+
+The file is in the format of the Minor Planet Center's 'MPCORB.DAT' or
+'CometEls.txt'. Each body brighter than the limiting magnitude at
+--date is drawn as a track to --end-date, with its designation.
+",
+    );
+
+    let image_subcmd = cmdline::add_end_date_arg(
+        image_subcmd,
+        "The date and time (UT) of the end of the tracks of minor bodies
+
+This is as for --date; the default is the date, so that each track is
+a single position.
+",
+    );
+
+    let image_subcmd = cmdline::add_step_arg(
+        image_subcmd,
+        "The step between positions along the track of a minor body
+
+This is in days, or in seconds, minutes or hours with a suffix of
+'s', 'm' or 'h'. The default is 1 day.
+",
+    );
+
+    let image_subcmd = cmdline::add_satellites_arg(
+        image_subcmd,
+        "Draw the tracks of satellites from a file of two-line elements
+
+Each satellite is drawn where it is above the horizon of the site
+between --date and --end-date, at steps of 10 seconds. This requires
+--site.
+",
+    );
+
+    let image_subcmd = cmdline::add_site_arg(
+        image_subcmd,
+        "The site of the observer, as 'LATITUDE,LONGITUDE[,HEIGHT]'
+
+The latitude and longitude (positive to the east) are in degrees, and
+the height in meters. Positions of minor bodies are geocentric if this
+is not given; it is required for satellites.
+",
+    );
+
+    let image_subcmd = cmdline::add_constellations_arg(
+        image_subcmd,
+        "Draw constellation figures, optionally from a file
+
+Draw the figures of the constellations, with their names.
+
+If no file is given then the figures of the 88 IAU constellations are
+drawn. Otherwise the figures are read from the file, which may be a
+Stellarium 'constellationship.fab' or 'index.json' file (for any sky
+culture), or a JSON list of constellations; 'none' draws no figures.
+",
+    );
+
     let cubemap_subcmd = Command::new("cubemap").about("Generate an cubemap of part of the sky");
     let cubemap_subcmd = cmdline::add_output_arg(
         cubemap_subcmd,
@@ -339,6 +808,109 @@ Note that 'angle' is applied *AFTER* this.
 ",
     );
 
+    let cubemap_subcmd = cmdline::add_deep_sky_arg(
+        cubemap_subcmd,
+        "Draw deep-sky objects, optionally from a file
+
+Draw the outlines of deep-sky objects, with their designations.
+
+If no file is given then the built-in Messier and Caldwell objects and
+bright NGC and IC objects are drawn. Otherwise the objects are read
+from the file, which must be in the text format of the built-in
+tables.
+",
+    );
+
+    let cubemap_subcmd = cmdline::add_draw_boundaries_arg(
+        cubemap_subcmd,
+        "Draw the boundaries between the IAU constellations
+
+The boundaries are those given by --boundaries (or the table included
+with the crate).
+",
+    );
+
+    let cubemap_subcmd = cmdline::add_planets_arg(
+        cubemap_subcmd,
+        "Draw the Sun, Moon and planets, with their names
+
+The positions are for the time given by --date (or the current time).
+",
+    );
+
+    let cubemap_subcmd = cmdline::add_date_arg(
+        cubemap_subcmd,
+        "The date and time (UT) for the positions of solar system bodies
+
+This is 'YYYY-MM-DD', optionally followed by 'THH:MM' or
+'THH:MM:SS', or a Julian Date. The default is the current time.
+",
+    );
+
+    let cubemap_subcmd = cmdline::add_minor_bodies_arg(
+        cubemap_subcmd,
+        "Draw the tracks of comets and asteroids from a file of elements
+
+The file is in the format of the Minor Planet Center's 'MPCORB.DAT' or
+'CometEls.txt'. Each body brighter than the limiting magnitude at
+--date is drawn as a track to --end-date, with its designation.
+",
+    );
+
+    let cubemap_subcmd = cmdline::add_end_date_arg(
+        cubemap_subcmd,
+        "The date and time (UT) of the end of the tracks of minor bodies
+
+This is as for --date; the default is the date, so that each track is
+a single position.
+",
+    );
+
+    let cubemap_subcmd = cmdline::add_step_arg(
+        cubemap_subcmd,
+        "The step between positions along the track of a minor body
+
+This is in days, or in seconds, minutes or hours with a suffix of
+'s', 'm' or 'h'. The default is 1 day.
+",
+    );
+
+    let cubemap_subcmd = cmdline::add_satellites_arg(
+        cubemap_subcmd,
+        "Draw the tracks of satellites from a file of two-line elements
+
+Each satellite is drawn where it is above the horizon of the site
+between --date and --end-date, at steps of 10 seconds. This requires
+--site.
+",
+    );
+
+    let cubemap_subcmd = cmdline::add_site_arg(
+        cubemap_subcmd,
+        "The site of the observer, as 'LATITUDE,LONGITUDE[,HEIGHT]'
+
+The latitude and longitude (positive to the east) are in degrees, and
+the height in meters. Positions of minor bodies are geocentric if this
+is not given; it is required for satellites.
+",
+    );
+
+    let cubemap_subcmd = cmdline::add_constellations_arg(
+        cubemap_subcmd,
+        "Draw constellation figures, optionally from a file
+
+Draw the figures of the constellations, with their names.
+
+If no file is given then the figures of the 88 IAU constellations are
+drawn. Otherwise the figures are read from the file, which may be a
+Stellarium 'constellationship.fab' or 'index.json' file (for any sky
+culture), or a JSON list of constellations; 'none' draws no figures.
+
+If this option is not given then the figures of the 88 IAU
+constellations are drawn without names.
+",
+    );
+
     let cubemap_subcmd = cmdline::add_angle_arg(
         cubemap_subcmd,
         "Angle to rotate 'up' by.
@@ -353,7 +925,12 @@ This is in degrees, and defaults to 0.
     let cmd = cmd.subcommand(find_subcmd);
     let cmd = cmd.subcommand(angle_subcmd);
     let cmd = cmd.subcommand(triangle_subcmd);
+    let cmd = cmd.subcommand(uniqueness_subcmd);
+    let cmd = cmd.subcommand(coverage_subcmd);
     let cmd = cmd.subcommand(write_subcmd);
+    let cmd = cmd.subcommand(planets_subcmd);
+    let cmd = cmd.subcommand(ephemeris_subcmd);
+    let cmd = cmd.subcommand(satellites_subcmd);
 
     #[cfg(feature = "image")]
     let cmd = { cmd.subcommand(image_subcmd) };
@@ -368,7 +945,33 @@ This is in degrees, and defaults to 0.
     let matches = cmd.get_matches();
 
     let magnitude = cmdline::magnitude(&matches, 12.0);
-    let catalog_filename: PathBuf = cmdline::catalog(&matches).into();
+    let boundaries = {
+        if let Some(boundaries_filename) = cmdline::boundaries(&matches) {
+            let s = std::fs::read_to_string(boundaries_filename)?;
+            s.parse::<ConstellationBoundaries>()?
+        } else {
+            ConstellationBoundaries::builtin()
+        }
+    };
+
+    // Subcommands that do not use the star catalog
+    match matches.subcommand() {
+        Some(("planets", sub_matches)) => {
+            return planets(&boundaries, sub_matches);
+        }
+        Some(("ephemeris", sub_matches)) => {
+            return ephemeris(&boundaries, magnitude, sub_matches);
+        }
+        Some(("satellites", sub_matches)) => {
+            return satellite_crossings(sub_matches);
+        }
+        _ => (),
+    }
+
+    let Some(catalog_filename) = cmdline::optional_catalog(&matches) else {
+        return Err(anyhow!("A star catalog must be given for this subcommand"));
+    };
+    let catalog_filename: PathBuf = catalog_filename.into();
 
     let mut catalog = {
         match catalog_filename.extension().and_then(|x| x.to_str()) {
@@ -428,11 +1031,17 @@ This is in degrees, and defaults to 0.
                 let id_names: Vec<(usize, String)> = serde_json::from_str(&s)?;
                 catalog.add_names(&id_names, true)?;
             }
+            Some("txt") => {
+                let s = std::fs::read_to_string(names_filename)?;
+                apply_iau_names(&mut catalog, &star_catalog::iau::read_csn(&s)?, true);
+            }
             None => {
                 if names_filename.as_os_str().as_encoded_bytes() == b"hipp" {
                     catalog.add_names(star_catalog::hipparcos::HIP_ALIASES, true)?;
                 } else if names_filename.as_os_str().as_encoded_bytes() == b"collated" {
                     catalog.add_names(star_catalog::hipparcos::HIP_COLLATED_ALIASES, true)?;
+                } else if names_filename.as_os_str().as_encoded_bytes() == b"iau" {
+                    apply_iau_names(&mut catalog, &star_catalog::iau::IauName::builtin(), false);
                 } else {
                     Err(anyhow!("Unknown builtin file {}", names_filename.display()))?
                 }
@@ -467,6 +1076,17 @@ This is in degrees, and defaults to 0.
         catalog.sort();
     }
 
+    if let Some(constellation) = cmdline::constellation(&matches) {
+        let mut ids: Vec<usize> = catalog
+            .stars_in_constellation(&boundaries, constellation)?
+            .into_iter()
+            .map(|i| catalog[i].id)
+            .collect();
+        ids.sort();
+        catalog.retain(move |s, _n| ids.binary_search(&s.id).is_ok());
+        catalog.sort();
+    }
+
     catalog.sort();
     catalog.derive_data();
     match matches.subcommand() {
@@ -474,10 +1094,10 @@ This is in degrees, and defaults to 0.
             list(catalog, sub_matches)?;
         }
         Some(("image", sub_matches)) => {
-            image(catalog, sub_matches)?;
+            image(catalog, &boundaries, magnitude, sub_matches)?;
         }
         Some(("cubemap", sub_matches)) => {
-            cubemap(catalog, sub_matches)?;
+            cubemap(catalog, &boundaries, magnitude, sub_matches)?;
         }
         Some(("triangle", sub_matches)) => {
             find_triangle(catalog, sub_matches)?;
@@ -486,11 +1106,17 @@ This is in degrees, and defaults to 0.
             write(catalog, sub_matches)?;
         }
         Some(("find", sub_matches)) => {
-            find(catalog, sub_matches)?;
+            find(catalog, &boundaries, sub_matches)?;
         }
         Some(("angle_between", sub_matches)) => {
             angle_between(catalog, sub_matches)?;
         }
+        Some(("uniqueness", sub_matches)) => {
+            uniqueness(catalog, sub_matches)?;
+        }
+        Some(("coverage", sub_matches)) => {
+            coverage(catalog, magnitude, sub_matches)?;
+        }
         _ => {
             println!("Catalog has {} stars", catalog.len());
         }
@@ -498,6 +1124,28 @@ This is in degrees, and defaults to 0.
     Ok(())
 }
 
+fn apply_iau_names(
+    catalog: &mut Catalog,
+    names: &[star_catalog::iau::IauName],
+    report_unmatched: bool,
+) {
+    catalog.derive_data();
+    let matches = catalog.apply_iau_names(names, (1.0_f64 / 60.0).to_radians());
+    if report_unmatched {
+        for i in &matches.unmatched {
+            eprintln!("IAU name {} does not match a star", names[*i].name);
+        }
+    }
+    for (i, stars) in &matches.ambiguous {
+        let ids: Vec<String> = stars.iter().map(|s| catalog[*s].id.to_string()).collect();
+        eprintln!(
+            "IAU name {} is ambiguous between stars {}",
+            names[*i].name,
+            ids.join(", ")
+        );
+    }
+}
+
 fn display_star(s: &Star) {
     let id = s.id;
     let ra = s.ra * 180.0 / std::f64::consts::PI;
@@ -507,12 +1155,18 @@ fn display_star(s: &Star) {
     println!("{id:8} : {ra}, {de} : {ly} :{mag}");
 }
 
-fn find(catalog: Catalog, matches: &ArgMatches) -> Result<(), anyhow::Error> {
+fn find(
+    catalog: Catalog,
+    boundaries: &ConstellationBoundaries,
+    matches: &ArgMatches,
+) -> Result<(), anyhow::Error> {
     if let Some(stars) = cmdline::stars(matches) {
         for s in stars {
             match find_id_or_name(&catalog, Some(s)) {
                 Ok(Some(index)) => {
                     display_star(&catalog[index]);
+                    let constellation = boundaries.constellation_of(&catalog[index].vector);
+                    println!("         in {}", constellation.unwrap_or("?"));
                 }
                 Err(e) => {
                     eprintln!("{e}");
@@ -525,6 +1179,143 @@ fn find(catalog: Catalog, matches: &ArgMatches) -> Result<(), anyhow::Error> {
     Ok(())
 }
 
+fn planets(
+    boundaries: &ConstellationBoundaries,
+    matches: &ArgMatches,
+) -> Result<(), anyhow::Error> {
+    let jd = cmdline::date(matches).unwrap_or_else(time::julian_date_now);
+    let (year, month, day) = time::calendar_date(jd);
+    println!("Positions at {year:04}-{month:02}-{day:06.3} (JD {jd:.4})");
+    for p in solar_system::positions(jd) {
+        let name = p.body.name();
+        let ra = p.ra.to_degrees();
+        let de = p.de.to_degrees();
+        let distance = p.distance;
+        let mag = p.mag;
+        let illuminated = p.illuminated;
+        print!("{name:8} : {ra:8.3}, {de:7.3} : {distance:9.5} AU : {mag:6.2} : {illuminated:.3}");
+        print!(
+            " : in {}",
+            boundaries.constellation_of(&p.vector).unwrap_or("?")
+        );
+        println!();
+    }
+    Ok(())
+}
+
+fn ephemeris(
+    boundaries: &ConstellationBoundaries,
+    magnitude: f32,
+    matches: &ArgMatches,
+) -> Result<(), anyhow::Error> {
+    let Some(bodies) = read_minor_bodies(matches)? else {
+        return Err(anyhow!(
+            "A file of orbital elements must be given with --minor-bodies"
+        ));
+    };
+    let start = cmdline::date(matches).unwrap_or_else(time::julian_date_now);
+    let end = cmdline::end_date(matches).unwrap_or(start);
+    let step = cmdline::step(matches, 1.0);
+    let site = cmdline::site(matches);
+    for b in &bodies {
+        if !b.position(start, site.as_ref()).brighter_than(magnitude) {
+            continue;
+        }
+        println!("{}", b.designation);
+        for p in b.track(start, end, step, site.as_ref()) {
+            let (year, month, day) = time::calendar_date(p.jd);
+            let ra = p.ra.to_degrees();
+            let de = p.de.to_degrees();
+            let distance = p.distance;
+            let sun_distance = p.sun_distance;
+            print!("  {year:04}-{month:02}-{day:06.3} : {ra:8.3}, {de:7.3} : {distance:9.5} AU : {sun_distance:8.5} AU");
+            if let Some(mag) = p.mag {
+                print!(" : {mag:5.2}");
+            }
+            print!(
+                " : in {}",
+                boundaries.constellation_of(&p.vector).unwrap_or("?")
+            );
+            println!();
+        }
+    }
+    Ok(())
+}
+
+fn read_minor_bodies(matches: &ArgMatches) -> Result<Option<Vec<MinorBody>>, anyhow::Error> {
+    let Some(filename) = cmdline::minor_bodies(matches) else {
+        return Ok(None);
+    };
+    let s = std::fs::read_to_string(filename)?;
+    Ok(Some(minor_bodies::read_mpc(&s)?))
+}
+
+fn satellite_crossings(matches: &ArgMatches) -> Result<(), anyhow::Error> {
+    let Some(satellites) = read_satellites(matches)? else {
+        return Err(anyhow!(
+            "A file of two-line elements must be given with --satellites"
+        ));
+    };
+    let Some(site) = cmdline::site(matches) else {
+        return Err(anyhow!(
+            "The site of the observer must be given with --site"
+        ));
+    };
+    let start = cmdline::date(matches).unwrap_or_else(time::julian_date_now);
+    let end = cmdline::end_date(matches).unwrap_or(start + 1.0 / 1440.0);
+    let step = cmdline::step(matches, 10.0 / 86400.0);
+    let fov = field_of_view(matches)?;
+    let v = Star::vec_of_ra_de(
+        cmdline::right_ascension(matches, 0.),
+        cmdline::declination(matches, 0.),
+    );
+    let angle = cmdline::angle(matches, 0.0);
+    let orient = Quat::look_at(&v, &[0., 0., 1.].into());
+    let orient = Quat::of_axis_angle(&[0., 0., 1.].into(), angle) * orient;
+
+    let crossings = satellites::crossings(&satellites, &site, &fov, &orient, start, end, step);
+    println!(
+        "Crossings of the field from {} to {} : {}",
+        time::format_date(start),
+        time::format_date(end),
+        crossings.len()
+    );
+    for c in &crossings {
+        let satellite = &satellites[c.satellite];
+        let entry = &c.positions[0];
+        let exit = &c.positions[c.positions.len() - 1];
+        println!(
+            "{:24} : {} {:8.3}, {:7.3} : {} {:8.3}, {:7.3} : {}",
+            satellite.name(),
+            time::format_date(entry.jd),
+            entry.ra.to_degrees(),
+            entry.de.to_degrees(),
+            time::format_date(exit.jd),
+            exit.ra.to_degrees(),
+            exit.de.to_degrees(),
+            if c.is_sunlit() { "sunlit" } else { "in shadow" }
+        );
+    }
+    Ok(())
+}
+
+fn read_satellites(matches: &ArgMatches) -> Result<Option<Vec<Satellite>>, anyhow::Error> {
+    let Some(filename) = cmdline::satellites(matches) else {
+        return Ok(None);
+    };
+    let s = std::fs::read_to_string(filename)?;
+    let mut satellites = satellites::read_satellites(&s)?;
+    let num_satellites = satellites.len();
+    satellites.retain(|s| !s.is_deep_space());
+    if satellites.len() < num_satellites {
+        eprintln!(
+            "Ignoring {} deep-space satellites (periods of 225 minutes or more), which cannot be propagated accurately",
+            num_satellites - satellites.len()
+        );
+    }
+    Ok(Some(satellites))
+}
+
 fn angle_between(catalog: Catalog, matches: &ArgMatches) -> Result<(), anyhow::Error> {
     if let Some(stars) = cmdline::stars(matches) {
         let mut star_indices = vec![];
@@ -583,20 +1374,142 @@ fn find_triangle(catalog: Catalog, matches: &ArgMatches) -> Result<(), anyhow::E
 
     let max_angle_delta = cmdline::angle(matches, 0.1);
 
-    let subcube_iter = Subcube::iter_all();
-    let r = catalog.find_star_triangles(subcube_iter, &angles_to_find, max_angle_delta);
-    for (a, b, c) in &r {
-        let a01 = catalog[*a].cos_angle_between(&catalog[*b]).acos() * 180.0 / std::f64::consts::PI;
-        let a02 = catalog[*a].cos_angle_between(&catalog[*c]).acos() * 180.0 / std::f64::consts::PI;
-        let a12 = catalog[*b].cos_angle_between(&catalog[*c]).acos() * 180.0 / std::f64::consts::PI;
+    let subcube_iter = catalog.iter_subcubes();
+    let r = catalog.find_star_triangles_ranked(
+        subcube_iter,
+        &angles_to_find,
+        max_angle_delta,
+        cmdline::dedup(matches),
+    );
+    for m in &r {
+        let [a, b, c] = m.stars;
+        let [a01, a02, a12] = m.angles.map(|a| a.to_degrees());
         println!(
-            "{}, {}, {} : {} {} {}",
-            catalog[*a].id, catalog[*b].id, catalog[*c].id, a01, a02, a12,
+            "{}, {}, {} : {} {} {} : {}",
+            catalog[a].id,
+            catalog[b].id,
+            catalog[c].id,
+            a01,
+            a02,
+            a12,
+            m.score.to_degrees(),
         );
     }
     Ok(())
 }
 
+fn uniqueness(catalog: Catalog, matches: &ArgMatches) -> Result<(), anyhow::Error> {
+    let mut stars = vec![];
+    if let Some(names) = cmdline::stars(matches) {
+        for s in names {
+            stars.push(catalog.find_id_or_name(s)?);
+        }
+    }
+    if stars.len() < 3 {
+        return Err(anyhow!(
+            "At least three stars must be specified to determine uniqueness"
+        ));
+    }
+
+    let max_angle_delta = cmdline::angle(matches, 0.1);
+
+    let r = catalog.triangle_uniqueness(&stars, max_angle_delta, 5);
+    for t in &r {
+        let [a, b, c] = t.stars;
+        let [a01, a02, a12] = t.angles.map(|a| a.to_degrees());
+        println!(
+            "{}, {}, {} : {} {} {} : {} competing",
+            catalog[a].id, catalog[b].id, catalog[c].id, a01, a02, a12, t.num_competing,
+        );
+        for m in &t.nearest {
+            let [a, b, c] = m.stars;
+            let [a01, a02, a12] = m.angles.map(|a| a.to_degrees());
+            println!(
+                "    {}, {}, {} : {} {} {} : {}",
+                catalog[a].id,
+                catalog[b].id,
+                catalog[c].id,
+                a01,
+                a02,
+                a12,
+                m.score.to_degrees(),
+            );
+        }
+    }
+    let num_unique = r.iter().filter(|t| t.is_unique()).count();
+    println!(
+        "{} of {} triangles are unique to within {} degrees",
+        num_unique,
+        r.len(),
+        max_angle_delta.to_degrees()
+    );
+    Ok(())
+}
+
+fn field_of_view(matches: &ArgMatches) -> Result<FieldOfView, anyhow::Error> {
+    let fov = cmdline::fov(matches, 20.0);
+    let fov = match cmdline::vfov(matches) {
+        Some(vfov) => FieldOfView::Rectangular(fov, vfov),
+        None => FieldOfView::Circular(fov),
+    };
+    fov.validate()?;
+    Ok(fov)
+}
+
+fn coverage(catalog: Catalog, magnitude: f32, matches: &ArgMatches) -> Result<(), anyhow::Error> {
+    let fov = field_of_view(matches)?;
+    let min_stars = cmdline::min_stars(matches, 3);
+    let order = cmdline::order(matches, 5);
+
+    let coverage = catalog.field_coverage(fov, magnitude, order)?;
+    match fov {
+        FieldOfView::Circular(d) => {
+            println!("Field of view        : {} degrees circular", d.to_degrees())
+        }
+        FieldOfView::Rectangular(w, h) => println!(
+            "Field of view        : {} by {} degrees rectangular",
+            w.to_degrees(),
+            h.to_degrees()
+        ),
+    }
+    println!("Limiting magnitude   : {}", magnitude);
+    println!("Samples              : {}", coverage.counts().len());
+    println!("Minimum stars        : {}", coverage.min());
+    println!("Median stars         : {}", coverage.median());
+    println!("Maximum stars        : {}", coverage.max());
+    println!("Mean stars           : {:.2}", coverage.mean());
+    println!(
+        "Sky with fewer than {} stars : {:.3}%",
+        min_stars,
+        coverage.fraction_below(min_stars) * 100.0
+    );
+
+    if let Some(heatmap) = cmdline::heatmap(matches) {
+        #[cfg(feature = "image")]
+        {
+            let width = cmdline::width(matches, 256) as u32;
+            let height = cmdline::height(matches, 256) as u32;
+            let image = image::DynamicImage::new_rgb8(width * 4, height * 3);
+            let mut image_view = ImageView::new(image);
+            image_view.set_tan_hfov(1.0);
+            for quadrant in 0..6 {
+                let (x_ofs, y_ofs, face_orient) = ImageView::cubemap_face(quadrant);
+                image_view.set_window((x_ofs * width, y_ofs * height), width, height);
+                image_view.set_orient(face_orient);
+                image_view.draw_field_coverage(&coverage, min_stars);
+            }
+            let image = image_view.take_image();
+            image.save(heatmap)?;
+        }
+        #[cfg(not(feature = "image"))]
+        Err(anyhow!(
+            "Cannot write heat-map {} without the image feature",
+            heatmap
+        ))?
+    }
+    Ok(())
+}
+
 fn list(catalog: Catalog, _matches: &ArgMatches) -> Result<(), anyhow::Error> {
     for s in catalog.iter_stars() {
         display_star(s);
@@ -604,8 +1517,28 @@ fn list(catalog: Catalog, _matches: &ArgMatches) -> Result<(), anyhow::Error> {
     Ok(())
 }
 
-fn write(catalog: Catalog, matches: &ArgMatches) -> Result<(), anyhow::Error> {
+fn write(mut catalog: Catalog, matches: &ArgMatches) -> Result<(), anyhow::Error> {
     use std::io::Write;
+    if let Some(max_per_cell) = cmdline::max_per_cell(matches) {
+        let min_separation = cmdline::min_separation(matches, 0.0);
+        let cells_per_side = cmdline::cells_per_side(matches);
+        if cells_per_side.is_some_and(|n| !(1..=Subcube::MAX_ELE_PER_SIDE).contains(&n)) {
+            return Err(anyhow!(
+                "The number of cells per side must be from 1 to {}",
+                Subcube::MAX_ELE_PER_SIDE
+            ));
+        }
+        catalog.set_subcube_ele_per_side(cells_per_side);
+        catalog.derive_data();
+        let num_stars = catalog.len();
+        catalog.thin_uniform(max_per_cell, min_separation);
+        catalog.sort();
+        eprintln!(
+            "Thinned catalog from {} to {} stars",
+            num_stars,
+            catalog.len()
+        );
+    }
     let output_filename: PathBuf = cmdline::output(matches).into();
     match output_filename.extension().and_then(|x| x.to_str()) {
         Some("json") => {
@@ -627,8 +1560,15 @@ fn write(catalog: Catalog, matches: &ArgMatches) -> Result<(), anyhow::Error> {
     Ok(())
 }
 
-fn image(catalog: Catalog, matches: &ArgMatches) -> Result<(), anyhow::Error> {
+fn image(
+    catalog: Catalog,
+    boundaries: &ConstellationBoundaries,
+    magnitude: f32,
+    matches: &ArgMatches,
+) -> Result<(), anyhow::Error> {
     let _ = &catalog;
+    let _ = boundaries;
+    let _ = magnitude;
     let _ = matches;
     #[cfg(feature = "image")]
     {
@@ -659,25 +1599,170 @@ fn image(catalog: Catalog, matches: &ArgMatches) -> Result<(), anyhow::Error> {
             .set_orient(orient)
             .set_star_size(width / 200);
         let output_filename: PathBuf = cmdline::output(matches).into();
+        let constellations = read_constellations(matches)?;
+        let constellation_style = ConstellationStyle {
+            gap: (width / 200) as f64 + 2.0,
+            label: Some(ConstellationLabel::Name),
+            ..Default::default()
+        };
+        let deep_sky = read_deep_sky(matches)?;
+        let deep_sky_style = DeepSkyStyle {
+            label: true,
+            ..Default::default()
+        };
+        let planets = read_planets(matches);
+        let solar_system_style = SolarSystemStyle {
+            label: true,
+            ..Default::default()
+        };
+        let tracks = read_tracks(matches, magnitude)?;
+        let track_style = TrackStyle {
+            label: true,
+            ..Default::default()
+        };
+        let satellite_tracks = read_satellite_tracks(matches)?;
+        let satellite_style = TrackStyle {
+            color: [128, 192, 255, 0].into(),
+            dot_radius: 1,
+            label: true,
+            ..Default::default()
+        };
 
         if true {
             image_view.draw_grid();
         }
+        if cmdline::draw_boundaries(matches) {
+            image_view.draw_constellation_boundaries([200, 200, 60, 0].into(), &boundaries.edges());
+        }
 
-        let subcubes = Subcube::iter_all();
+        let subcubes = catalog.iter_subcubes();
         let star_iter = catalog.iter_within_subcubes(subcubes);
 
         for s in star_iter {
             image_view.draw_star(s);
         }
+        if let Some(constellations) = &constellations {
+            image_view.draw_constellations(&catalog, constellations, &constellation_style);
+        }
+        if let Some(deep_sky) = &deep_sky {
+            image_view.draw_deep_sky_objects(deep_sky, &deep_sky_style);
+        }
+        if let Some(planets) = &planets {
+            image_view.draw_solar_system(planets, &solar_system_style);
+        }
+        for (designation, track) in &tracks {
+            image_view.draw_track(track, designation, &track_style);
+        }
+        for (name, track) in &satellite_tracks {
+            image_view.draw_track(track, name, &satellite_style);
+        }
         let image = image_view.take_image();
         image.save(output_filename)?;
     }
     Ok(())
 }
 
-fn cubemap(catalog: Catalog, matches: &ArgMatches) -> Result<(), anyhow::Error> {
+#[cfg(feature = "image")]
+fn read_constellations(matches: &ArgMatches) -> Result<Option<Vec<Constellation>>, anyhow::Error> {
+    let Some(filename) = cmdline::constellations(matches) else {
+        return Ok(None);
+    };
+    if filename == "none" {
+        return Ok(None);
+    }
+    if filename == "western" {
+        return Ok(Some(Constellation::western()));
+    }
+    let s = std::fs::read_to_string(filename)?;
+    if !filename.ends_with(".json") {
+        return Ok(Some(Constellation::read_stellarium_fab(&s)?));
+    }
+    if let Ok(constellations) = serde_json::from_str(&s) {
+        return Ok(Some(constellations));
+    }
+    Ok(Some(Constellation::read_stellarium_json(&s)?))
+}
+
+#[cfg(feature = "image")]
+fn read_deep_sky(matches: &ArgMatches) -> Result<Option<DeepSkyCatalog>, anyhow::Error> {
+    let Some(filename) = cmdline::deep_sky(matches) else {
+        return Ok(None);
+    };
+    if filename == "builtin" {
+        return Ok(Some(DeepSkyCatalog::builtin()));
+    }
+    let s = std::fs::read_to_string(filename)?;
+    Ok(Some(s.parse()?))
+}
+
+#[cfg(feature = "image")]
+fn read_planets(matches: &ArgMatches) -> Option<Vec<solar_system::BodyPosition>> {
+    if !cmdline::planets(matches) {
+        return None;
+    }
+    let jd = cmdline::date(matches).unwrap_or_else(time::julian_date_now);
+    Some(solar_system::positions(jd))
+}
+
+#[cfg(feature = "image")]
+fn read_tracks(
+    matches: &ArgMatches,
+    magnitude: f32,
+) -> Result<Vec<(String, Vec<star_catalog::Vec3>)>, anyhow::Error> {
+    let Some(bodies) = read_minor_bodies(matches)? else {
+        return Ok(vec![]);
+    };
+    let start = cmdline::date(matches).unwrap_or_else(time::julian_date_now);
+    let end = cmdline::end_date(matches).unwrap_or(start);
+    let step = cmdline::step(matches, 1.0);
+    let site = cmdline::site(matches);
+    Ok(bodies
+        .iter()
+        .filter(|b| b.position(start, site.as_ref()).brighter_than(magnitude))
+        .map(|b| {
+            let track = b
+                .track(start, end, step, site.as_ref())
+                .into_iter()
+                .map(|p| p.vector)
+                .collect();
+            (b.designation.clone(), track)
+        })
+        .collect())
+}
+
+#[cfg(feature = "image")]
+fn read_satellite_tracks(
+    matches: &ArgMatches,
+) -> Result<Vec<(String, Vec<star_catalog::Vec3>)>, anyhow::Error> {
+    let Some(satellites) = read_satellites(matches)? else {
+        return Ok(vec![]);
+    };
+    let Some(site) = cmdline::site(matches) else {
+        return Err(anyhow!(
+            "The site of the observer must be given with --site to draw satellites"
+        ));
+    };
+    let start = cmdline::date(matches).unwrap_or_else(time::julian_date_now);
+    let end = cmdline::end_date(matches).unwrap_or(start);
+    let mut tracks = vec![];
+    for s in &satellites {
+        for pass in s.passes(start, end, 10.0 / 86400.0, &site) {
+            let track = pass.iter().map(|p| p.vector).collect();
+            tracks.push((s.name().to_string(), track));
+        }
+    }
+    Ok(tracks)
+}
+
+fn cubemap(
+    catalog: Catalog,
+    boundaries: &ConstellationBoundaries,
+    magnitude: f32,
+    matches: &ArgMatches,
+) -> Result<(), anyhow::Error> {
     let _ = &catalog;
+    let _ = boundaries;
+    let _ = magnitude;
     let _ = matches;
     #[cfg(feature = "image")]
     {
@@ -704,70 +1789,78 @@ fn cubemap(catalog: Catalog, matches: &ArgMatches) -> Result<(), anyhow::Error>
         let mut image_view = ImageView::new(image);
         image_view.set_tan_hfov(1.0).set_star_size(width / 200);
         let output_filename: PathBuf = cmdline::output(matches).into();
+        // Cubemaps have the unlabelled western figures unless
+        // --constellations is given
+        let (constellations, constellation_style) = {
+            if cmdline::constellations(matches).is_some() {
+                let style = ConstellationStyle {
+                    label: Some(ConstellationLabel::Name),
+                    ..Default::default()
+                };
+                (read_constellations(matches)?, style)
+            } else {
+                (
+                    Some(Constellation::western()),
+                    ConstellationStyle::default(),
+                )
+            }
+        };
+        let deep_sky = read_deep_sky(matches)?;
+        let deep_sky_style = DeepSkyStyle {
+            label: true,
+            ..Default::default()
+        };
+        let planets = read_planets(matches);
+        let solar_system_style = SolarSystemStyle {
+            label: true,
+            ..Default::default()
+        };
+        let tracks = read_tracks(matches, magnitude)?;
+        let track_style = TrackStyle {
+            label: true,
+            ..Default::default()
+        };
+        let satellite_tracks = read_satellite_tracks(matches)?;
+        let satellite_style = TrackStyle {
+            color: [128, 192, 255, 0].into(),
+            dot_radius: 1,
+            label: true,
+            ..Default::default()
+        };
+
+        let boundary_edges = cmdline::draw_boundaries(matches).then(|| boundaries.edges());
 
         for quadrant in 0..6 {
-            let (x_ofs, y_ofs, face_orient) = match quadrant {
-                0 => (
-                    0,
-                    1,
-                    Quat::look_at(&[-1., 0., 0.].into(), &[0., 1., 0.].into()),
-                ),
-                1 => (
-                    1,
-                    1,
-                    Quat::look_at(&[0., 0., -1.].into(), &[0., 1., 0.].into()),
-                ),
-                2 => (
-                    2,
-                    1,
-                    Quat::look_at(&[1., 0., 0.].into(), &[0., 1., 0.].into()),
-                ),
-                3 => (
-                    3,
-                    1,
-                    Quat::look_at(&[0., 0., 1.].into(), &[0., 1., 0.].into()),
-                ),
-                4 => (
-                    1,
-                    0,
-                    Quat::look_at(&[0., 1., 0.].into(), &[0., 0., 1.].into()),
-                ),
-                _ => (
-                    1,
-                    2,
-                    Quat::look_at(&[0., -1., 0.].into(), &[0., 0., -1.].into()),
-                ),
-            };
+            let (x_ofs, y_ofs, face_orient) = ImageView::cubemap_face(quadrant);
             image_view.set_window((x_ofs * width, y_ofs * height), width, height);
             image_view.set_orient(face_orient * orient);
             if true {
                 image_view.draw_grid();
             }
+            if let Some(edges) = &boundary_edges {
+                image_view.draw_constellation_boundaries([200, 200, 60, 0].into(), edges);
+            }
 
-            let subcubes = Subcube::iter_all();
+            let subcubes = catalog.iter_subcubes();
             let star_iter = catalog.iter_within_subcubes(subcubes);
 
             for s in star_iter {
                 image_view.draw_star(s);
             }
-            for (_c, s) in star_catalog::constellations::NORTHERN_HEMISPHERE {
-                let mut last = None;
-                for id in s.iter() {
-                    if *id == 0 {
-                        last = None;
-                        continue;
-                    }
-                    if let Some(index) = catalog.find_sorted(*id) {
-                        if let Some(l) = last {
-                            image_view.draw_line_between_stars(
-                                [155, 255, 255, 0].into(),
-                                &catalog[l],
-                                &catalog[index],
-                            );
-                        }
-                        last = Some(index);
-                    }
-                }
+            if let Some(constellations) = &constellations {
+                image_view.draw_constellations(&catalog, constellations, &constellation_style);
+            }
+            if let Some(deep_sky) = &deep_sky {
+                image_view.draw_deep_sky_objects(deep_sky, &deep_sky_style);
+            }
+            if let Some(planets) = &planets {
+                image_view.draw_solar_system(planets, &solar_system_style);
+            }
+            for (designation, track) in &tracks {
+                image_view.draw_track(track, designation, &track_style);
+            }
+            for (name, track) in &satellite_tracks {
+                image_view.draw_track(track, name, &satellite_style);
             }
         }
         let image = image_view.take_image();
@@ -0,0 +1,391 @@
+//a Imports
+use geo_nd::Vector;
+
+use super::GAUSSIAN_GRAVITY;
+use crate::solar_system::{
+    earth_heliocentric, ecliptic_of_equatorial, ecliptic_of_orbit, equatorial_of_ecliptic,
+    ra_de_of_vec, solve_kepler, Observer, AU_PER_LIGHT_YEAR, KM_PER_AU, LIGHT_DAYS_PER_AU,
+};
+use crate::{Star, Vec3};
+
+//a Constants
+/// The largest difference of the eccentricity from 1 for which an
+/// orbit is treated as parabolic
+const PARABOLIC_TOLERANCE: f64 = 1E-6;
+
+//a MinorBodyKind
+//tp MinorBodyKind
+/// The kind of a [MinorBody]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MinorBodyKind {
+    /// An asteroid (minor planet)
+    Asteroid,
+    /// A comet
+    Comet,
+}
+
+//ip MinorBodyKind
+impl MinorBodyKind {
+    //ap color_index
+    /// Get a typical B-V color index for the kind of body, as for a
+    /// [Star]
+    pub fn color_index(&self) -> f32 {
+        match self {
+            Self::Asteroid => 0.75,
+            Self::Comet => 0.80,
+        }
+    }
+}
+
+//a MagnitudeLaw
+//tp MagnitudeLaw
+/// The law giving the apparent magnitude of a [MinorBody] from its
+/// distances from the Sun and Earth and its phase angle
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MagnitudeLaw {
+    /// The IAU H, G system for asteroids: the absolute magnitude H
+    /// and slope parameter G
+    HG {
+        /// The absolute magnitude
+        h: f32,
+        /// The slope parameter (typically 0.15)
+        g: f32,
+    },
+    /// The total magnitude of a comet, H + 5 log(delta) + 2.5 K
+    /// log(r), as used by the Minor Planet Center
+    Comet {
+        /// The absolute magnitude
+        h: f32,
+        /// The slope parameter (typically 4)
+        k: f32,
+    },
+}
+
+//ip MagnitudeLaw
+impl MagnitudeLaw {
+    //mp magnitude
+    /// Get the apparent magnitude given the distances from the Sun
+    /// and observer (in AU) and the phase angle (in radians)
+    pub fn magnitude(&self, sun_distance: f64, distance: f64, phase_angle: f64) -> f32 {
+        match *self {
+            Self::HG { h, g } => {
+                let tan_half = (phase_angle / 2.0).tan().abs();
+                let phi_1 = (-3.33 * tan_half.powf(0.63)).exp();
+                let phi_2 = (-1.87 * tan_half.powf(1.22)).exp();
+                let g = g as f64;
+                let phase = ((1.0 - g) * phi_1 + g * phi_2).max(1E-10);
+                (h as f64 + 5.0 * (sun_distance * distance).log10() - 2.5 * phase.log10()) as f32
+            }
+            Self::Comet { h, k } => {
+                (h as f64 + 5.0 * distance.log10() + 2.5 * k as f64 * sun_distance.log10()) as f32
+            }
+        }
+    }
+}
+
+//a MinorBody
+//tp MinorBody
+/// A comet or asteroid given by its osculating orbital elements, for
+/// the ecliptic and equinox of J2000
+///
+/// The orbit is held by its perihelion distance, eccentricity and
+/// time of perihelion, so that elliptic, parabolic and hyperbolic
+/// orbits are treated alike; asteroid elements with a mean anomaly at
+/// an epoch are converted with [MinorBody::of_mean_anomaly].
+#[derive(Debug, Clone)]
+pub struct MinorBody {
+    /// The designation, such as '(1) Ceres' or 'C/2023 A3'
+    pub designation: String,
+    /// The kind of body
+    pub kind: MinorBodyKind,
+    /// The perihelion distance in AU
+    pub perihelion_distance: f64,
+    /// The eccentricity
+    pub eccentricity: f64,
+    /// The Julian Date (TT) of perihelion
+    pub perihelion_time: f64,
+    /// The inclination in radians
+    pub inclination: f64,
+    /// The longitude of the ascending node in radians
+    pub node: f64,
+    /// The argument of perihelion in radians
+    pub arg_perihelion: f64,
+    /// The Julian Date of the epoch of osculation
+    pub epoch: f64,
+    /// The magnitude law, if the absolute magnitude is known
+    pub magnitude: Option<MagnitudeLaw>,
+}
+
+//ip MinorBody
+impl MinorBody {
+    //cp new
+    /// Create a new minor body given its designation, kind, perihelion
+    /// distance (in AU), eccentricity and Julian Date of perihelion
+    ///
+    /// The orbit is in the plane of the ecliptic until
+    /// [MinorBody::with_orientation] is used; the epoch is the time of
+    /// perihelion
+    pub fn new(
+        designation: &str,
+        kind: MinorBodyKind,
+        perihelion_distance: f64,
+        eccentricity: f64,
+        perihelion_time: f64,
+    ) -> Self {
+        Self {
+            designation: designation.into(),
+            kind,
+            perihelion_distance,
+            eccentricity,
+            perihelion_time,
+            inclination: 0.,
+            node: 0.,
+            arg_perihelion: 0.,
+            epoch: perihelion_time,
+            magnitude: None,
+        }
+    }
+
+    //cp of_mean_anomaly
+    /// Create a new minor body in an elliptic orbit given its
+    /// designation, kind, semi-major axis (in AU), eccentricity, and
+    /// mean anomaly (in radians) at an epoch (a Julian Date)
+    pub fn of_mean_anomaly(
+        designation: &str,
+        kind: MinorBodyKind,
+        semi_major_axis: f64,
+        eccentricity: f64,
+        mean_anomaly: f64,
+        epoch: f64,
+    ) -> Self {
+        let n = GAUSSIAN_GRAVITY / semi_major_axis.powf(1.5);
+        let m = (mean_anomaly + std::f64::consts::PI).rem_euclid(std::f64::consts::TAU)
+            - std::f64::consts::PI;
+        let perihelion_distance = semi_major_axis * (1.0 - eccentricity);
+        Self::new(
+            designation,
+            kind,
+            perihelion_distance,
+            eccentricity,
+            epoch - m / n,
+        )
+        .with_epoch(epoch)
+    }
+
+    //cp with_orientation
+    /// Set the inclination, longitude of the ascending node and
+    /// argument of perihelion (in radians)
+    pub fn with_orientation(mut self, inclination: f64, node: f64, arg_perihelion: f64) -> Self {
+        self.inclination = inclination;
+        self.node = node;
+        self.arg_perihelion = arg_perihelion;
+        self
+    }
+
+    //cp with_epoch
+    /// Set the Julian Date of the epoch of osculation
+    pub fn with_epoch(mut self, epoch: f64) -> Self {
+        self.epoch = epoch;
+        self
+    }
+
+    //cp with_magnitude
+    /// Set the magnitude law
+    pub fn with_magnitude(mut self, magnitude: MagnitudeLaw) -> Self {
+        self.magnitude = Some(magnitude);
+        self
+    }
+
+    //ap semi_major_axis
+    /// Get the semi-major axis in AU, if the orbit is elliptic
+    pub fn semi_major_axis(&self) -> Option<f64> {
+        if self.eccentricity < 1.0 - PARABOLIC_TOLERANCE {
+            Some(self.perihelion_distance / (1.0 - self.eccentricity))
+        } else {
+            None
+        }
+    }
+
+    //ap period
+    /// Get the orbital period in days, if the orbit is elliptic
+    pub fn period(&self) -> Option<f64> {
+        self.semi_major_axis()
+            .map(|a| std::f64::consts::TAU * a.powf(1.5) / GAUSSIAN_GRAVITY)
+    }
+
+    //mi orbit_position
+    /// Get the position (in AU) in the plane of the orbit, with x
+    /// towards perihelion, at a Julian Date
+    fn orbit_position(&self, jd: f64) -> (f64, f64) {
+        let q = self.perihelion_distance;
+        let e = self.eccentricity;
+        let dt = jd - self.perihelion_time;
+        if (e - 1.0).abs() < PARABOLIC_TOLERANCE {
+            // Barker's equation, for s = tan(true anomaly / 2)
+            let w = 3.0 * GAUSSIAN_GRAVITY * dt / (2.0 * q * q * q).sqrt();
+            let y = (w / 2.0 + (w * w / 4.0 + 1.0).sqrt()).cbrt();
+            let s = y - 1.0 / y;
+            (q * (1.0 - s * s), 2.0 * q * s)
+        } else if e < 1.0 {
+            let a = q / (1.0 - e);
+            let m = GAUSSIAN_GRAVITY / a.powf(1.5) * dt;
+            let m =
+                (m + std::f64::consts::PI).rem_euclid(std::f64::consts::TAU) - std::f64::consts::PI;
+            let ea = solve_kepler(m, e);
+            (a * (ea.cos() - e), a * (1.0 - e * e).sqrt() * ea.sin())
+        } else {
+            let a = q / (e - 1.0);
+            let m = GAUSSIAN_GRAVITY / a.powf(1.5) * dt;
+            let ha = solve_hyperbolic_kepler(m, e);
+            (a * (e - ha.cosh()), a * (e * e - 1.0).sqrt() * ha.sinh())
+        }
+    }
+
+    //mp heliocentric
+    /// Get the heliocentric position of the body (in AU, in the
+    /// ecliptic frame of J2000) at a Julian Date
+    pub fn heliocentric(&self, jd: f64) -> Vec3 {
+        let (x, y) = self.orbit_position(jd);
+        ecliptic_of_orbit(x, y, self.inclination, self.node, self.arg_perihelion)
+    }
+
+    //mp position
+    /// Get the position of the body at a Julian Date as seen from the
+    /// center of the Earth, or from an observer on its surface,
+    /// corrected for light time
+    pub fn position(&self, jd: f64, observer: Option<&Observer>) -> MinorBodyPosition {
+        let mut earth = earth_heliocentric(jd);
+        if let Some(observer) = observer {
+            earth += ecliptic_of_equatorial(&observer.geocentric(jd)) * (1.0 / KM_PER_AU);
+        }
+        let mut helio = self.heliocentric(jd);
+        let mut geo = helio - earth;
+        // Two iterations suffice for the light time
+        for _ in 0..2 {
+            let light_time = geo.length() * LIGHT_DAYS_PER_AU;
+            helio = self.heliocentric(jd - light_time);
+            geo = helio - earth;
+        }
+        let distance = geo.length();
+        let sun_distance = helio.length();
+        let phase_angle = (helio.dot(&geo) / (sun_distance * distance))
+            .clamp(-1., 1.)
+            .acos();
+        let vector = equatorial_of_ecliptic(&geo).normalize();
+        let (ra, de) = ra_de_of_vec(&vector);
+        let mag = self
+            .magnitude
+            .map(|m| m.magnitude(sun_distance, distance, phase_angle));
+        MinorBodyPosition {
+            kind: self.kind,
+            jd,
+            ra,
+            de,
+            vector,
+            distance,
+            sun_distance,
+            phase_angle,
+            mag,
+        }
+    }
+
+    //mp track
+    /// Get the positions of the body from one Julian Date to another,
+    /// at a step in days; the last position is always at the end
+    /// date
+    ///
+    /// If the step is not positive then there are no positions
+    pub fn track(
+        &self,
+        start: f64,
+        end: f64,
+        step: f64,
+        observer: Option<&Observer>,
+    ) -> Vec<MinorBodyPosition> {
+        if step.is_nan() || step <= 0. {
+            return vec![];
+        }
+        let n = ((end - start) / step).ceil().max(0.) as usize;
+        (0..=n)
+            .map(|i| {
+                let jd = (start + step * i as f64).min(end.max(start));
+                self.position(jd, observer)
+            })
+            .collect()
+    }
+}
+
+//a MinorBodyPosition
+//tp MinorBodyPosition
+/// The position of a [MinorBody] at a time, from
+/// [MinorBody::position]
+#[derive(Debug, Clone)]
+pub struct MinorBodyPosition {
+    /// The kind of body
+    pub kind: MinorBodyKind,
+    /// The Julian Date
+    pub jd: f64,
+    /// The right ascension (J2000) in radians
+    pub ra: f64,
+    /// The declination (J2000) in radians
+    pub de: f64,
+    /// The unit vector of the direction of the body, as for
+    /// [Star::vector]
+    pub vector: Vec3,
+    /// The distance from the Earth (or observer) in AU
+    pub distance: f64,
+    /// The distance from the Sun in AU
+    pub sun_distance: f64,
+    /// The phase angle (Sun - body - Earth) in radians
+    pub phase_angle: f64,
+    /// The apparent magnitude, if the body has a magnitude law
+    pub mag: Option<f32>,
+}
+
+//ip MinorBodyPosition
+impl MinorBodyPosition {
+    //ap brighter_than
+    /// Return true if the magnitude is less than a value
+    ///
+    /// A body without a magnitude is taken to be brighter, so that it
+    /// is not missed
+    pub fn brighter_than(&self, mag: f32) -> bool {
+        match self.mag {
+            Some(m) => m < mag,
+            None => true,
+        }
+    }
+
+    //mp to_star
+    /// Create a [Star] for the body at its position, with an id, so
+    /// that it may be added to a catalog (for example to identify it
+    /// in an image rather than reject it as a false star)
+    ///
+    /// A body without a magnitude is given a magnitude of 99
+    pub fn to_star(&self, id: usize) -> Star {
+        Star::new(
+            id,
+            self.ra,
+            self.de,
+            (self.distance / AU_PER_LIGHT_YEAR) as f32,
+            self.mag.unwrap_or(99.),
+            self.kind.color_index(),
+        )
+    }
+}
+
+//a Functions
+//fi solve_hyperbolic_kepler
+/// Solve Kepler's equation for a hyperbolic orbit, M = e sinh(H) - H,
+/// for H given the mean anomaly M and eccentricity e (greater than 1)
+fn solve_hyperbolic_kepler(m: f64, e: f64) -> f64 {
+    let mut ha = (m / e).asinh();
+    for _ in 0..100 {
+        let d = (e * ha.sinh() - ha - m) / (e * ha.cosh() - 1.0);
+        ha -= d;
+        if d.abs() < 1E-12 * (1.0 + ha.abs()) {
+            break;
+        }
+    }
+    ha
+}
@@ -0,0 +1,62 @@
+//! Comets and asteroids from osculating orbital elements
+//!
+//! Elements are read from files in the formats of the Minor Planet
+//! Center: 'MPCORB.DAT' (and other files of the same format) for
+//! asteroids, and 'CometEls.txt' for comets. Both may be read with
+//! [read_mpc], which determines the format of each line.
+//!
+//! A [MinorBody] is propagated as a two-body (Keplerian) orbit about
+//! the Sun, from its elements for the ecliptic and equinox of J2000;
+//! elliptic, parabolic and hyperbolic orbits are supported. The
+//! perturbations of the planets are ignored, so positions become
+//! worse with time from the epoch of the elements - elements should
+//! be current to within a few months for an accuracy of arcseconds.
+//!
+//! A [MinorBodyPosition] is geocentric (or topocentric, for an
+//! [crate::solar_system::Observer]), corrected for light time, for the
+//! equator and equinox of J2000 - the same frame as
+//! [crate::Star::vector]. The apparent magnitude uses the H, G system
+//! for asteroids, and the total magnitude law for comets.
+//!
+//! Times are Julian Dates, as provided by the [crate::time] module.
+
+//a Imports
+use geo_nd::Vector;
+
+use crate::solar_system::Observer;
+use crate::Vec3;
+
+mod body;
+mod mpc;
+
+pub use body::{MagnitudeLaw, MinorBody, MinorBodyKind, MinorBodyPosition};
+pub use mpc::{parse_comet_line, parse_mpcorb_line, read_mpc, unpack_date};
+
+//a Constants
+/// The Gaussian gravitational constant, the mean motion in radians
+/// per day of a body with a semi-major axis of 1 AU
+pub const GAUSSIAN_GRAVITY: f64 = 0.017_202_098_95;
+
+//a Functions
+//fp positions_around
+/// Get the positions of those of a set of minor bodies that are
+/// within an angle (in radians) of a unit vector at a Julian Date,
+/// with the index of each body in the slice
+///
+/// This can be used to identify the minor bodies in an image, given
+/// the direction of its center and the angle to its corners
+pub fn positions_around(
+    bodies: &[MinorBody],
+    jd: f64,
+    observer: Option<&Observer>,
+    v: &Vec3,
+    angle: f64,
+) -> Vec<(usize, MinorBodyPosition)> {
+    let cos_angle = angle.min(std::f64::consts::PI).cos();
+    bodies
+        .iter()
+        .enumerate()
+        .map(|(i, b)| (i, b.position(jd, observer)))
+        .filter(|(_, p)| p.vector.dot(v) >= cos_angle)
+        .collect()
+}
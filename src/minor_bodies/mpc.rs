@@ -0,0 +1,170 @@
+//a Imports
+use super::{MagnitudeLaw, MinorBody, MinorBodyKind};
+use crate::Error;
+
+//a Functions
+//fi field
+/// Get the trimmed text of a range of (zero-based) columns of a line,
+/// which is empty if the line is too short
+fn field(line: &str, start: usize, end: usize) -> &str {
+    line.get(start..end.min(line.len())).unwrap_or("").trim()
+}
+
+//fi number
+/// Parse a range of columns of a line as a number, if possible
+fn number<T: std::str::FromStr>(line: &str, start: usize, end: usize) -> Option<T> {
+    field(line, start, end).parse().ok()
+}
+
+//fp unpack_date
+/// Get the Julian Date (at midnight TT) of a date packed in the five
+/// characters used by the Minor Planet Center, such as 'K24AH' for
+/// 2024 October 17
+///
+/// The first character is the century (I is 1800, J is 1900, K is
+/// 2000), then two digits of the year; the month and day are each a
+/// single character of 1 to 9 then A onwards for 10 and more
+pub fn unpack_date(s: &str) -> Option<f64> {
+    let chars: Vec<char> = s.trim().chars().collect();
+    if chars.len() != 5 {
+        return None;
+    }
+    let century = match chars[0] {
+        'I' => 18,
+        'J' => 19,
+        'K' => 20,
+        'L' => 21,
+        _ => return None,
+    };
+    let year: i32 = s.trim().get(1..3)?.parse().ok()?;
+    let unpack = |c: char| match c {
+        '1'..='9' => Some(c as u32 - '0' as u32),
+        'A'..='V' => Some(c as u32 - 'A' as u32 + 10),
+        _ => None,
+    };
+    let month = unpack(chars[3]).filter(|m| *m <= 12)?;
+    let day = unpack(chars[4])?;
+    Some(crate::time::julian_date(
+        century * 100 + year,
+        month,
+        day as f64,
+    ))
+}
+
+//fp parse_mpcorb_line
+/// Parse a line of asteroid orbital elements in the format of the
+/// Minor Planet Center's 'MPCORB.DAT'
+///
+/// The readable designation (from column 167) is used if present,
+/// otherwise the packed designation. A missing slope parameter G is
+/// taken to be 0.15.
+pub fn parse_mpcorb_line(line: &str) -> Option<MinorBody> {
+    let epoch = unpack_date(field(line, 20, 25))?;
+    let m: f64 = number(line, 26, 35)?;
+    let arg_perihelion: f64 = number(line, 37, 46)?;
+    let node: f64 = number(line, 48, 57)?;
+    let inclination: f64 = number(line, 59, 68)?;
+    let e: f64 = number(line, 70, 79)?;
+    let a: f64 = number(line, 92, 103)?;
+    if !(0.0..1.0).contains(&e) || a <= 0. {
+        return None;
+    }
+    let mut designation = field(line, 166, 194);
+    if designation.is_empty() {
+        designation = field(line, 0, 7);
+    }
+    let mut body = MinorBody::of_mean_anomaly(
+        designation,
+        MinorBodyKind::Asteroid,
+        a,
+        e,
+        m.to_radians(),
+        epoch,
+    )
+    .with_orientation(
+        inclination.to_radians(),
+        node.to_radians(),
+        arg_perihelion.to_radians(),
+    );
+    if let Some(h) = number(line, 8, 13) {
+        let g = number(line, 14, 19).unwrap_or(0.15);
+        body = body.with_magnitude(MagnitudeLaw::HG { h, g });
+    }
+    Some(body)
+}
+
+//fp parse_comet_line
+/// Parse a line of comet orbital elements in the format of the Minor
+/// Planet Center's 'CometEls.txt'
+///
+/// The designation and name (from column 103) are used if present,
+/// otherwise the packed designation. A missing slope parameter K is
+/// taken to be 4.
+pub fn parse_comet_line(line: &str) -> Option<MinorBody> {
+    if !matches!(line.get(4..5), Some("C" | "P" | "D" | "X" | "I" | "A")) {
+        return None;
+    }
+    let year: i32 = number(line, 14, 18)?;
+    let month: u32 = number(line, 19, 21)?;
+    let day: f64 = number(line, 22, 29)?;
+    let q: f64 = number(line, 30, 39)?;
+    let e: f64 = number(line, 41, 49)?;
+    let arg_perihelion: f64 = number(line, 51, 59)?;
+    let node: f64 = number(line, 61, 69)?;
+    let inclination: f64 = number(line, 71, 79)?;
+    if !(1..=12).contains(&month) || q <= 0. || e < 0. {
+        return None;
+    }
+    let perihelion_time = crate::time::julian_date(year, month, day);
+    let mut designation = field(line, 102, 158);
+    if designation.is_empty() {
+        designation = field(line, 0, 12);
+    }
+    let mut body = MinorBody::new(designation, MinorBodyKind::Comet, q, e, perihelion_time)
+        .with_orientation(
+            inclination.to_radians(),
+            node.to_radians(),
+            arg_perihelion.to_radians(),
+        );
+    let epoch = field(line, 81, 89);
+    if epoch.len() == 8 {
+        let y = epoch[0..4].parse();
+        let m = epoch[4..6].parse();
+        let d = epoch[6..8].parse::<u32>();
+        if let (Ok(y), Ok(m), Ok(d)) = (y, m, d) {
+            body = body.with_epoch(crate::time::julian_date(y, m, d as f64));
+        }
+    }
+    if let Some(h) = number(line, 91, 95) {
+        let k = number(line, 96, 100).unwrap_or(4.);
+        body = body.with_magnitude(MagnitudeLaw::Comet { h, k });
+    }
+    Some(body)
+}
+
+//fp read_mpc
+/// Read a file of orbital elements in the formats of the Minor Planet
+/// Center, where each line may be of comet or asteroid elements
+///
+/// Blank lines are ignored, as is the header of 'MPCORB.DAT' (up to
+/// a line of dashes). A line that cannot be parsed is an error,
+/// reporting its line number (from 1).
+pub fn read_mpc(s: &str) -> Result<Vec<MinorBody>, Error> {
+    let lines: Vec<&str> = s.lines().collect();
+    let first = lines
+        .iter()
+        .position(|l| l.starts_with("-----"))
+        .map(|n| n + 1)
+        .unwrap_or(0);
+    let mut bodies = vec![];
+    for (n, line) in lines.iter().enumerate().skip(first) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let body = parse_comet_line(line)
+            .or_else(|| parse_mpcorb_line(line))
+            .ok_or(Error::BadMpcLine(n + 1))?;
+        bodies.push(body);
+    }
+    Ok(bodies)
+}
@@ -0,0 +1,155 @@
+//a Imports
+use serde::{Deserialize, Serialize};
+
+use crate::Catalog;
+
+//a StarPair
+//tp StarPair
+/// A pair of stars in a [PairIndex], and the angle between them
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StarPair {
+    /// The ids of the two stars, the lower id first
+    pub ids: [usize; 2],
+    /// The angle between the stars, in radians
+    pub angle: f32,
+}
+
+//a PairIndex
+//tp PairIndex
+/// A table of all the pairs of stars in a [Catalog] that are within a
+/// maximum angle of each other, sorted by angle, with a k-vector for
+/// looking up the pairs within a range of angles in constant time
+///
+/// This is the structure used by star trackers for identifying stars
+/// in a field of view: the maximum angle is then the diagonal of the
+/// field of view.
+///
+/// The k-vector splits the range of angles into as many equal steps
+/// as there are pairs; entry *k* of the k-vector is the number of
+/// pairs with an angle less than the start of step *k*. Hence finding
+/// the pairs within a range of angles requires just two lookups of
+/// the k-vector, plus a short scan of the pairs at each end.
+///
+/// The index refers to stars by their id, and can be serialized; it
+/// can hence be used with any catalog that contains the stars.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PairIndex {
+    /// The maximum angle between stars of a pair, in radians
+    max_angle: f64,
+    /// The magnitude limit of the stars
+    magnitude: f32,
+    /// The pairs, sorted by angle
+    pairs: Vec<StarPair>,
+    /// The k-vector; entry k is the number of pairs with an angle less
+    /// than k * step
+    k_vector: Vec<u32>,
+    /// The angle step of the k-vector
+    step: f64,
+}
+
+//ip PairIndex
+impl PairIndex {
+    //cp new
+    /// Create a new [PairIndex] from a catalog, of all the pairs of
+    /// stars brighter than a magnitude that are within an angle (in
+    /// radians) of each other
+    ///
+    /// The catalog filter is also applied to the stars
+    ///
+    /// Needs data to have been derived for the Catalog
+    pub fn new(catalog: &Catalog, max_angle: f64, magnitude: f32) -> Self {
+        let mut pairs = vec![];
+        let mut accepted = 0;
+        for s in catalog.iter_stars() {
+            if !s.brighter_than(magnitude) || !catalog.filter().call(s, accepted) {
+                continue;
+            }
+            accepted += 1;
+            for j in catalog.find_stars_around_brighter_than(&s.vector, max_angle, magnitude) {
+                let s2 = &catalog[j];
+                if s2.id <= s.id {
+                    continue;
+                }
+                let angle = s.cos_angle_between(s2).clamp(-1., 1.).acos() as f32;
+                pairs.push(StarPair {
+                    ids: [s.id, s2.id],
+                    angle,
+                });
+            }
+        }
+        pairs.sort_by(|a, b| a.angle.total_cmp(&b.angle).then(a.ids.cmp(&b.ids)));
+
+        let n = pairs.len().max(1);
+        let step = max_angle / n as f64;
+        let mut k_vector = Vec::with_capacity(n + 1);
+        let mut count = 0;
+        for k in 0..=n {
+            let angle = k as f64 * step;
+            while count < pairs.len() && (pairs[count].angle as f64) < angle {
+                count += 1;
+            }
+            k_vector.push(count as u32);
+        }
+        Self {
+            max_angle,
+            magnitude,
+            pairs,
+            k_vector,
+            step,
+        }
+    }
+
+    //ap max_angle
+    /// Get the maximum angle between the stars of a pair, in radians
+    pub fn max_angle(&self) -> f64 {
+        self.max_angle
+    }
+
+    //ap magnitude
+    /// Get the magnitude limit of the stars
+    pub fn magnitude(&self) -> f32 {
+        self.magnitude
+    }
+
+    //ap len
+    /// Get the number of pairs in the index
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    //ap is_empty
+    /// Return true if the index has no pairs
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    //mi first_at_least
+    /// Get the index of the first pair with an angle at least a value
+    fn first_at_least(&self, angle: f64) -> usize {
+        if self.pairs.is_empty() || angle <= 0. {
+            return 0;
+        }
+        let k = (angle / self.step).floor() as usize;
+        if k >= self.k_vector.len() {
+            return self.pairs.len();
+        }
+        // All pairs before k_vector[k] have an angle < k * step <= angle
+        let mut n = self.k_vector[k] as usize;
+        while n < self.pairs.len() && (self.pairs[n].angle as f64) < angle {
+            n += 1;
+        }
+        n
+    }
+
+    //mp pairs_with_angle
+    /// Get the pairs whose angle (in radians) is between a minimum
+    /// and maximum (inclusive)
+    pub fn pairs_with_angle(&self, min_angle: f64, max_angle: f64) -> &[StarPair] {
+        let start = self.first_at_least(min_angle);
+        let mut end = self.first_at_least(max_angle).max(start);
+        while end < self.pairs.len() && self.pairs[end].angle as f64 <= max_angle {
+            end += 1;
+        }
+        &self.pairs[start..end]
+    }
+}
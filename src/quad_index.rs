@@ -0,0 +1,368 @@
+//a Imports
+use std::collections::{HashMap, HashSet};
+
+use geo_nd::{Vector, Vector3};
+use serde::{Deserialize, Serialize};
+
+use crate::{Catalog, CatalogIndex, HealpixIndex, Vec3};
+
+//a Useful functions
+//fi complex_mul
+/// Multiply two complex numbers given as (re, im)
+fn complex_mul(a: [f64; 2], b: [f64; 2]) -> [f64; 2] {
+    [a[0] * b[0] - a[1] * b[1], a[0] * b[1] + a[1] * b[0]]
+}
+
+//fi code_of_points
+/// Get the hash code of four points A, B, C, D in a plane
+///
+/// The points are mapped by the similarity transform that takes A to
+/// (0, 0) and B to (1, 1); the code is then the coordinates of C and
+/// D. If C and D are within the circle with AB as its diameter then
+/// the code values are all between 0 and 1.
+///
+/// Returns None if A and B are coincident
+fn code_of_points(p: &[[f64; 2]; 4]) -> Option<[f64; 4]> {
+    let ab = [p[1][0] - p[0][0], p[1][1] - p[0][1]];
+    let l2 = ab[0] * ab[0] + ab[1] * ab[1];
+    if l2 <= 0. {
+        return None;
+    }
+    // (1+i) / (B-A)
+    let scale = complex_mul([1., 1.], [ab[0] / l2, -ab[1] / l2]);
+    let c = complex_mul([p[2][0] - p[0][0], p[2][1] - p[0][1]], scale);
+    let d = complex_mul([p[3][0] - p[0][0], p[3][1] - p[0][1]], scale);
+    Some([c[0], c[1], d[0], d[1]])
+}
+
+//fi in_circle
+/// Return true if the C and D of a code are within the circle that
+/// has AB as its diameter
+fn in_circle(code: &[f64; 4]) -> bool {
+    let r2 = |x: f64, y: f64| (x - 0.5) * (x - 0.5) + (y - 0.5) * (y - 0.5);
+    r2(code[0], code[1]) <= 0.5 && r2(code[2], code[3]) <= 0.5
+}
+
+//fi project
+/// Project unit vectors onto the plane tangent to the sphere at a
+/// (unit) direction (a gnomonic projection, as a rectilinear lens
+/// does)
+fn project<const N: usize>(vectors: [&Vec3; N], center: &Vec3) -> [[f64; 2]; N] {
+    let other: Vec3 = if center[0].abs() < 0.9 {
+        [1., 0., 0.].into()
+    } else {
+        [0., 1., 0.].into()
+    };
+    let e0 = other.cross_product(center).normalize();
+    let e1 = center.cross_product(&e0);
+    vectors.map(|v| {
+        let z = v.dot(center);
+        [v.dot(&e0) / z, v.dot(&e1) / z]
+    })
+}
+
+//a QuadMatch
+//tp QuadMatch
+/// A match of four points in an image to four stars of a [QuadIndex]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuadMatch {
+    /// The ids of the stars corresponding to the four points, in the
+    /// same order as the points
+    pub ids: [usize; 4],
+    /// The implied plate scale, in radians per pixel (or whatever
+    /// unit the points are given in)
+    pub plate_scale: f64,
+    /// True if the points are a mirror image of the stars as
+    /// projected with the image X, Y and viewing directions forming a
+    /// right-handed set
+    pub reflected: bool,
+    /// The distance between the hash code of the points and that of
+    /// the stars; smaller is a better match
+    pub distance: f64,
+}
+
+//a QuadIndex
+//ti Quad
+/// A quad of stars in the index
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct Quad {
+    /// The hash code of the quad
+    code: [f32; 4],
+    /// The ids of the stars A, B, C and D
+    ids: [usize; 4],
+    /// The angle between stars A and B, in radians
+    ab_angle: f32,
+}
+
+//tp QuadIndex
+/// A geometric hash index of quads of stars from a [Catalog], for
+/// blind identification of stars in an image (in the style of
+/// astrometry.net)
+///
+/// A quad is four stars A, B, C and D, where A and B are the most
+/// widely separated and C and D lie within the circle with AB as its
+/// diameter. The stars are projected onto the plane tangent to the
+/// sky at the midpoint of A and B, and the hash code of the quad is
+/// the position of C and D after the similarity transform that takes
+/// A to (0, 0) and B to (1, 1). This code does not depend on the
+/// rotation or scale of the image; querying with all the orderings
+/// and reflections of a quad from an image makes the search also
+/// independent of the labelling of the points and of reflection.
+///
+/// The index is built from the brightest stars in each region of the
+/// sky (the pixels of a [HealpixIndex]), so that faint stars are
+/// used where there are few bright ones; the magnitude limit of each
+/// region is recorded. Quads are then formed from the brightest of
+/// these stars around each region.
+///
+/// The index refers to stars by their id, and can be serialized; it
+/// can hence be used with any catalog that contains the stars.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QuadIndex {
+    /// The HEALPix order of the regions
+    order: u32,
+    /// The minimum and maximum angle between stars A and B
+    scale: (f64, f64),
+    /// The magnitude limit for each region (pixel) that has stars,
+    /// if not all of its stars were used
+    region_limits: Vec<(u64, Option<f32>)>,
+    /// The quads, sorted by the first value of their code
+    quads: Vec<Quad>,
+}
+
+//ip QuadIndex
+impl QuadIndex {
+    //cp new
+    /// Create a new [QuadIndex] from a catalog, with regions of a
+    /// HEALPix order, using up to *stars_per_region* stars from each
+    /// region, and making up to *quads_per_region* quads for each
+    /// region with A and B between *min_angle* and *max_angle* (in
+    /// radians) apart
+    ///
+    /// The catalog filter is applied to the stars
+    pub fn new(
+        catalog: &Catalog,
+        order: u32,
+        stars_per_region: usize,
+        quads_per_region: usize,
+        min_angle: f64,
+        max_angle: f64,
+    ) -> Self {
+        let healpix = HealpixIndex::new(order);
+
+        // Select the brightest stars in each region
+        let mut regions: HashMap<u64, Vec<CatalogIndex>> = HashMap::new();
+        let mut accepted = 0;
+        for (i, s) in catalog.iter_stars().enumerate() {
+            if catalog.filter().call(s, accepted) {
+                accepted += 1;
+                let pixel = healpix.pixel_of_vector(&s.vector);
+                regions.entry(pixel).or_default().push(i.into());
+            }
+        }
+        let mut region_limits = vec![];
+        for (pixel, stars) in regions.iter_mut() {
+            stars.sort_by(|a, b| catalog[*a].mag.total_cmp(&catalog[*b].mag));
+            stars.truncate(stars_per_region);
+            if stars.len() == stars_per_region {
+                region_limits.push((*pixel, Some(catalog[stars[stars.len() - 1]].mag)));
+            } else {
+                region_limits.push((*pixel, None));
+            }
+        }
+        region_limits.sort_by_key(|(p, _)| *p);
+
+        // Form quads around each region, with the midpoint of A and B
+        // within the region
+        let mut seen = HashSet::new();
+        let mut quads = vec![];
+        let radius = max_angle / 2.0 + healpix.max_pixel_radius();
+        for (pixel, _) in region_limits.iter() {
+            let center = healpix.vector_of_pixel(*pixel);
+            let mut candidates: Vec<CatalogIndex> = healpix
+                .pixels_in_cone(&center, radius)
+                .into_iter()
+                .filter_map(|p| regions.get(&p))
+                .flatten()
+                .copied()
+                .filter(|i| catalog[*i].vector.dot(&center) >= radius.cos())
+                .collect();
+            // Use the brightest of the stars around the region; this
+            // allows for the region's own stars and some from its
+            // neighbours
+            candidates.sort_by(|a, b| catalog[*a].mag.total_cmp(&catalog[*b].mag));
+            candidates.truncate(stars_per_region.max(4) * 2);
+
+            let mut num_quads = 0;
+            'ab: for (n, a) in candidates.iter().enumerate() {
+                for b in candidates[..n].iter() {
+                    let va = &catalog[*a].vector;
+                    let vb = &catalog[*b].vector;
+                    let ab_angle = va.dot(vb).clamp(-1., 1.).acos();
+                    if ab_angle < min_angle || ab_angle > max_angle {
+                        continue;
+                    }
+                    let mid = (*va + *vb).normalize();
+                    if healpix.pixel_of_vector(&mid) != *pixel {
+                        continue;
+                    }
+                    for (m, c) in candidates.iter().enumerate() {
+                        for d in candidates[..m].iter() {
+                            if [a, b].contains(&c) || [a, b].contains(&d) {
+                                continue;
+                            }
+                            let vectors = [va, vb, &catalog[*c].vector, &catalog[*d].vector];
+                            if vectors.iter().any(|v| v.dot(&mid) <= 0.) {
+                                continue;
+                            }
+                            let Some(code) = code_of_points(&project(vectors, &mid)) else {
+                                continue;
+                            };
+                            if !in_circle(&code) {
+                                continue;
+                            }
+                            let ids = [a, b, c, d].map(|i| catalog[*i].id);
+                            let mut key = ids;
+                            key.sort();
+                            if !seen.insert(key) {
+                                continue;
+                            }
+                            quads.push(Quad {
+                                code: code.map(|c| c as f32),
+                                ids,
+                                ab_angle: ab_angle as f32,
+                            });
+                            num_quads += 1;
+                            if num_quads >= quads_per_region {
+                                break 'ab;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        quads.sort_by(|a, b| a.code[0].total_cmp(&b.code[0]));
+        Self {
+            order,
+            scale: (min_angle, max_angle),
+            region_limits,
+            quads,
+        }
+    }
+
+    //ap order
+    /// Get the HEALPix order of the regions of the index
+    pub fn order(&self) -> u32 {
+        self.order
+    }
+
+    //ap scale
+    /// Get the minimum and maximum angle (in radians) between the
+    /// stars A and B of the quads
+    pub fn scale(&self) -> (f64, f64) {
+        self.scale
+    }
+
+    //ap len
+    /// Get the number of quads in the index
+    pub fn len(&self) -> usize {
+        self.quads.len()
+    }
+
+    //ap is_empty
+    /// Return true if the index has no quads
+    pub fn is_empty(&self) -> bool {
+        self.quads.is_empty()
+    }
+
+    //mp region_limit
+    /// Get the magnitude limit of the stars used in the region of
+    /// the sky containing a vector
+    ///
+    /// This is None if all the stars in the region were used (or if
+    /// the region has no stars)
+    pub fn region_limit(&self, v: &Vec3) -> Option<f32> {
+        let pixel = HealpixIndex::new(self.order).pixel_of_vector(v);
+        self.region_limits
+            .binary_search_by_key(&pixel, |(p, _)| *p)
+            .ok()
+            .and_then(|n| self.region_limits[n].1)
+    }
+
+    //mp iter_quads
+    /// Iterate over the star ids of the quads (A, B, C, D) in the index
+    pub fn iter_quads(&self) -> impl Iterator<Item = [usize; 4]> + '_ {
+        self.quads.iter().map(|q| q.ids)
+    }
+
+    //mp find_matches
+    /// Find the quads of stars that match four points (such as star
+    /// centroids in an image), whose hash codes are within a
+    /// distance of that of the points
+    ///
+    /// The result is sorted by distance, best first
+    pub fn find_matches(&self, points: &[[f64; 2]; 4], max_distance: f64) -> Vec<QuadMatch> {
+        let mut result = vec![];
+
+        // A and B are the most widely separated points
+        let d2 = |a: &[f64; 2], b: &[f64; 2]| (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2);
+        let mut ab = (0, 1);
+        for i in 0..4 {
+            for j in (i + 1)..4 {
+                if d2(&points[i], &points[j]) > d2(&points[ab.0], &points[ab.1]) {
+                    ab = (i, j);
+                }
+            }
+        }
+        let cd: Vec<usize> = (0..4).filter(|i| *i != ab.0 && *i != ab.1).collect();
+        let pixels_ab = d2(&points[ab.0], &points[ab.1]).sqrt();
+
+        for order in [
+            [ab.0, ab.1, cd[0], cd[1]],
+            [ab.1, ab.0, cd[0], cd[1]],
+            [ab.0, ab.1, cd[1], cd[0]],
+            [ab.1, ab.0, cd[1], cd[0]],
+        ] {
+            let Some(code) = code_of_points(&order.map(|i| points[i])) else {
+                return result;
+            };
+            for reflected in [false, true] {
+                let code = if reflected {
+                    [code[1], code[0], code[3], code[2]]
+                } else {
+                    code
+                };
+                let start = self
+                    .quads
+                    .partition_point(|q| (q.code[0] as f64) < code[0] - max_distance);
+                for q in self.quads[start..].iter() {
+                    if q.code[0] as f64 > code[0] + max_distance {
+                        break;
+                    }
+                    let distance = q
+                        .code
+                        .iter()
+                        .zip(code.iter())
+                        .map(|(a, b)| (*a as f64 - b).powi(2))
+                        .sum::<f64>()
+                        .sqrt();
+                    if distance > max_distance {
+                        continue;
+                    }
+                    let mut ids = [0; 4];
+                    for (o, id) in order.iter().zip(q.ids.iter()) {
+                        ids[*o] = *id;
+                    }
+                    result.push(QuadMatch {
+                        ids,
+                        plate_scale: q.ab_angle as f64 / pixels_ab,
+                        reflected,
+                        distance,
+                    });
+                }
+            }
+        }
+        result.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        result
+    }
+}
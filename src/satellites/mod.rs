@@ -0,0 +1,339 @@
+//! Artificial satellites from two-line elements
+//!
+//! Two-line elements (TLEs) are read with [read_tles], and each is
+//! made into a [Satellite] that is propagated with the SGP4 model
+//! (following Vallado et al, 'Revisiting Spacetrack Report #3'). The
+//! lunar, solar and resonance terms of the deep-space model (SDP4)
+//! are not included, so satellites with periods of 225 minutes or
+//! more (such as those in geostationary orbits) are not accurate;
+//! these are flagged by [Satellite::is_deep_space], and are skipped
+//! by [crossings].
+//!
+//! A [SatellitePosition] is topocentric for an
+//! [crate::solar_system::Observer] (or geocentric), for the equator
+//! and equinox of J2000 - the same frame as [crate::Star::vector] -
+//! with the elevation of the satellite above the horizon and whether
+//! it is lit by the Sun.
+//!
+//! [crossings] finds the satellites that cross a field of view (such
+//! as that of a solved image) between two times, so that their trails
+//! may be masked from the image before it is matched to stars.
+//!
+//! Times are Julian Dates (UTC), as provided by the [crate::time]
+//! module.
+
+//a Imports
+use geo_nd::{Quaternion, Vector};
+
+use crate::solar_system::{self, Body, Observer, EARTH_RADIUS};
+use crate::{FieldOfView, Quat, Vec3};
+
+mod sgp4;
+mod tle;
+
+use sgp4::Sgp4;
+pub use tle::{read_tles, Tle};
+
+//a Satellite
+//tp Satellite
+/// A satellite given by its two-line elements, with the SGP4 model
+/// initialized from them
+#[derive(Debug, Clone)]
+pub struct Satellite {
+    /// The two-line elements
+    tle: Tle,
+    /// The SGP4 model
+    sgp4: Sgp4,
+}
+
+//ip Satellite
+impl Satellite {
+    //cp new
+    /// Create a new satellite from its two-line elements
+    pub fn new(tle: Tle) -> Self {
+        let sgp4 = Sgp4::new(&tle);
+        Self { tle, sgp4 }
+    }
+
+    //ap tle
+    /// Get the two-line elements of the satellite
+    pub fn tle(&self) -> &Tle {
+        &self.tle
+    }
+
+    //ap name
+    /// Get the name of the satellite
+    pub fn name(&self) -> &str {
+        &self.tle.name
+    }
+
+    //ap is_deep_space
+    /// Return true if the satellite requires the deep-space model,
+    /// and so its positions are not accurate (see
+    /// [Tle::is_deep_space])
+    pub fn is_deep_space(&self) -> bool {
+        self.tle.is_deep_space()
+    }
+
+    //mp teme
+    /// Get the position (km) and velocity (km/s) of the satellite in
+    /// the TEME frame (the true equator and mean equinox of date) at a
+    /// Julian Date
+    ///
+    /// This uses the near-Earth model even for a deep-space satellite
+    /// (see [Satellite::is_deep_space]), so its position is then not
+    /// accurate
+    ///
+    /// Returns None if the orbit has decayed by the time
+    pub fn teme(&self, jd: f64) -> Option<(Vec3, Vec3)> {
+        self.sgp4.propagate_jd(jd)
+    }
+
+    //mp position
+    /// Get the position of the satellite at a Julian Date as seen from
+    /// an observer on the surface of the Earth, or from the center of
+    /// the Earth
+    ///
+    /// Returns None if the orbit has decayed by the time
+    pub fn position(&self, jd: f64, observer: Option<&Observer>) -> Option<SatellitePosition> {
+        let (geocentric, _) = self.teme(jd)?;
+        let (topocentric, elevation) = match observer {
+            Some(observer) => {
+                let site = observer.geocentric(jd);
+                let topocentric = geocentric - site;
+                let (slat, clat) = observer.latitude.sin_cos();
+                let (slst, clst) = observer.local_sidereal_time(jd).sin_cos();
+                let zenith: Vec3 = [clat * clst, clat * slst, slat].into();
+                let elevation = (topocentric.dot(&zenith) / topocentric.length())
+                    .clamp(-1., 1.)
+                    .asin();
+                (topocentric, Some(elevation))
+            }
+            None => (geocentric, None),
+        };
+        let distance = topocentric.length();
+        let vector = solar_system::precess_to_j2000(&topocentric, jd).normalize();
+        let (ra, de) = solar_system::ra_de_of_vec(&vector);
+
+        // The satellite is in the shadow of the Earth (taken to be a
+        // cylinder) if it is behind the Earth from the Sun and within
+        // its radius of the line from the Sun
+        let sun = Body::Sun.position(jd).vector;
+        let geocentric = solar_system::precess_to_j2000(&geocentric, jd);
+        let along = geocentric.dot(&sun);
+        let sunlit = along >= 0. || (geocentric - sun * along).length() > EARTH_RADIUS;
+        Some(SatellitePosition {
+            jd,
+            ra,
+            de,
+            vector,
+            distance,
+            elevation,
+            sunlit,
+        })
+    }
+
+    //mp track
+    /// Get the positions of the satellite from one Julian Date to
+    /// another, at a step in days; the last position is always at the
+    /// end date
+    ///
+    /// Times at which the orbit has decayed are omitted, and if the
+    /// step is not positive then there are no positions
+    pub fn track(
+        &self,
+        start: f64,
+        end: f64,
+        step: f64,
+        observer: Option<&Observer>,
+    ) -> Vec<SatellitePosition> {
+        if step.is_nan() || step <= 0. {
+            return vec![];
+        }
+        let n = ((end - start) / step).ceil().max(0.) as usize;
+        (0..=n)
+            .filter_map(|i| {
+                let jd = (start + step * i as f64).min(end.max(start));
+                self.position(jd, observer)
+            })
+            .collect()
+    }
+
+    //mp passes
+    /// Get the passes of the satellite above the horizon for an
+    /// observer, from one Julian Date to another; each pass is the
+    /// positions of the track (at a step in days) that are above the
+    /// horizon, in order
+    pub fn passes(
+        &self,
+        start: f64,
+        end: f64,
+        step: f64,
+        observer: &Observer,
+    ) -> Vec<Vec<SatellitePosition>> {
+        self.track(start, end, step, Some(observer))
+            .split(|p| !p.is_above_horizon())
+            .filter(|pass| !pass.is_empty())
+            .map(|pass| pass.to_vec())
+            .collect()
+    }
+}
+
+//a SatellitePosition
+//tp SatellitePosition
+/// The position of a [Satellite] at a time, from
+/// [Satellite::position]
+#[derive(Debug, Clone)]
+pub struct SatellitePosition {
+    /// The Julian Date
+    pub jd: f64,
+    /// The right ascension (J2000) in radians
+    pub ra: f64,
+    /// The declination (J2000) in radians
+    pub de: f64,
+    /// The unit vector of the direction of the satellite, as for
+    /// [crate::Star::vector]
+    pub vector: Vec3,
+    /// The distance from the observer (or the center of the Earth) in
+    /// kilometers
+    pub distance: f64,
+    /// The elevation above the horizon of the observer in radians,
+    /// if there is an observer
+    pub elevation: Option<f64>,
+    /// True if the satellite is lit by the Sun
+    pub sunlit: bool,
+}
+
+//ip SatellitePosition
+impl SatellitePosition {
+    //ap is_above_horizon
+    /// Return true if the satellite is above the horizon of the
+    /// observer (always true for a geocentric position)
+    pub fn is_above_horizon(&self) -> bool {
+        self.elevation.is_none_or(|e| e > 0.)
+    }
+}
+
+//a SatelliteCrossing
+//tp SatelliteCrossing
+/// A crossing of a field of view by a satellite, from [crossings]
+#[derive(Debug, Clone)]
+pub struct SatelliteCrossing {
+    /// The index of the satellite in the slice given to [crossings]
+    pub satellite: usize,
+    /// The positions of the satellite within the field of view, in
+    /// order of time
+    pub positions: Vec<SatellitePosition>,
+}
+
+//ip SatelliteCrossing
+impl SatelliteCrossing {
+    //ap entry
+    /// Get the Julian Date of the first position within the field of
+    /// view
+    pub fn entry(&self) -> f64 {
+        self.positions[0].jd
+    }
+
+    //ap exit
+    /// Get the Julian Date of the last position within the field of
+    /// view
+    pub fn exit(&self) -> f64 {
+        self.positions[self.positions.len() - 1].jd
+    }
+
+    //ap is_sunlit
+    /// Return true if the satellite is lit by the Sun for any part of
+    /// the crossing, and so may leave a trail
+    pub fn is_sunlit(&self) -> bool {
+        self.positions.iter().any(|p| p.sunlit)
+    }
+}
+
+//a Functions
+//fp read_satellites
+/// Read a file of two-line elements (as for [read_tles]) as
+/// satellites
+pub fn read_satellites(s: &str) -> Result<Vec<Satellite>, crate::Error> {
+    Ok(read_tles(s)?.into_iter().map(Satellite::new).collect())
+}
+
+//fp crossings
+/// Find the satellites that cross a field of view, for a view
+/// orientation (as for [FieldOfView::contains]), as seen by an
+/// observer between two Julian Dates
+///
+/// Each satellite is sampled at the step (in days); where a step
+/// passes near the field of view it is divided into steps of an
+/// eighth of the size of the field of view, and the positions within
+/// the field of view (above the horizon) are returned. A satellite
+/// that crosses the field more than once has more than one crossing.
+///
+/// The step must be short enough that the path of a satellite over a
+/// step is close to an arc of a great circle; a few seconds is
+/// suitable for satellites in low orbits. If it is not positive then
+/// there are no crossings.
+///
+/// Deep-space satellites (see [Satellite::is_deep_space]) are
+/// skipped, as their positions are not accurate enough. The field of
+/// view should be valid (see [FieldOfView::validate]).
+pub fn crossings(
+    satellites: &[Satellite],
+    observer: &Observer,
+    fov: &FieldOfView,
+    orient: &Quat,
+    start: f64,
+    end: f64,
+    step: f64,
+) -> Vec<SatelliteCrossing> {
+    let center = orient.conjugate().apply3(&[0., 0., -1.].into());
+    let max_angle = fov.max_angle();
+    let fine_angle = max_angle / 8.0;
+    let angle_between = |v0: &Vec3, v1: &Vec3| v0.dot(v1).clamp(-1., 1.).acos();
+    let mut result = vec![];
+    for (index, satellite) in satellites.iter().enumerate() {
+        if satellite.is_deep_space() {
+            continue;
+        }
+        let track = satellite.track(start, end, step, Some(observer));
+        let mut times = vec![];
+        for p in track.windows(2) {
+            let (p0, p1) = (&p[0], &p[1]);
+            let moved = angle_between(&p0.vector, &p1.vector);
+            let near = angle_between(&center, &p0.vector) <= max_angle + 2.0 * moved;
+            if near && (p0.is_above_horizon() || p1.is_above_horizon()) {
+                let n = ((moved / fine_angle).ceil() as usize).max(1);
+                times.extend((0..n).map(|i| p0.jd + (p1.jd - p0.jd) * i as f64 / n as f64));
+            } else {
+                times.push(p0.jd);
+            }
+        }
+        if let Some(last) = track.last() {
+            times.push(last.jd);
+        }
+
+        let mut positions = vec![];
+        for jd in times {
+            let inside = satellite
+                .position(jd, Some(observer))
+                .filter(|p| p.is_above_horizon() && fov.contains(orient, &p.vector));
+            match inside {
+                Some(p) => positions.push(p),
+                None if !positions.is_empty() => {
+                    result.push(SatelliteCrossing {
+                        satellite: index,
+                        positions: std::mem::take(&mut positions),
+                    });
+                }
+                None => (),
+            }
+        }
+        if !positions.is_empty() {
+            result.push(SatelliteCrossing {
+                satellite: index,
+                positions,
+            });
+        }
+    }
+    result
+}
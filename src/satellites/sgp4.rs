@@ -0,0 +1,384 @@
+//a Imports
+use super::Tle;
+use crate::Vec3;
+
+//a Constants
+/// The equatorial radius of the Earth in kilometers (WGS72, as used
+/// for two-line elements)
+const RADIUS: f64 = 6378.135;
+
+/// The gravitational parameter of the Earth in km^3/s^2 (WGS72)
+const MU: f64 = 398_600.8;
+
+/// The second zonal harmonic of the Earth (WGS72)
+const J2: f64 = 0.001_082_616;
+
+/// The third zonal harmonic of the Earth (WGS72)
+const J3: f64 = -0.000_002_538_81;
+
+/// The fourth zonal harmonic of the Earth (WGS72)
+const J4: f64 = -0.000_001_655_97;
+
+/// The number of minutes in a day
+const MINUTES_PER_DAY: f64 = 1440.0;
+
+//a Sgp4
+//ti Sgp4
+/// The SGP4 model of a satellite, initialized from its two-line
+/// elements
+///
+/// This follows Vallado, Crawford, Hujsak and Kelso, 'Revisiting
+/// Spacetrack Report #3' (2006), for near-earth orbits. The lunar,
+/// solar and resonance terms of the deep-space (SDP4) model, for
+/// orbits with a period of 225 minutes or more, are not included.
+#[derive(Debug, Clone)]
+pub(crate) struct Sgp4 {
+    /// Julian Date of the epoch of the elements
+    epoch: f64,
+    /// Drag term (per Earth radius)
+    bstar: f64,
+    /// Mean elements at the epoch: inclination, node, eccentricity,
+    /// argument of perigee, mean anomaly (radians) and mean motion
+    /// (un-Kozai'd, radians per minute)
+    inclo: f64,
+    nodeo: f64,
+    ecco: f64,
+    argpo: f64,
+    mo: f64,
+    no: f64,
+    /// True for the simplified drag model of low perigees
+    isimp: bool,
+    /// Secular rates and coefficients
+    mdot: f64,
+    argpdot: f64,
+    nodedot: f64,
+    nodecf: f64,
+    omgcof: f64,
+    xmcof: f64,
+    eta: f64,
+    delmo: f64,
+    sinmao: f64,
+    cc1: f64,
+    cc4: f64,
+    cc5: f64,
+    d2: f64,
+    d3: f64,
+    d4: f64,
+    t2cof: f64,
+    t3cof: f64,
+    t4cof: f64,
+    t5cof: f64,
+    /// Long-period and short-period coefficients
+    aycof: f64,
+    xlcof: f64,
+    con41: f64,
+    x1mth2: f64,
+    x7thm1: f64,
+}
+
+//ip Sgp4
+impl Sgp4 {
+    //fp xke
+    /// The square root of the gravitational parameter in Earth radii
+    /// per minute units
+    fn xke() -> f64 {
+        60.0 / (RADIUS * RADIUS * RADIUS / MU).sqrt()
+    }
+
+    //cp new
+    /// Initialize the model from two-line elements
+    pub fn new(tle: &Tle) -> Self {
+        let xke = Self::xke();
+        let j3oj2 = J3 / J2;
+        let x2o3 = 2.0 / 3.0;
+
+        let ecco = tle.eccentricity;
+        let inclo = tle.inclination;
+        let argpo = tle.arg_perigee;
+        let mo = tle.mean_anomaly;
+        let nodeo = tle.node;
+        let bstar = tle.bstar;
+        let no_kozai = tle.mean_motion * std::f64::consts::TAU / MINUTES_PER_DAY;
+
+        // Recover the original mean motion and semi-major axis
+        let eccsq = ecco * ecco;
+        let omeosq = 1.0 - eccsq;
+        let rteosq = omeosq.sqrt();
+        let cosio = inclo.cos();
+        let cosio2 = cosio * cosio;
+        let ak = (xke / no_kozai).powf(x2o3);
+        let d1 = 0.75 * J2 * (3.0 * cosio2 - 1.0) / (rteosq * omeosq);
+        let del = d1 / (ak * ak);
+        let adel = ak * (1.0 - del * del - del * (1.0 / 3.0 + 134.0 * del * del / 81.0));
+        let del = d1 / (adel * adel);
+        let no = no_kozai / (1.0 + del);
+        let ao = (xke / no).powf(x2o3);
+        let sinio = inclo.sin();
+        let po = ao * omeosq;
+        let con42 = 1.0 - 5.0 * cosio2;
+        let con41 = -con42 - cosio2 - cosio2;
+        let posq = po * po;
+        let rp = ao * (1.0 - ecco);
+
+        // The atmospheric density parameters, adjusted for low
+        // perigees
+        let ss = 78.0 / RADIUS + 1.0;
+        let qzms2t = ((120.0 - 78.0) / RADIUS).powi(4);
+        let isimp = rp < 220.0 / RADIUS + 1.0;
+        let mut sfour = ss;
+        let mut qzms24 = qzms2t;
+        let perige = (rp - 1.0) * RADIUS;
+        if perige < 156.0 {
+            sfour = if perige < 98.0 { 20.0 } else { perige - 78.0 };
+            qzms24 = ((120.0 - sfour) / RADIUS).powi(4);
+            sfour = sfour / RADIUS + 1.0;
+        }
+
+        let pinvsq = 1.0 / posq;
+        let tsi = 1.0 / (ao - sfour);
+        let eta = ao * ecco * tsi;
+        let etasq = eta * eta;
+        let eeta = ecco * eta;
+        let psisq = (1.0 - etasq).abs();
+        let coef = qzms24 * tsi.powi(4);
+        let coef1 = coef / psisq.powf(3.5);
+        let cc2 = coef1
+            * no
+            * (ao * (1.0 + 1.5 * etasq + eeta * (4.0 + etasq))
+                + 0.375 * J2 * tsi / psisq * con41 * (8.0 + 3.0 * etasq * (8.0 + etasq)));
+        let cc1 = bstar * cc2;
+        let cc3 = if ecco > 1.0E-4 {
+            -2.0 * coef * tsi * j3oj2 * no * sinio / ecco
+        } else {
+            0.
+        };
+        let x1mth2 = 1.0 - cosio2;
+        let cc4 = 2.0
+            * no
+            * coef1
+            * ao
+            * omeosq
+            * (eta * (2.0 + 0.5 * etasq) + ecco * (0.5 + 2.0 * etasq)
+                - J2 * tsi / (ao * psisq)
+                    * (-3.0 * con41 * (1.0 - 2.0 * eeta + etasq * (1.5 - 0.5 * eeta))
+                        + 0.75
+                            * x1mth2
+                            * (2.0 * etasq - eeta * (1.0 + etasq))
+                            * (2.0 * argpo).cos()));
+        let cc5 = 2.0 * coef1 * ao * omeosq * (1.0 + 2.75 * (etasq + eeta) + eeta * etasq);
+
+        // Secular rates of the mean anomaly, argument of perigee and
+        // node
+        let cosio4 = cosio2 * cosio2;
+        let temp1 = 1.5 * J2 * pinvsq * no;
+        let temp2 = 0.5 * temp1 * J2 * pinvsq;
+        let temp3 = -0.46875 * J4 * pinvsq * pinvsq * no;
+        let mdot = no
+            + 0.5 * temp1 * rteosq * con41
+            + 0.0625 * temp2 * rteosq * (13.0 - 78.0 * cosio2 + 137.0 * cosio4);
+        let argpdot = -0.5 * temp1 * con42
+            + 0.0625 * temp2 * (7.0 - 114.0 * cosio2 + 395.0 * cosio4)
+            + temp3 * (3.0 - 36.0 * cosio2 + 49.0 * cosio4);
+        let xhdot1 = -temp1 * cosio;
+        let nodedot = xhdot1
+            + (0.5 * temp2 * (4.0 - 19.0 * cosio2) + 2.0 * temp3 * (3.0 - 7.0 * cosio2)) * cosio;
+        let omgcof = bstar * cc3 * argpo.cos();
+        let xmcof = if ecco > 1.0E-4 {
+            -x2o3 * coef * bstar / eeta
+        } else {
+            0.
+        };
+        let nodecf = 3.5 * omeosq * xhdot1 * cc1;
+        let t2cof = 1.5 * cc1;
+        let xlcof = -0.25 * j3oj2 * sinio * (3.0 + 5.0 * cosio)
+            / if (cosio + 1.0).abs() > 1.5E-12 {
+                1.0 + cosio
+            } else {
+                1.5E-12
+            };
+        let aycof = -0.5 * j3oj2 * sinio;
+        let delmo = (1.0 + eta * mo.cos()).powi(3);
+        let sinmao = mo.sin();
+        let x7thm1 = 7.0 * cosio2 - 1.0;
+
+        let (mut d2, mut d3, mut d4) = (0., 0., 0.);
+        let (mut t3cof, mut t4cof, mut t5cof) = (0., 0., 0.);
+        if !isimp {
+            let cc1sq = cc1 * cc1;
+            d2 = 4.0 * ao * tsi * cc1sq;
+            let temp = d2 * tsi * cc1 / 3.0;
+            d3 = (17.0 * ao + sfour) * temp;
+            d4 = 0.5 * temp * ao * tsi * (221.0 * ao + 31.0 * sfour) * cc1;
+            t3cof = d2 + 2.0 * cc1sq;
+            t4cof = 0.25 * (3.0 * d3 + cc1 * (12.0 * d2 + 10.0 * cc1sq));
+            t5cof = 0.2
+                * (3.0 * d4 + 12.0 * cc1 * d3 + 6.0 * d2 * d2 + 15.0 * cc1sq * (2.0 * d2 + cc1sq));
+        }
+
+        Self {
+            epoch: tle.epoch,
+            bstar,
+            inclo,
+            nodeo,
+            ecco,
+            argpo,
+            mo,
+            no,
+            isimp,
+            mdot,
+            argpdot,
+            nodedot,
+            nodecf,
+            omgcof,
+            xmcof,
+            eta,
+            delmo,
+            sinmao,
+            cc1,
+            cc4,
+            cc5,
+            d2,
+            d3,
+            d4,
+            t2cof,
+            t3cof,
+            t4cof,
+            t5cof,
+            aycof,
+            xlcof,
+            con41,
+            x1mth2,
+            x7thm1,
+        }
+    }
+
+    //mp propagate
+    /// Get the position (km) and velocity (km/s) in the TEME frame
+    /// (the true equator and mean equinox of date) at a time in
+    /// minutes from the epoch of the elements
+    ///
+    /// Returns None if the orbit has decayed or the elements are not
+    /// valid at the time
+    pub fn propagate(&self, t: f64) -> Option<(Vec3, Vec3)> {
+        let tau = std::f64::consts::TAU;
+        let xke = Self::xke();
+
+        // Secular gravity and atmospheric drag
+        let xmdf = self.mo + self.mdot * t;
+        let argpdf = self.argpo + self.argpdot * t;
+        let nodedf = self.nodeo + self.nodedot * t;
+        let mut argpm = argpdf;
+        let mut mm = xmdf;
+        let t2 = t * t;
+        let mut nodem = nodedf + self.nodecf * t2;
+        let mut tempa = 1.0 - self.cc1 * t;
+        let mut tempe = self.bstar * self.cc4 * t;
+        let mut templ = self.t2cof * t2;
+        if !self.isimp {
+            let delomg = self.omgcof * t;
+            let delm = self.xmcof * ((1.0 + self.eta * xmdf.cos()).powi(3) - self.delmo);
+            let temp = delomg + delm;
+            mm = xmdf + temp;
+            argpm = argpdf - temp;
+            let t3 = t2 * t;
+            let t4 = t3 * t;
+            tempa -= self.d2 * t2 + self.d3 * t3 + self.d4 * t4;
+            tempe += self.bstar * self.cc5 * (mm.sin() - self.sinmao);
+            templ += self.t3cof * t3 + t4 * (self.t4cof + t * self.t5cof);
+        }
+        let am = (xke / self.no).powf(2.0 / 3.0) * tempa * tempa;
+        let nm = xke / am.powf(1.5);
+        let mut em = self.ecco - tempe;
+        if !(-0.001..1.0).contains(&em) || am <= 0. {
+            return None;
+        }
+        em = em.max(1.0E-6);
+        mm += self.no * templ;
+        let xlm = mm + argpm + nodem;
+        nodem = nodem.rem_euclid(tau);
+        argpm = argpm.rem_euclid(tau);
+        let xlm = xlm.rem_euclid(tau);
+        mm = (xlm - argpm - nodem).rem_euclid(tau);
+
+        // Long-period periodics
+        let (sinip, cosip) = self.inclo.sin_cos();
+        let axnl = em * argpm.cos();
+        let temp = 1.0 / (am * (1.0 - em * em));
+        let aynl = em * argpm.sin() + temp * self.aycof;
+        let xl = mm + argpm + nodem + temp * self.xlcof * axnl;
+
+        // Kepler's equation for the eccentric longitude
+        let u = (xl - nodem).rem_euclid(tau);
+        let mut eo1 = u;
+        let (mut sineo1, mut coseo1) = (0., 0.);
+        for _ in 0..10 {
+            (sineo1, coseo1) = eo1.sin_cos();
+            let d =
+                (u - aynl * coseo1 + axnl * sineo1 - eo1) / (1.0 - coseo1 * axnl - sineo1 * aynl);
+            eo1 += d.clamp(-0.95, 0.95);
+            if d.abs() < 1.0E-12 {
+                break;
+            }
+        }
+
+        // Short-period periodics
+        let ecose = axnl * coseo1 + aynl * sineo1;
+        let esine = axnl * sineo1 - aynl * coseo1;
+        let el2 = axnl * axnl + aynl * aynl;
+        let pl = am * (1.0 - el2);
+        if pl < 0. {
+            return None;
+        }
+        let rl = am * (1.0 - ecose);
+        let rdotl = am.sqrt() * esine / rl;
+        let rvdotl = pl.sqrt() / rl;
+        let betal = (1.0 - el2).sqrt();
+        let temp = esine / (1.0 + betal);
+        let sinu = am / rl * (sineo1 - aynl - axnl * temp);
+        let cosu = am / rl * (coseo1 - axnl + aynl * temp);
+        let su = sinu.atan2(cosu);
+        let sin2u = (cosu + cosu) * sinu;
+        let cos2u = 1.0 - 2.0 * sinu * sinu;
+        let temp = 1.0 / pl;
+        let temp1 = 0.5 * J2 * temp;
+        let temp2 = temp1 * temp;
+
+        let mrt = rl * (1.0 - 1.5 * temp2 * betal * self.con41) + 0.5 * temp1 * self.x1mth2 * cos2u;
+        let su = su - 0.25 * temp2 * self.x7thm1 * sin2u;
+        let xnode = nodem + 1.5 * temp2 * cosip * sin2u;
+        let xinc = self.inclo + 1.5 * temp2 * cosip * sinip * cos2u;
+        let mvt = rdotl - nm * temp1 * self.x1mth2 * sin2u / xke;
+        let rvdot = rvdotl + nm * temp1 * (self.x1mth2 * cos2u + 1.5 * self.con41) / xke;
+        if mrt < 1.0 {
+            return None;
+        }
+
+        // Orientation vectors
+        let (sinsu, cossu) = su.sin_cos();
+        let (snod, cnod) = xnode.sin_cos();
+        let (sini, cosi) = xinc.sin_cos();
+        let xmx = -snod * cosi;
+        let xmy = cnod * cosi;
+        let u: Vec3 = [
+            xmx * sinsu + cnod * cossu,
+            xmy * sinsu + snod * cossu,
+            sini * sinsu,
+        ]
+        .into();
+        let v: Vec3 = [
+            xmx * cossu - cnod * sinsu,
+            xmy * cossu - snod * sinsu,
+            sini * cossu,
+        ]
+        .into();
+        let km_per_sec = RADIUS * xke / 60.0;
+        Some((u * (mrt * RADIUS), (u * mvt + v * rvdot) * km_per_sec))
+    }
+
+    //mp propagate_jd
+    /// Get the position (km) and velocity (km/s) in the TEME frame at
+    /// a Julian Date
+    pub fn propagate_jd(&self, jd: f64) -> Option<(Vec3, Vec3)> {
+        self.propagate((jd - self.epoch) * MINUTES_PER_DAY)
+    }
+}
@@ -0,0 +1,211 @@
+//a Imports
+use crate::Error;
+
+//a Tle
+//tp Tle
+/// The two-line elements of a satellite, as published by NORAD (for
+/// example by Celestrak or Space-Track)
+///
+/// The angles are in radians, and the elements are the mean elements
+/// of the SGP4 model for the TEME frame (the true equator and mean
+/// equinox of date)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tle {
+    /// The name of the satellite (from the line before the elements,
+    /// if any; otherwise the international designator)
+    pub name: String,
+    /// The NORAD catalog number
+    pub catalog_number: u32,
+    /// The international designator, such as '98067A'
+    pub designator: String,
+    /// The Julian Date (UTC) of the epoch of the elements
+    pub epoch: f64,
+    /// The drag term B* (per Earth radius)
+    pub bstar: f64,
+    /// The inclination in radians
+    pub inclination: f64,
+    /// The right ascension of the ascending node in radians
+    pub node: f64,
+    /// The eccentricity
+    pub eccentricity: f64,
+    /// The argument of perigee in radians
+    pub arg_perigee: f64,
+    /// The mean anomaly in radians
+    pub mean_anomaly: f64,
+    /// The mean motion in revolutions per day
+    pub mean_motion: f64,
+}
+
+//ip Tle
+impl Tle {
+    //fp parse
+    /// Parse the two lines of a set of elements, with the name of the
+    /// satellite if known
+    ///
+    /// The line numbers, catalog numbers and checksums of the lines
+    /// must be correct
+    pub fn parse(name: Option<&str>, line1: &str, line2: &str) -> Option<Self> {
+        let line1 = line1.trim_end();
+        let line2 = line2.trim_end();
+        if line1.len() < 69 || line2.len() < 69 || !line1.is_ascii() || !line2.is_ascii() {
+            return None;
+        }
+        if !line1.starts_with("1 ") || !line2.starts_with("2 ") {
+            return None;
+        }
+        if !has_valid_checksum(line1) || !has_valid_checksum(line2) {
+            return None;
+        }
+        let catalog_number = catalog_number(&line1[2..7])?;
+        if catalog_number != self::catalog_number(&line2[2..7])? {
+            return None;
+        }
+        let designator = line1[9..17].trim().to_string();
+        let year: i32 = line1[18..20].trim().parse().ok()?;
+        let year = if year < 57 { 2000 + year } else { 1900 + year };
+        let day: f64 = line1[20..32].trim().parse().ok()?;
+        let epoch = crate::time::julian_date(year, 1, 0.0) + day;
+        let bstar = exponential(&line1[53..61])?;
+
+        let angle = |s: &str| s.trim().parse::<f64>().ok().map(f64::to_radians);
+        let inclination = angle(&line2[8..16])?;
+        let node = angle(&line2[17..25])?;
+        let eccentricity: f64 = format!("0.{}", line2[26..33].trim()).parse().ok()?;
+        let arg_perigee = angle(&line2[34..42])?;
+        let mean_anomaly = angle(&line2[43..51])?;
+        let mean_motion: f64 = line2[52..63].trim().parse().ok()?;
+        if mean_motion <= 0. {
+            return None;
+        }
+        let name = match name.map(str::trim) {
+            Some(name) if !name.is_empty() => name.to_string(),
+            _ => designator.clone(),
+        };
+        Some(Self {
+            name,
+            catalog_number,
+            designator,
+            epoch,
+            bstar,
+            inclination,
+            node,
+            eccentricity,
+            arg_perigee,
+            mean_anomaly,
+            mean_motion,
+        })
+    }
+
+    //ap period
+    /// Get the orbital period in minutes
+    pub fn period(&self) -> f64 {
+        1440.0 / self.mean_motion
+    }
+
+    //ap is_deep_space
+    /// Return true if the orbital period is 225 minutes or more, for
+    /// which the deep-space model (SDP4) is required
+    ///
+    /// The deep-space terms are not implemented, so the positions of
+    /// such satellites are not accurate
+    pub fn is_deep_space(&self) -> bool {
+        self.period() >= 225.0
+    }
+}
+
+//a Functions
+//fi has_valid_checksum
+/// Return true if the checksum (the last of 69 columns) of a line of
+/// elements is correct: the sum of the digits of the line, with 1 for
+/// each minus sign, modulo 10
+fn has_valid_checksum(line: &str) -> bool {
+    let sum: u32 = line[0..68]
+        .chars()
+        .map(|c| match c {
+            '-' => 1,
+            _ => c.to_digit(10).unwrap_or(0),
+        })
+        .sum();
+    line[68..69].parse::<u32>().ok() == Some(sum % 10)
+}
+
+//fi catalog_number
+/// Parse a catalog number, which may use the 'alpha-5' form of a
+/// letter (other than I or O) for the ten-thousands of numbers from
+/// 100000
+fn catalog_number(s: &str) -> Option<u32> {
+    let s = s.trim();
+    let first = s.chars().next()?;
+    if !first.is_ascii_uppercase() {
+        return s.parse().ok();
+    }
+    let mut value = first as u32 - 'A' as u32 + 10;
+    if first > 'I' {
+        value -= 1;
+    }
+    if first > 'O' {
+        value -= 1;
+    }
+    let rest: u32 = s.get(1..)?.parse().ok()?;
+    (first != 'I' && first != 'O').then_some(value * 10000 + rest)
+}
+
+//fi exponential
+/// Parse a number in the form of the drag terms of the elements, such
+/// as ' 28098-4' for 0.28098E-4, with an implied decimal point
+fn exponential(s: &str) -> Option<f64> {
+    let s = s.trim();
+    if s.len() < 2 {
+        return None;
+    }
+    let (mantissa, exponent) = s.split_at(s.len() - 2);
+    let (sign, digits) = match mantissa.strip_prefix('-') {
+        Some(digits) => (-1.0, digits),
+        None => (1.0, mantissa.strip_prefix('+').unwrap_or(mantissa)),
+    };
+    let mantissa: f64 = format!("0.{}", digits.trim()).parse().ok()?;
+    let exponent: i32 = exponent.parse().ok()?;
+    Some(sign * mantissa * 10_f64.powi(exponent))
+}
+
+//fp read_tles
+/// Read a file of two-line elements, each optionally preceded by a
+/// line with the name of the satellite (which may start with '0 ', as
+/// in the three-line format)
+///
+/// Blank lines are ignored. An element line that cannot be parsed is
+/// an error, reporting its line number (from 1).
+///
+/// Elements of deep-space satellites are read, but should be checked
+/// for with [Tle::is_deep_space] as they cannot be propagated
+/// accurately.
+pub fn read_tles(s: &str) -> Result<Vec<Tle>, Error> {
+    let lines: Vec<&str> = s.lines().collect();
+    let mut tles = vec![];
+    let mut name: Option<(usize, &str)> = None;
+    let mut n = 0;
+    while n < lines.len() {
+        let line = lines[n];
+        if line.trim().is_empty() {
+            n += 1;
+            continue;
+        }
+        if !line.starts_with("1 ") {
+            if let Some((name_line, _)) = name {
+                return Err(Error::BadTleLine(name_line + 1));
+            }
+            name = Some((n, line.strip_prefix("0 ").unwrap_or(line)));
+            n += 1;
+            continue;
+        }
+        let line2 = lines.get(n + 1).copied().unwrap_or("");
+        let tle = Tle::parse(name.take().map(|(_, name)| name), line, line2)
+            .ok_or(Error::BadTleLine(n + 1))?;
+        tles.push(tle);
+        n += 2;
+    }
+    if let Some((name_line, _)) = name {
+        return Err(Error::BadTleLine(name_line + 1));
+    }
+    Ok(tles)
+}
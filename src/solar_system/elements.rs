@@ -0,0 +1,170 @@
+//a Imports
+use crate::Vec3;
+
+//a PlanetElements
+//ti PlanetElements
+/// The mean Keplerian elements of a planet (or the Earth-Moon
+/// barycenter) and their rates per Julian century, for the ecliptic
+/// and equinox of J2000, with the extra terms for the mean anomaly of
+/// the outer planets
+///
+/// These are from Standish, 'Keplerian Elements for Approximate
+/// Positions of the Major Planets' (JPL), for 3000 BC to 3000 AD; the
+/// errors are up to a few arcminutes for the inner planets, and
+/// somewhat more for Saturn, Uranus and Neptune.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PlanetElements {
+    /// Semi-major axis (AU) and rate
+    a: (f64, f64),
+    /// Eccentricity and rate
+    e: (f64, f64),
+    /// Inclination (degrees) and rate
+    i: (f64, f64),
+    /// Mean longitude (degrees) and rate
+    l: (f64, f64),
+    /// Longitude of perihelion (degrees) and rate
+    peri: (f64, f64),
+    /// Longitude of the ascending node (degrees) and rate
+    node: (f64, f64),
+    /// The extra terms b, c, s and f for the mean anomaly
+    extra: [f64; 4],
+}
+
+//ip PlanetElements
+impl PlanetElements {
+    /// Mercury
+    pub const MERCURY: Self = Self {
+        a: (0.38709843, 0.0),
+        e: (0.20563661, 0.00002123),
+        i: (7.00559432, -0.00590158),
+        l: (252.25166724, 149472.67486623),
+        peri: (77.45771895, 0.15940013),
+        node: (48.33961819, -0.12214182),
+        extra: [0.; 4],
+    };
+    /// Venus
+    pub const VENUS: Self = Self {
+        a: (0.72332102, -0.00000026),
+        e: (0.00676399, -0.00005107),
+        i: (3.39777545, 0.00043494),
+        l: (181.97970850, 58517.81560260),
+        peri: (131.76755713, 0.05679648),
+        node: (76.67261496, -0.27274174),
+        extra: [0.; 4],
+    };
+    /// The Earth-Moon barycenter
+    pub const EM_BARY: Self = Self {
+        a: (1.00000018, -0.00000003),
+        e: (0.01673163, -0.00003661),
+        i: (-0.00054346, -0.01337178),
+        l: (100.46691572, 35999.37306329),
+        peri: (102.93005885, 0.31795260),
+        node: (-5.11260389, -0.24123856),
+        extra: [0.; 4],
+    };
+    /// Mars
+    pub const MARS: Self = Self {
+        a: (1.52371243, 0.00000097),
+        e: (0.09336511, 0.00009149),
+        i: (1.85181869, -0.00724757),
+        l: (-4.56813164, 19140.29934243),
+        peri: (-23.91744784, 0.45223625),
+        node: (49.71320984, -0.26852431),
+        extra: [0.; 4],
+    };
+    /// Jupiter
+    pub const JUPITER: Self = Self {
+        a: (5.20248019, -0.00002864),
+        e: (0.04853590, 0.00018026),
+        i: (1.29861416, -0.00322699),
+        l: (34.33479152, 3034.90371757),
+        peri: (14.27495244, 0.18199196),
+        node: (100.29282654, 0.13024619),
+        extra: [-0.00012452, 0.06064060, -0.35635438, 38.35125000],
+    };
+    /// Saturn
+    pub const SATURN: Self = Self {
+        a: (9.54149883, -0.00003065),
+        e: (0.05550825, -0.00032044),
+        i: (2.49424102, 0.00451969),
+        l: (50.07571329, 1222.11494724),
+        peri: (92.86136063, 0.54179478),
+        node: (113.63998702, -0.25015002),
+        extra: [0.00025899, -0.13434469, 0.87320147, 38.35125000],
+    };
+    /// Uranus
+    pub const URANUS: Self = Self {
+        a: (19.18797948, -0.00020455),
+        e: (0.04685740, -0.00001550),
+        i: (0.77298127, -0.00180155),
+        l: (314.20276625, 428.49512595),
+        peri: (172.43404441, 0.09266985),
+        node: (73.96250215, 0.05739699),
+        extra: [0.00058331, -0.97731848, 0.17689245, 7.67025000],
+    };
+    /// Neptune
+    pub const NEPTUNE: Self = Self {
+        a: (30.06952752, 0.00006447),
+        e: (0.00895439, 0.00000818),
+        i: (1.77005520, 0.00022400),
+        l: (304.22289287, 218.46515314),
+        peri: (46.68158724, 0.01009938),
+        node: (131.78635853, -0.00606302),
+        extra: [-0.00041348, 0.68346318, -0.10162547, 7.67025000],
+    };
+
+    //mp heliocentric
+    /// Get the heliocentric position (in AU, for the ecliptic and
+    /// equinox of J2000) at a time in Julian centuries from J2000.0
+    pub fn heliocentric(&self, t: f64) -> Vec3 {
+        let at = |(x, dx): (f64, f64)| x + dx * t;
+        let a = at(self.a);
+        let e = at(self.e);
+        let i = at(self.i).to_radians();
+        let l = at(self.l);
+        let peri = at(self.peri);
+        let node = at(self.node);
+        let [b, c, s, f] = self.extra;
+        let ft = (f * t).to_radians();
+        let m = l - peri + b * t * t + c * ft.cos() + s * ft.sin();
+        let m = (m + 180.0).rem_euclid(360.0) - 180.0;
+        let ea = solve_kepler(m.to_radians(), e);
+        let x = a * (ea.cos() - e);
+        let y = a * (1.0 - e * e).sqrt() * ea.sin();
+        ecliptic_of_orbit(x, y, i, node.to_radians(), (peri - node).to_radians())
+    }
+}
+
+//a Functions
+//fp solve_kepler
+/// Solve Kepler's equation M = E - e sin(E) for the eccentric anomaly
+/// E (in radians) of an elliptical orbit, given the mean anomaly M (in
+/// radians) and eccentricity e (less than 1)
+pub(crate) fn solve_kepler(m: f64, e: f64) -> f64 {
+    let mut ea = m + 0.85 * e * m.sin().signum();
+    for _ in 0..50 {
+        let d = (ea - e * ea.sin() - m) / (1.0 - e * ea.cos());
+        ea -= d;
+        if d.abs() < 1E-12 {
+            break;
+        }
+    }
+    ea
+}
+
+//fp ecliptic_of_orbit
+/// Get the position in the ecliptic frame of a point (x, y) in the
+/// plane of an orbit, where x is towards perihelion, given the
+/// inclination, longitude of the ascending node and argument of
+/// perihelion (in radians)
+pub(crate) fn ecliptic_of_orbit(x: f64, y: f64, i: f64, node: f64, arg_peri: f64) -> Vec3 {
+    let (sw, cw) = arg_peri.sin_cos();
+    let (sn, cn) = node.sin_cos();
+    let (si, ci) = i.sin_cos();
+    [
+        (cw * cn - sw * sn * ci) * x + (-sw * cn - cw * sn * ci) * y,
+        (cw * sn + sw * cn * ci) * x + (-sw * sn + cw * cn * ci) * y,
+        (sw * si) * x + (cw * si) * y,
+    ]
+    .into()
+}
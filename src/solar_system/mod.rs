@@ -0,0 +1,356 @@
+//! Low-precision positions of the Sun, Moon and planets
+//!
+//! The planets (and the Earth-Moon barycenter) use mean Keplerian
+//! elements with secular rates and the perturbation terms of the
+//! outer planets, from Standish (JPL), valid for 3000 BC to 3000 AD;
+//! the Moon uses the main terms of the ELP-2000/82 series as given by
+//! Meeus. Positions are good to a few arcminutes (the Moon to about an
+//! arcminute), which is enough to identify a planet in an image.
+//!
+//! A [BodyPosition] is geocentric, corrected for light time, for the
+//! equator and equinox of J2000 - the same frame as [Star::vector];
+//! aberration and nutation are ignored. It includes the apparent
+//! magnitude, phase angle and illuminated fraction of the body. Note
+//! that the parallax of the Moon (up to a degree) is not included.
+//!
+//! Times are Julian Dates, as provided by the [crate::time] module.
+//!
+//! An [Observer] is a site on the Earth, whose position is used for
+//! topocentric positions of minor bodies and satellites.
+
+//a Imports
+use geo_nd::Vector;
+
+use crate::{Star, Vec3};
+
+mod elements;
+mod moon;
+mod observer;
+
+use elements::PlanetElements;
+pub(crate) use elements::{ecliptic_of_orbit, solve_kepler};
+pub(crate) use moon::KM_PER_AU;
+pub use observer::{Observer, EARTH_RADIUS};
+
+//a Constants
+/// The obliquity of the ecliptic at J2000.0 in radians
+pub const OBLIQUITY_J2000: f64 = 23.439_291_1 * std::f64::consts::PI / 180.0;
+
+/// The light time for one AU, in days
+pub(crate) const LIGHT_DAYS_PER_AU: f64 = 0.005_775_518_3;
+
+/// The ratio of the mass of the Earth to that of the Moon
+const EARTH_MOON_MASS_RATIO: f64 = 81.300_57;
+
+/// The number of AU in a light year
+pub(crate) const AU_PER_LIGHT_YEAR: f64 = 63_241.077;
+
+//a Functions
+//fp equatorial_of_ecliptic
+/// Convert a vector in the ecliptic frame of J2000 to the equatorial
+/// frame of J2000 (as used for [Star::vector])
+pub fn equatorial_of_ecliptic(v: &Vec3) -> Vec3 {
+    let (s, c) = OBLIQUITY_J2000.sin_cos();
+    [v[0], c * v[1] - s * v[2], s * v[1] + c * v[2]].into()
+}
+
+//fp ecliptic_of_equatorial
+/// Convert a vector in the equatorial frame of J2000 (as used for
+/// [Star::vector]) to the ecliptic frame of J2000
+pub fn ecliptic_of_equatorial(v: &Vec3) -> Vec3 {
+    let (s, c) = OBLIQUITY_J2000.sin_cos();
+    [v[0], c * v[1] + s * v[2], -s * v[1] + c * v[2]].into()
+}
+
+//fp precess_to_j2000
+/// Convert a vector in the frame of the mean equator and equinox of a
+/// Julian Date to the equatorial frame of J2000 (as used for
+/// [Star::vector])
+///
+/// This uses the IAU 1976 precession angles, as for
+/// [crate::constellations::j2000_of_b1875]; nutation is ignored
+pub fn precess_to_j2000(v: &Vec3, jd: f64) -> Vec3 {
+    use crate::constellations::{precession_angles, rotate_y, rotate_z};
+    let (zeta, z, theta) = precession_angles(crate::time::julian_centuries(jd));
+    let v = rotate_z([v[0], v[1], v[2]], -z);
+    let v = rotate_y(v, -theta);
+    rotate_z(v, -zeta).into()
+}
+
+//fp earth_heliocentric
+/// Get the heliocentric position of the Earth (in AU, in the ecliptic
+/// frame of J2000) at a Julian Date
+pub fn earth_heliocentric(jd: f64) -> Vec3 {
+    let t = crate::time::julian_centuries(jd);
+    PlanetElements::EM_BARY.heliocentric(t)
+        - moon::geocentric(t) * (1.0 / (1.0 + EARTH_MOON_MASS_RATIO))
+}
+
+//fi ra_de_of_vec
+/// Get the right ascension (0 to 2*PI) and declination of a unit
+/// vector
+pub(crate) fn ra_de_of_vec(v: &Vec3) -> (f64, f64) {
+    let ra = v[1].atan2(v[0]).rem_euclid(std::f64::consts::TAU);
+    let de = v[2].clamp(-1., 1.).asin();
+    (ra, de)
+}
+
+//fp positions
+/// Get the positions of the Sun, Moon and planets at a Julian Date
+pub fn positions(jd: f64) -> Vec<BodyPosition> {
+    Body::ALL.iter().map(|b| b.position(jd)).collect()
+}
+
+//fp positions_around
+/// Get the positions of those of the Sun, Moon and planets that are
+/// within an angle (in radians) of a unit vector at a Julian Date
+///
+/// The angular radius of each body is included, so that the Sun or
+/// Moon partly within the angle is returned
+pub fn positions_around(jd: f64, v: &Vec3, angle: f64) -> Vec<BodyPosition> {
+    positions(jd)
+        .into_iter()
+        .filter(|p| p.vector.dot(v) >= (angle + p.angular_radius).min(std::f64::consts::PI).cos())
+        .collect()
+}
+
+//a Body
+//tp Body
+/// A body of the solar system whose position is provided
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Body {
+    Sun,
+    Moon,
+    Mercury,
+    Venus,
+    Mars,
+    Jupiter,
+    Saturn,
+    Uranus,
+    Neptune,
+}
+
+//ip Body
+impl Body {
+    /// All of the bodies
+    pub const ALL: [Self; 9] = [
+        Self::Sun,
+        Self::Moon,
+        Self::Mercury,
+        Self::Venus,
+        Self::Mars,
+        Self::Jupiter,
+        Self::Saturn,
+        Self::Uranus,
+        Self::Neptune,
+    ];
+
+    //ap name
+    /// Get the name of the body
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Sun => "Sun",
+            Self::Moon => "Moon",
+            Self::Mercury => "Mercury",
+            Self::Venus => "Venus",
+            Self::Mars => "Mars",
+            Self::Jupiter => "Jupiter",
+            Self::Saturn => "Saturn",
+            Self::Uranus => "Uranus",
+            Self::Neptune => "Neptune",
+        }
+    }
+
+    //fp of_name
+    /// Get the body given its name (ignoring case)
+    pub fn of_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|b| b.name().eq_ignore_ascii_case(name))
+    }
+
+    //ap radius
+    /// Get the equatorial radius of the body in kilometers
+    pub fn radius(&self) -> f64 {
+        match self {
+            Self::Sun => 695_700.,
+            Self::Moon => 1_737.4,
+            Self::Mercury => 2_440.5,
+            Self::Venus => 6_051.8,
+            Self::Mars => 3_396.2,
+            Self::Jupiter => 71_492.,
+            Self::Saturn => 60_268.,
+            Self::Uranus => 25_559.,
+            Self::Neptune => 24_764.,
+        }
+    }
+
+    //ap color_index
+    /// Get the B-V color index of the body, as for a [Star]
+    pub fn color_index(&self) -> f32 {
+        match self {
+            Self::Sun => 0.65,
+            Self::Moon => 0.92,
+            Self::Mercury => 0.93,
+            Self::Venus => 0.82,
+            Self::Mars => 1.36,
+            Self::Jupiter => 0.83,
+            Self::Saturn => 1.04,
+            Self::Uranus => 0.56,
+            Self::Neptune => 0.41,
+        }
+    }
+
+    //fi elements
+    /// Get the orbital elements of a planet
+    fn elements(&self) -> Option<PlanetElements> {
+        match self {
+            Self::Mercury => Some(PlanetElements::MERCURY),
+            Self::Venus => Some(PlanetElements::VENUS),
+            Self::Mars => Some(PlanetElements::MARS),
+            Self::Jupiter => Some(PlanetElements::JUPITER),
+            Self::Saturn => Some(PlanetElements::SATURN),
+            Self::Uranus => Some(PlanetElements::URANUS),
+            Self::Neptune => Some(PlanetElements::NEPTUNE),
+            _ => None,
+        }
+    }
+
+    //mp heliocentric
+    /// Get the heliocentric position of the body (in AU, in the
+    /// ecliptic frame of J2000) at a Julian Date
+    pub fn heliocentric(&self, jd: f64) -> Vec3 {
+        let t = crate::time::julian_centuries(jd);
+        match self {
+            Self::Sun => [0., 0., 0.].into(),
+            Self::Moon => earth_heliocentric(jd) + moon::geocentric(t),
+            _ => self.elements().unwrap().heliocentric(t),
+        }
+    }
+
+    //mp position
+    /// Get the geocentric position of the body at a Julian Date,
+    /// corrected for light time
+    pub fn position(&self, jd: f64) -> BodyPosition {
+        let earth = earth_heliocentric(jd);
+        let mut helio = self.heliocentric(jd);
+        let mut geo = helio - earth;
+        if *self != Self::Moon {
+            // One iteration suffices for the light time
+            let light_time = geo.length() * LIGHT_DAYS_PER_AU;
+            helio = self.heliocentric(jd - light_time);
+            geo = helio - earth;
+        }
+        let distance = geo.length();
+        let sun_distance = helio.length();
+        let phase_angle = if *self == Self::Sun {
+            0.
+        } else {
+            (helio.dot(&geo) / (sun_distance * distance))
+                .clamp(-1., 1.)
+                .acos()
+        };
+        let vector = equatorial_of_ecliptic(&geo).normalize();
+        let (ra, de) = ra_de_of_vec(&vector);
+        let mag = self.magnitude(&helio, &geo, phase_angle.to_degrees(), jd);
+        BodyPosition {
+            body: *self,
+            jd,
+            ra,
+            de,
+            vector,
+            distance,
+            sun_distance,
+            phase_angle,
+            illuminated: (1.0 + phase_angle.cos()) / 2.0,
+            mag,
+            angular_radius: (self.radius() / (distance * KM_PER_AU)).asin(),
+        }
+    }
+
+    //mi magnitude
+    /// Get the apparent visual magnitude of the body given its
+    /// heliocentric and geocentric positions (ecliptic J2000, in AU)
+    /// and phase angle in degrees
+    ///
+    /// This uses the formulae of the Astronomical Almanac as given by
+    /// Meeus (chapter 41) for the planets, ignoring the difference
+    /// between the Saturnicentric longitudes of the Sun and Earth for
+    /// Saturn's rings; and that of Allen for the Moon
+    fn magnitude(&self, helio: &Vec3, geo: &Vec3, i: f64, jd: f64) -> f32 {
+        let r = helio.length();
+        let delta = geo.length();
+        let d = 5.0 * (r * delta).log10();
+        let mag = match self {
+            Self::Sun => -26.74 + 5.0 * delta.log10(),
+            Self::Moon => 0.21 + d + 0.026 * i + 4.0E-9 * i.powi(4),
+            Self::Mercury => -0.42 + d + 0.0380 * i - 0.000273 * i * i + 0.000002 * i.powi(3),
+            Self::Venus => -4.40 + d + 0.0009 * i + 0.000239 * i * i - 0.00000065 * i.powi(3),
+            Self::Mars => -1.52 + d + 0.016 * i,
+            Self::Jupiter => -9.40 + d + 0.005 * i,
+            Self::Saturn => {
+                // The tilt of the rings to the Earth, using the pole of
+                // the ring plane for the J2000 ecliptic
+                let t = crate::time::julian_centuries(jd);
+                let inclination = (28.0752 - 0.0130 * t).to_radians();
+                let node = (169.5085 + 0.0023 * t).to_radians();
+                let (lambda, beta) = ra_de_of_vec(&geo.normalize());
+                let sin_b = inclination.sin() * beta.cos() * (lambda - node).sin()
+                    - inclination.cos() * beta.sin();
+                -8.88 + d - 2.60 * sin_b.abs() + 1.25 * sin_b * sin_b
+            }
+            Self::Uranus => -7.19 + d,
+            Self::Neptune => -6.87 + d,
+        };
+        mag as f32
+    }
+}
+
+//a BodyPosition
+//tp BodyPosition
+/// The position of a [Body] as seen from the center of the Earth at a
+/// time, from [Body::position]
+#[derive(Debug, Clone)]
+pub struct BodyPosition {
+    /// The body
+    pub body: Body,
+    /// The Julian Date
+    pub jd: f64,
+    /// The right ascension (J2000) in radians
+    pub ra: f64,
+    /// The declination (J2000) in radians
+    pub de: f64,
+    /// The unit vector of the direction of the body, as for
+    /// [Star::vector]
+    pub vector: Vec3,
+    /// The distance from the Earth in AU
+    pub distance: f64,
+    /// The distance from the Sun in AU (zero for the Sun)
+    pub sun_distance: f64,
+    /// The phase angle (Sun - body - Earth) in radians
+    pub phase_angle: f64,
+    /// The illuminated fraction of the disc, from 0 to 1
+    pub illuminated: f64,
+    /// The apparent visual magnitude
+    pub mag: f32,
+    /// The angular radius of the disc in radians
+    pub angular_radius: f64,
+}
+
+//ip BodyPosition
+impl BodyPosition {
+    //mp to_star
+    /// Create a [Star] for the body at its position, with an id, so
+    /// that it may be added to a catalog (for example to match an
+    /// image that includes a bright planet)
+    pub fn to_star(&self, id: usize) -> Star {
+        Star::new(
+            id,
+            self.ra,
+            self.de,
+            (self.distance / AU_PER_LIGHT_YEAR) as f32,
+            self.mag,
+            self.body.color_index(),
+        )
+    }
+}
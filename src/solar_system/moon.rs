@@ -0,0 +1,166 @@
+//a Imports
+use crate::Vec3;
+
+//a Constants
+/// The main periodic terms for the longitude and distance of the
+/// Moon: multiples of D, M, M' and F, then the coefficients of the
+/// sine of the argument for longitude (1E-6 degrees) and of the
+/// cosine for distance (1E-3 km)
+///
+/// These are the largest terms of Meeus, 'Astronomical Algorithms'
+/// table 47.A (from ELP-2000/82)
+const LONGITUDE_DISTANCE: &[([i8; 4], f64, f64)] = &[
+    ([0, 0, 1, 0], 6288774., -20905355.),
+    ([2, 0, -1, 0], 1274027., -3699111.),
+    ([2, 0, 0, 0], 658314., -2955968.),
+    ([0, 0, 2, 0], 213618., -569925.),
+    ([0, 1, 0, 0], -185116., 48888.),
+    ([0, 0, 0, 2], -114332., -3149.),
+    ([2, 0, -2, 0], 58793., 246158.),
+    ([2, -1, -1, 0], 57066., -152138.),
+    ([2, 0, 1, 0], 53322., -170733.),
+    ([2, -1, 0, 0], 45758., -204586.),
+    ([0, 1, -1, 0], -40923., -129620.),
+    ([1, 0, 0, 0], -34720., 108743.),
+    ([0, 1, 1, 0], -30383., 104755.),
+    ([2, 0, 0, -2], 15327., 10321.),
+    ([0, 0, 1, 2], -12528., 0.),
+    ([0, 0, 1, -2], 10980., 79661.),
+    ([4, 0, -1, 0], 10675., -34782.),
+    ([0, 0, 3, 0], 10034., -23210.),
+    ([4, 0, -2, 0], 8548., -21636.),
+    ([2, 1, -1, 0], -7888., 24208.),
+    ([2, 1, 0, 0], -6766., 30824.),
+    ([1, 0, -1, 0], -5163., -8379.),
+    ([1, 1, 0, 0], 4987., -16675.),
+    ([2, -1, 1, 0], 4036., -12831.),
+    ([2, 0, 2, 0], 3994., -10445.),
+    ([4, 0, 0, 0], 3861., -11650.),
+    ([2, 0, -3, 0], 3665., 14403.),
+    ([0, 1, -2, 0], -2689., -7003.),
+    ([2, 0, -1, 2], -2602., 0.),
+    ([2, -1, -2, 0], 2390., 10056.),
+    ([1, 0, 1, 0], -2348., 6322.),
+    ([2, -2, 0, 0], 2236., -9884.),
+    ([0, 1, 2, 0], -2120., 5751.),
+    ([0, 2, 0, 0], -2069., 0.),
+];
+
+/// The main periodic terms for the latitude of the Moon: multiples of
+/// D, M, M' and F, then the coefficient of the sine of the argument
+/// (1E-6 degrees)
+///
+/// These are the largest terms of Meeus table 47.B
+const LATITUDE: &[([i8; 4], f64)] = &[
+    ([0, 0, 0, 1], 5128122.),
+    ([0, 0, 1, 1], 280602.),
+    ([0, 0, 1, -1], 277693.),
+    ([2, 0, 0, -1], 173237.),
+    ([2, 0, -1, 1], 55413.),
+    ([2, 0, -1, -1], 46271.),
+    ([2, 0, 0, 1], 32573.),
+    ([0, 0, 2, 1], 17198.),
+    ([2, 0, 1, -1], 9266.),
+    ([0, 0, 2, -1], 8822.),
+    ([2, -1, 0, -1], 8216.),
+    ([2, 0, -2, -1], 4324.),
+    ([2, 0, 1, 1], 4200.),
+    ([2, 1, 0, -1], -3359.),
+    ([2, -1, -1, 1], 2463.),
+    ([2, -1, 0, 1], 2211.),
+    ([2, -1, -1, -1], 2065.),
+    ([0, 1, -1, -1], -1870.),
+    ([4, 0, -1, -1], 1828.),
+    ([0, 1, 0, 1], -1794.),
+    ([0, 0, 0, 3], -1749.),
+    ([0, 1, -1, 1], -1565.),
+    ([1, 0, 0, 1], -1491.),
+    ([0, 1, 1, 1], -1475.),
+    ([0, 1, 1, -1], -1410.),
+    ([0, 1, 0, -1], -1344.),
+    ([1, 0, 0, -1], -1335.),
+];
+
+/// The number of kilometers in an astronomical unit
+pub(crate) const KM_PER_AU: f64 = 149_597_870.7;
+
+//a Functions
+//fp geocentric
+/// Get the geocentric position of the Moon (in AU, for the ecliptic
+/// and equinox of J2000) at a time in Julian centuries from J2000.0
+///
+/// The truncated series gives the longitude and latitude to better
+/// than an arcminute and the distance to a few tens of kilometers,
+/// for the ecliptic of date; this is moved to the J2000 equinox with
+/// the general precession in longitude
+pub(crate) fn geocentric(t: f64) -> Vec3 {
+    let poly = |c: [f64; 5]| c[0] + t * (c[1] + t * (c[2] + t * (c[3] + t * c[4])));
+    let l = poly([
+        218.3164477,
+        481267.88123421,
+        -0.0015786,
+        1.0 / 538841.0,
+        -1.0 / 65194000.0,
+    ]);
+    let d = poly([
+        297.8501921,
+        445267.1114034,
+        -0.0018819,
+        1.0 / 545868.0,
+        -1.0 / 113065000.0,
+    ]);
+    let m = poly([357.5291092, 35999.0502909, -0.0001536, 1.0 / 24490000.0, 0.]);
+    let mp = poly([
+        134.9633964,
+        477198.8675055,
+        0.0087414,
+        1.0 / 69699.0,
+        -1.0 / 14712000.0,
+    ]);
+    let f = poly([
+        93.2720950,
+        483202.0175233,
+        -0.0036539,
+        -1.0 / 3526000.0,
+        1.0 / 863310000.0,
+    ]);
+    let e = 1.0 - t * (0.002516 + t * 0.0000074);
+    let argument = |k: &[i8; 4]| {
+        let angle = k[0] as f64 * d + k[1] as f64 * m + k[2] as f64 * mp + k[3] as f64 * f;
+        (angle.to_radians(), e.powi(k[1].unsigned_abs() as i32))
+    };
+
+    let a1 = (119.75 + 131.849 * t).to_radians();
+    let a2 = (53.09 + 479264.290 * t).to_radians();
+    let a3 = (313.45 + 481266.484 * t).to_radians();
+    let (l_r, mp_r, f_r) = (l.to_radians(), mp.to_radians(), f.to_radians());
+
+    let mut sum_l = 3958. * a1.sin() + 1962. * (l_r - f_r).sin() + 318. * a2.sin();
+    let mut sum_r = 0.;
+    for (k, cl, cr) in LONGITUDE_DISTANCE {
+        let (angle, scale) = argument(k);
+        sum_l += cl * scale * angle.sin();
+        sum_r += cr * scale * angle.cos();
+    }
+    let mut sum_b = -2235. * l_r.sin()
+        + 382. * a3.sin()
+        + 175. * (a1 - f_r).sin()
+        + 175. * (a1 + f_r).sin()
+        + 127. * (l_r - mp_r).sin()
+        - 115. * (l_r + mp_r).sin();
+    for (k, cb) in LATITUDE {
+        let (angle, scale) = argument(k);
+        sum_b += cb * scale * angle.sin();
+    }
+
+    let precession = (5029.0966 * t + 1.11113 * t * t) / 3600.0;
+    let longitude = (l + sum_l / 1E6 - precession).to_radians();
+    let latitude = (sum_b / 1E6).to_radians();
+    let distance = (385000.56 + sum_r / 1000.0) / KM_PER_AU;
+    [
+        distance * latitude.cos() * longitude.cos(),
+        distance * latitude.cos() * longitude.sin(),
+        distance * latitude.sin(),
+    ]
+    .into()
+}
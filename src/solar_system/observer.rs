@@ -0,0 +1,67 @@
+//a Imports
+use crate::Vec3;
+
+//a Constants
+/// The equatorial radius of the Earth (WGS84) in kilometers
+pub const EARTH_RADIUS: f64 = 6_378.137;
+
+/// The flattening of the Earth (WGS84)
+const EARTH_FLATTENING: f64 = 1.0 / 298.257_223_563;
+
+//a Observer
+//tp Observer
+/// A site on the surface of the Earth from which positions may be
+/// observed
+///
+/// The latitude is geodetic (WGS84), and the longitude is positive to
+/// the east of Greenwich; both are in radians. The height is above the
+/// ellipsoid in meters.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Observer {
+    /// The geodetic latitude in radians
+    pub latitude: f64,
+    /// The longitude in radians, positive to the east
+    pub longitude: f64,
+    /// The height above the ellipsoid in meters
+    pub height: f64,
+}
+
+//ip Observer
+impl Observer {
+    //cp new
+    /// Create a new observer given the latitude and longitude (east
+    /// positive) in radians and the height in meters
+    pub fn new(latitude: f64, longitude: f64, height: f64) -> Self {
+        Self {
+            latitude,
+            longitude,
+            height,
+        }
+    }
+
+    //ap local_sidereal_time
+    /// Get the local mean sidereal time (in radians, 0 to 2*PI) at the
+    /// site at a Julian Date
+    pub fn local_sidereal_time(&self, jd: f64) -> f64 {
+        (crate::time::gmst(jd) + self.longitude).rem_euclid(std::f64::consts::TAU)
+    }
+
+    //mp geocentric
+    /// Get the position of the site relative to the center of the
+    /// Earth (in kilometers, in the equatorial frame) at a Julian Date
+    ///
+    /// The frame is that of the equator and equinox of date, which
+    /// is used without precession as that of J2000; the error this
+    /// introduces in the direction of a body is only a small part of
+    /// its parallax
+    pub fn geocentric(&self, jd: f64) -> Vec3 {
+        let (s, c) = self.latitude.sin_cos();
+        let b_a = 1.0 - EARTH_FLATTENING;
+        let n = EARTH_RADIUS / (c * c + b_a * b_a * s * s).sqrt();
+        let h = self.height / 1000.0;
+        let rho_xy = (n + h) * c;
+        let z = (n * b_a * b_a + h) * s;
+        let (st, ct) = self.local_sidereal_time(jd).sin_cos();
+        [rho_xy * ct, rho_xy * st, z].into()
+    }
+}
@@ -0,0 +1,133 @@
+//a Imports
+use std::collections::HashMap;
+
+use geo_nd::Vector;
+
+use crate::{Catalog, CatalogIndex, Vec3};
+
+//a SpatialIndex
+//tt SpatialIndex
+/// A division of the unit sphere into cells, used to speed up
+/// geometric searching of a catalog
+///
+/// This is implemented by [crate::SubcubeIndex] (which is what a
+/// [Catalog] uses internally) and [crate::HealpixIndex].
+///
+/// A cell is a Copy value that identifies one part of the sphere;
+/// each cell also has a unique usize id, which is less than
+/// `num_cells()`.
+pub trait SpatialIndex {
+    /// The type that identifies a cell of the index
+    type Cell: Copy;
+
+    /// The number of cells in the index; all cell ids are less than this
+    fn num_cells(&self) -> usize;
+
+    /// Get the cell that contains a vector
+    fn cell_of_vector(&self, v: &Vec3) -> Self::Cell;
+
+    /// Get the unique id of a cell
+    fn cell_id(&self, cell: Self::Cell) -> usize;
+
+    /// Get a unit vector for the center of a cell
+    fn cell_center(&self, cell: Self::Cell) -> Vec3;
+
+    /// Get the maximum angle, in radians, between the center of any
+    /// cell and any point on the unit sphere within it
+    fn cell_max_angle(&self) -> f64;
+
+    /// Get the cells that neighbor a cell
+    fn cell_neighbors(&self, cell: Self::Cell) -> Vec<Self::Cell>;
+
+    /// Get all the cells that might contain points within an angle
+    /// of a unit vector
+    fn cells_in_cone(&self, v: &Vec3, angle: f64) -> Vec<Self::Cell>;
+
+    /// Get all the cells that might overlap a convex spherical
+    /// polygon, given by its vertices (as unit vectors) in order
+    fn cells_in_polygon(&self, vertices: &[Vec3]) -> Vec<Self::Cell>;
+}
+
+//a SpatialCells
+//tp SpatialCells
+/// The stars of a [Catalog] placed into the cells of a [SpatialIndex]
+///
+/// Only occupied cells are stored; the stars within each cell are
+/// sorted by magnitude, brightest first.
+///
+/// The [SpatialCells] refers to stars by [CatalogIndex], and so it
+/// becomes invalid if the catalog is sorted again or has stars added
+/// or removed.
+pub struct SpatialCells<S: SpatialIndex> {
+    /// The spatial index used to divide up the sphere
+    index: S,
+    /// Star indices within each occupied cell, keyed by cell id
+    cells: HashMap<usize, Vec<CatalogIndex>>,
+}
+
+//ip SpatialCells
+impl<S: SpatialIndex> SpatialCells<S> {
+    //cp new
+    /// Create a new [SpatialCells] from the stars of a catalog
+    pub fn new(index: S, catalog: &Catalog) -> Self {
+        let mut cells: HashMap<usize, Vec<CatalogIndex>> = HashMap::new();
+        for (i, s) in catalog.iter_stars().enumerate() {
+            let cell = index.cell_id(index.cell_of_vector(&s.vector));
+            cells.entry(cell).or_default().push(i.into());
+        }
+        for stars in cells.values_mut() {
+            stars.sort_by(|a, b| catalog[*a].mag.total_cmp(&catalog[*b].mag));
+        }
+        Self { index, cells }
+    }
+
+    //ap index
+    /// Get the [SpatialIndex] used
+    pub fn index(&self) -> &S {
+        &self.index
+    }
+
+    //ap num_occupied
+    /// Get the number of cells that contain stars
+    pub fn num_occupied(&self) -> usize {
+        self.cells.len()
+    }
+
+    //mp stars_in
+    /// Get the stars within a cell, brightest first
+    pub fn stars_in(&self, cell: S::Cell) -> &[CatalogIndex] {
+        self.cells
+            .get(&self.index.cell_id(cell))
+            .map(|v| v.as_slice())
+            .unwrap_or(&[])
+    }
+
+    //mp find_stars_around
+    /// Find stars of the catalog within a certain angle around a
+    /// vector, applying the catalog's filter
+    ///
+    /// The catalog must be the one that the [SpatialCells] was
+    /// created from
+    pub fn find_stars_around(
+        &self,
+        catalog: &Catalog,
+        vector: &Vec3,
+        max_angle: f64,
+    ) -> Vec<CatalogIndex> {
+        let max_cos = max_angle.cos();
+        let mut result = vec![];
+        for cell in self.index.cells_in_cone(vector, max_angle) {
+            for index in self.stars_in(cell) {
+                let star = &catalog[*index];
+                if star.vector.dot(vector) < max_cos {
+                    continue;
+                }
+                if !catalog.filter().call(star, result.len()) {
+                    continue;
+                }
+                result.push(*index);
+            }
+        }
+        result
+    }
+}
@@ -2,7 +2,7 @@
 use geo_nd::Vector;
 use serde::{Deserialize, Serialize};
 
-use crate::{Subcube, Vec3};
+use crate::{Subcube, SubcubeIndex, Vec3};
 
 //a Star and StarSerialized
 //tp StarSerialized
@@ -172,7 +172,7 @@ impl Star {
     /// Create a new [Star] given its details
     pub fn new(id: usize, ra: f64, de: f64, ly: f32, mag: f32, bv: f32) -> Self {
         let vector = Self::vec_of_ra_de(ra, de);
-        let subcube = Subcube::of_vector(&vector);
+        let subcube = SubcubeIndex::default().of_vector(&vector);
         Self {
             id,
             ra,
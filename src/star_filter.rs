@@ -34,6 +34,9 @@ impl<F: StarFilterFn + 'static> From<F> for StarFilter {
 impl StarFilter {
     //mp call
     /// Invoke the filter
+    ///
+    /// *n* is the number of stars that have been accepted so far by
+    /// the search (or index construction) using the filter
     pub fn call(&self, s: &Star, n: usize) -> bool {
         self.0(s, n)
     }
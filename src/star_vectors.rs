@@ -0,0 +1,102 @@
+//a Imports
+use std::ops::Range;
+
+use crate::{Star, Vec3};
+
+//a StarVectors
+//tp StarVectors
+/// A structure-of-arrays copy of the star vectors and magnitudes of a
+/// catalog, in the order that the catalog groups its stars by subcube
+///
+/// Geometric scans only need the vector (and sometimes the
+/// magnitude) of each star; keeping these in contiguous arrays means
+/// a scan of a subcube touches only the data it needs (rather than
+/// the whole of each [Star]), and the dot products can be vectorized
+/// by the compiler.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct StarVectors {
+    /// X components of the star vectors
+    x: Vec<f64>,
+    /// Y components of the star vectors
+    y: Vec<f64>,
+    /// Z components of the star vectors
+    z: Vec<f64>,
+    /// Magnitudes of the stars
+    mag: Vec<f32>,
+}
+
+//ip StarVectors
+impl StarVectors {
+    //mp clear
+    /// Clear the arrays
+    pub(crate) fn clear(&mut self) {
+        self.x.clear();
+        self.y.clear();
+        self.z.clear();
+        self.mag.clear();
+    }
+
+    //mp push
+    /// Add a star to the end of the arrays
+    pub(crate) fn push(&mut self, star: &Star) {
+        self.x.push(star.vector[0]);
+        self.y.push(star.vector[1]);
+        self.z.push(star.vector[2]);
+        self.mag.push(star.mag);
+    }
+
+    //ap vector
+    /// Get the vector of the star at a position as an array
+    #[inline]
+    pub(crate) fn vector(&self, n: usize) -> [f64; 3] {
+        [self.x[n], self.y[n], self.z[n]]
+    }
+
+    //ap mag
+    /// Get the magnitude of the star at a position
+    #[inline]
+    pub(crate) fn mag(&self, n: usize) -> f32 {
+        self.mag[n]
+    }
+
+    //mp dot
+    /// Get the dot product of the star at a position with a vector
+    #[inline]
+    pub(crate) fn dot(&self, n: usize, v: &[f64; 3]) -> f64 {
+        self.x[n] * v[0] + self.y[n] * v[1] + self.z[n] * v[2]
+    }
+
+    //mp for_each_within
+    /// Invoke a function with the position and cosine of the angle to
+    /// a vector for every star within a range of positions whose
+    /// cosine is in the range min_cos to max_cos (inclusive)
+    #[inline]
+    pub(crate) fn for_each_within<F>(
+        &self,
+        range: Range<usize>,
+        v: &[f64; 3],
+        min_cos: f64,
+        max_cos: f64,
+        mut f: F,
+    ) where
+        F: FnMut(usize, f64),
+    {
+        let start = range.start;
+        let x = &self.x[range.clone()];
+        let y = &self.y[range.clone()];
+        let z = &self.z[range];
+        for (i, ((x, y), z)) in x.iter().zip(y.iter()).zip(z.iter()).enumerate() {
+            let c = x * v[0] + y * v[1] + z * v[2];
+            if c >= min_cos && c <= max_cos {
+                f(start + i, c);
+            }
+        }
+    }
+}
+
+//fp array_of_vec3
+/// Convert a [Vec3] to an array for use with [StarVectors]
+#[inline]
+pub(crate) fn array_of_vec3(v: &Vec3) -> [f64; 3] {
+    [v[0], v[1], v[2]]
+}
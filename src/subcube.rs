@@ -1,6 +1,7 @@
 use geo_nd::Vector;
 
-use crate::Vec3;
+use crate::healpix::polygon_edge_normals;
+use crate::{SpatialIndex, Vec3};
 
 //tp Subcube
 /// A representation of a portion of the unit cube, to improve
@@ -33,6 +34,10 @@ use crate::Vec3;
 ///
 /// # Choice of ELE_PER_SIDE
 ///
+/// The number of subdivisions is carried by each [Subcube] (it may be
+/// up to 255), and a [crate::Catalog] chooses it when its data is
+/// derived; [Subcube::ELE_PER_SIDE] is the default.
+///
 /// If ELE_PER_SIDE were 2 then the maximum angle of difference
 /// between any two points in a subcube would be 90 degrees
 ///
@@ -93,13 +98,16 @@ pub struct Subcube(u32);
 //ip Subcube
 impl Subcube {
     //cp ELE_PER_SIDE
-    /// The number of subdivisions per dimension of the (-1,1) cube
-    /// that produces the subcubes
+    /// The default number of subdivisions per dimension of the (-1,1)
+    /// cube that produces the subcubes
     pub const ELE_PER_SIDE: usize = 32;
 
-    const ELE_PER_SIDE2: usize = Self::ELE_PER_SIDE * Self::ELE_PER_SIDE;
+    //cp MAX_ELE_PER_SIDE
+    /// The maximum number of subdivisions per dimension supported
+    pub const MAX_ELE_PER_SIDE: usize = 255;
 
-    /// The number of subcubes in the (-1,1) cube
+    /// The number of subcubes in the (-1,1) cube for the default
+    /// ELE_PER_SIDE
     ///
     /// A Catalog may have a Vec with this number of entries; each
     /// element would be a Vec, but only those that contain stars (and
@@ -110,54 +118,82 @@ impl Subcube {
     /// of populated subcubes approaches 2*ELE_PER_SIDE^2
     pub const NUM_SUBCUBES: usize = Self::ELE_PER_SIDE * Self::ELE_PER_SIDE * Self::ELE_PER_SIDE;
 
-    /// The size of each side of a Subcube
+    /// The size of each side of a Subcube for the default ELE_PER_SIDE
     pub const SUBCUBE_SIZE: f64 = 2.0 / Self::ELE_PER_SIDE as f64;
 
-    /// The raduis of the circumsphere of a Subcube - i.e. all stars
-    /// within the subcube must be closer tthan this to the centre of
-    /// the Subcube (although some such stars may be in a neighboring
-    /// subcube)
+    /// The raduis of the circumsphere of a Subcube for the default
+    /// ELE_PER_SIDE - i.e. all stars within the subcube must be
+    /// closer tthan this to the centre of the Subcube (although some
+    /// such stars may be in a neighboring subcube)
     pub const SUBCUBE_RADIUS: f64 = 1.7321 * (Self::SUBCUBE_SIZE / 2.0);
 
+    /// Number of bits of the value used for the index of the subcube;
+    /// the remaining bits hold ELE_PER_SIDE
+    const INDEX_BITS: u32 = 24;
+
+    //fi new
+    /// Create a subcube from its index and the number of elements per side
+    fn new(index: usize, ele_per_side: usize) -> Self {
+        Subcube((index as u32) | ((ele_per_side as u32) << Self::INDEX_BITS))
+    }
+
     //fi index_of_coord
     /// Get the subcube index of a coordinate
     ///
     /// The coordinate must be in the range -1. to 1.
-    fn index_of_coord(c: f64) -> usize {
+    fn index_of_coord(c: f64, ele_per_side: usize) -> usize {
         let c = c.min(1.);
-        ((c + 1.0).abs() * (Self::ELE_PER_SIDE as f64) / 2.0 * 0.999_999).floor() as usize
+        ((c + 1.0).abs() * (ele_per_side as f64) / 2.0 * 0.999_999).floor() as usize
     }
 
     //fi coord_of_index
     /// Get the coordinate of the centre of an index
-    fn coord_of_index(i: usize) -> f64 {
-        (2 * i + 1) as f64 / Self::ELE_PER_SIDE as f64 - 1.0
+    fn coord_of_index(i: usize, ele_per_side: usize) -> f64 {
+        (2 * i + 1) as f64 / ele_per_side as f64 - 1.0
     }
 
     //cp of_vector
     /// Get the subcube of a unit vector (which is thus a point on the
-    /// unit sphere)
+    /// unit sphere), for the default ELE_PER_SIDE
+    ///
+    /// A [crate::Catalog] may use a different number of subdivisions,
+    /// so use [crate::Catalog::subcube_of_vector] for its subcubes
+    #[deprecated(
+        since = "0.0.8",
+        note = "use Catalog::subcube_of_vector, or SubcubeIndex::of_vector"
+    )]
     pub fn of_vector(v: &Vec3) -> Self {
-        let xe = Self::index_of_coord(v[0]);
-        let ye = Self::index_of_coord(v[1]);
-        let ze = Self::index_of_coord(v[2]);
-        (xe, ye, ze).into()
+        SubcubeIndex::default().of_vector(v)
     }
 
     //ap as_usize
     /// Get a value for the subcube, different for each within the (-1,1) cube
     pub fn as_usize(&self) -> usize {
-        self.0 as usize
+        (self.0 & ((1 << Self::INDEX_BITS) - 1)) as usize
+    }
+
+    //ap ele_per_side
+    /// Get the number of subdivisions per dimension of the (-1,1)
+    /// cube that this subcube is one of
+    pub fn ele_per_side(&self) -> usize {
+        (self.0 >> Self::INDEX_BITS) as usize
+    }
+
+    //ap subcube_index
+    /// Get the [SubcubeIndex] that this subcube is part of
+    pub fn subcube_index(&self) -> SubcubeIndex {
+        SubcubeIndex::new(self.ele_per_side())
     }
 
     //ap center
     /// Get the vector of the centre of the Subcube (this is *NOT* a unit vector)!
     pub fn center(&self) -> Vec3 {
         let (x, y, z): (usize, usize, usize) = self.into();
+        let e = self.ele_per_side();
         [
-            Self::coord_of_index(x),
-            Self::coord_of_index(y),
-            Self::coord_of_index(z),
+            Self::coord_of_index(x, e),
+            Self::coord_of_index(y, e),
+            Self::coord_of_index(z, e),
         ]
         .into()
     }
@@ -170,7 +206,8 @@ impl Subcube {
     /// sphere for any part of the subcube to overlap the unit sphere
     pub fn may_be_on_sphere(&self) -> bool {
         let r = self.center().length();
-        (1.0 - Self::SUBCUBE_RADIUS..=1.0 + Self::SUBCUBE_RADIUS).contains(&r)
+        let radius = self.subcube_index().subcube_radius();
+        (1.0 - radius..=1.0 + radius).contains(&r)
     }
 
     //mp cos_angle_on_sphere
@@ -188,7 +225,8 @@ impl Subcube {
     pub fn cos_angle_on_sphere(&self, v: &Vec3) -> Option<f64> {
         let c = self.center();
         let r = c.length();
-        if (1.0 - Self::SUBCUBE_RADIUS..=1.0 + Self::SUBCUBE_RADIUS).contains(&r) {
+        let radius = self.subcube_index().subcube_radius();
+        if (1.0 - radius..=1.0 + radius).contains(&r) {
             Some(v.dot(&c) / r)
         } else {
             None
@@ -196,14 +234,17 @@ impl Subcube {
     }
 
     //mp iter_all
-    /// Get an iterator over all the Subcubes in the (-1, 1) cube
+    /// Get an iterator over all the Subcubes in the (-1, 1) cube, for
+    /// the default ELE_PER_SIDE
+    ///
+    /// A [crate::Catalog] may use a different number of subdivisions,
+    /// so use [crate::Catalog::iter_subcubes] for its subcubes
+    #[deprecated(
+        since = "0.0.8",
+        note = "use Catalog::iter_subcubes, or SubcubeIndex::iter_all"
+    )]
     pub fn iter_all() -> SubcubeRangeIter {
-        SubcubeRangeIter {
-            xyz: (0, 0, 0),
-            xrange: 0..Self::ELE_PER_SIDE,
-            yrange: 0..Self::ELE_PER_SIDE,
-            zrange: 0..Self::ELE_PER_SIDE,
-        }
+        SubcubeIndex::default().iter_all()
     }
 
     //mp iter_range
@@ -217,13 +258,15 @@ impl Subcube {
     /// and all its immediate neighbors
     pub fn iter_range(&self, dxyz: usize) -> SubcubeRangeIter {
         let xyz: (usize, usize, usize) = (*self).into();
-        let xmin = if xyz.0 < dxyz { 0 } else { xyz.0 - dxyz };
-        let xmax = (xyz.0 + dxyz + 1).min(Self::ELE_PER_SIDE);
-        let ymin = if xyz.1 < dxyz { 0 } else { xyz.1 - dxyz };
-        let ymax = (xyz.1 + dxyz + 1).min(Self::ELE_PER_SIDE);
-        let zmin = if xyz.2 < dxyz { 0 } else { xyz.2 - dxyz };
-        let zmax = (xyz.2 + dxyz + 1).min(Self::ELE_PER_SIDE);
+        let e = self.ele_per_side();
+        let xmin = xyz.0.saturating_sub(dxyz);
+        let xmax = (xyz.0 + dxyz + 1).min(e);
+        let ymin = xyz.1.saturating_sub(dxyz);
+        let ymax = (xyz.1 + dxyz + 1).min(e);
+        let zmin = xyz.2.saturating_sub(dxyz);
+        let zmax = (xyz.2 + dxyz + 1).min(e);
         SubcubeRangeIter {
+            ele_per_side: e,
             xyz: (xmin, ymin, zmin),
             xrange: xmin..xmax,
             yrange: ymin..ymax,
@@ -242,19 +285,24 @@ impl From<Subcube> for (usize, usize, usize) {
 //ip From<&Subcube> for (usize, usize, usize)
 impl From<&Subcube> for (usize, usize, usize) {
     fn from(s: &Subcube) -> (usize, usize, usize) {
+        let e = s.ele_per_side();
         let s = s.as_usize();
-        let x = s % Subcube::ELE_PER_SIDE;
-        let y = (s / Subcube::ELE_PER_SIDE) % Subcube::ELE_PER_SIDE;
-        let z = s / Subcube::ELE_PER_SIDE2;
+        let x = s % e;
+        let y = (s / e) % e;
+        let z = s / (e * e);
         (x, y, z)
     }
 }
 
 //ip From<(usize, usize, usize)> for Subcube
+/// Create a Subcube for the default ELE_PER_SIDE
+///
+/// A [crate::Catalog] may use a different number of subdivisions, so
+/// use [SubcubeIndex::of_xyz] (with [crate::Catalog::subcube_index])
+/// for its subcubes
 impl From<(usize, usize, usize)> for Subcube {
-    fn from((x, y, z): (usize, usize, usize)) -> Subcube {
-        let s = x + y * Self::ELE_PER_SIDE + z * Self::ELE_PER_SIDE2;
-        Subcube(s as u32)
+    fn from(xyz: (usize, usize, usize)) -> Subcube {
+        SubcubeIndex::default().of_xyz(xyz)
     }
 }
 
@@ -262,18 +310,227 @@ impl From<(usize, usize, usize)> for Subcube {
 impl std::ops::Add<isize> for Subcube {
     type Output = Subcube;
     fn add(self, delta: isize) -> Subcube {
-        let s = self.0 as isize + delta;
+        let s = self.as_usize() as isize + delta;
         assert!(
             s >= 0,
             "Delta of Subcube used to take subcube out of bounds"
         );
-        Subcube(s as u32)
+        Subcube::new(s as usize, self.ele_per_side())
+    }
+}
+
+//a SubcubeIndex
+//tp SubcubeIndex
+/// The division of the unit sphere into [Subcube]s, as a [SpatialIndex]
+///
+/// This is the spatial index that a [crate::Catalog] uses
+/// internally, with the number of subdivisions per dimension of the
+/// (-1, 1) cube chosen for the catalog
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubcubeIndex {
+    /// Number of subdivisions per dimension of the (-1, 1) cube
+    ele_per_side: usize,
+}
+
+//ip Default for SubcubeIndex
+impl std::default::Default for SubcubeIndex {
+    fn default() -> Self {
+        Self::new(Subcube::ELE_PER_SIDE)
+    }
+}
+
+//ip SubcubeIndex
+impl SubcubeIndex {
+    //cp new
+    /// Create a new [SubcubeIndex] with a number of subdivisions per
+    /// dimension of the (-1, 1) cube
+    ///
+    /// Panics if this is zero or more than [Subcube::MAX_ELE_PER_SIDE]
+    pub fn new(ele_per_side: usize) -> Self {
+        assert!(
+            (1..=Subcube::MAX_ELE_PER_SIDE).contains(&ele_per_side),
+            "Subcube elements per side of {ele_per_side} is not supported"
+        );
+        Self { ele_per_side }
+    }
+
+    //cp for_num_stars
+    /// Create a new [SubcubeIndex] suitable for a catalog with a
+    /// number of stars
+    ///
+    /// This picks the smallest power of two elements per side (from 4
+    /// to 64) for which the subcubes on the unit sphere hold, on
+    /// average, no more than TARGET_STARS_PER_SUBCUBE stars; there
+    /// are about 3.7*ELE_PER_SIDE^2 subcubes on the sphere.
+    ///
+    /// Hence the full Hipparcos catalog (116,812 stars) uses 32, a
+    /// catalog of stars of magnitude 7.0 or brighter uses 16, and a
+    /// naked-eye catalog uses 4 or 8.
+    pub fn for_num_stars(num_stars: usize) -> Self {
+        const TARGET_STARS_PER_SUBCUBE: f64 = 32.0;
+        let mut ele_per_side = 4;
+        while ele_per_side < 64 {
+            let e = ele_per_side as f64;
+            if (num_stars as f64) / (3.7 * e * e) <= TARGET_STARS_PER_SUBCUBE {
+                break;
+            }
+            ele_per_side *= 2;
+        }
+        Self::new(ele_per_side)
+    }
+
+    //ap ele_per_side
+    /// Get the number of subdivisions per dimension of the (-1, 1) cube
+    pub fn ele_per_side(&self) -> usize {
+        self.ele_per_side
+    }
+
+    //ap num_subcubes
+    /// Get the number of subcubes in the (-1, 1) cube
+    pub fn num_subcubes(&self) -> usize {
+        self.ele_per_side * self.ele_per_side * self.ele_per_side
+    }
+
+    //ap subcube_size
+    /// Get the size of each side of a Subcube
+    pub fn subcube_size(&self) -> f64 {
+        2.0 / self.ele_per_side as f64
+    }
+
+    //ap subcube_radius
+    /// Get the raduis of the circumsphere of a Subcube
+    pub fn subcube_radius(&self) -> f64 {
+        1.7321 * (self.subcube_size() / 2.0)
+    }
+
+    //ap subcube_max_angle
+    /// Get an upper bound on the angle between the directions of any
+    /// two points on the unit sphere that are within the same Subcube
+    ///
+    /// A subcube that touches the unit sphere has its centre at least
+    /// 1-R from the origin (for a circumsphere radius of R), so the
+    /// angle between the direction of its centre and any point within
+    /// it is at most asin(R/(1-R)); this is double that. It is hence
+    /// also a bound on how much the angle between two stars may
+    /// differ from the angle between the centres of their subcubes.
+    ///
+    /// This is capped at 180 degrees, which only applies to a very
+    /// small number of elements per side
+    pub fn subcube_max_angle(&self) -> f64 {
+        let r = self.subcube_radius();
+        if r >= 0.5 {
+            std::f64::consts::PI
+        } else {
+            2.0 * (r / (1.0 - r)).asin()
+        }
+    }
+
+    //mp of_xyz
+    /// Get the subcube at an X, Y, Z position within the (-1, 1) cube
+    pub fn of_xyz(&self, (x, y, z): (usize, usize, usize)) -> Subcube {
+        let e = self.ele_per_side;
+        Subcube::new(x + y * e + z * e * e, e)
+    }
+
+    //mp subcube_of_usize
+    /// Get the subcube from its usize value (see [Subcube::as_usize])
+    pub fn subcube_of_usize(&self, s: usize) -> Subcube {
+        Subcube::new(s, self.ele_per_side)
+    }
+
+    //mp of_vector
+    /// Get the subcube of a unit vector (which is thus a point on the
+    /// unit sphere)
+    pub fn of_vector(&self, v: &Vec3) -> Subcube {
+        let e = self.ele_per_side;
+        let xe = Subcube::index_of_coord(v[0], e);
+        let ye = Subcube::index_of_coord(v[1], e);
+        let ze = Subcube::index_of_coord(v[2], e);
+        self.of_xyz((xe, ye, ze))
+    }
+
+    //mp iter_all
+    /// Get an iterator over all the Subcubes in the (-1, 1) cube
+    pub fn iter_all(&self) -> SubcubeRangeIter {
+        SubcubeRangeIter {
+            ele_per_side: self.ele_per_side,
+            xyz: (0, 0, 0),
+            xrange: 0..self.ele_per_side,
+            yrange: 0..self.ele_per_side,
+            zrange: 0..self.ele_per_side,
+        }
+    }
+
+    //mp range_for_angle
+    /// Get the range (in subcubes in each dimension) that must be
+    /// searched around a subcube to find all the points on the unit
+    /// sphere within an angle of any point in the subcube
+    ///
+    /// Two unit vectors separated by an angle *a* differ in each
+    /// coordinate by at most the chord length 2.sin(a/2)
+    pub fn range_for_angle(&self, angle: f64) -> usize {
+        let chord = 2.0 * (angle.clamp(0., std::f64::consts::PI) / 2.0).sin();
+        (chord / self.subcube_size()).ceil() as usize + 1
+    }
+}
+
+//ip SpatialIndex for SubcubeIndex
+impl SpatialIndex for SubcubeIndex {
+    type Cell = Subcube;
+    fn num_cells(&self) -> usize {
+        self.num_subcubes()
+    }
+    fn cell_of_vector(&self, v: &Vec3) -> Subcube {
+        self.of_vector(v)
+    }
+    fn cell_id(&self, cell: Subcube) -> usize {
+        cell.as_usize()
+    }
+    fn cell_center(&self, cell: Subcube) -> Vec3 {
+        cell.center().normalize()
+    }
+    fn cell_max_angle(&self) -> f64 {
+        self.subcube_max_angle()
+    }
+    fn cell_neighbors(&self, cell: Subcube) -> Vec<Subcube> {
+        cell.iter_range(1)
+            .filter(|s| s.as_usize() != cell.as_usize())
+            .collect()
+    }
+    fn cells_in_cone(&self, v: &Vec3, angle: f64) -> Vec<Subcube> {
+        let max_subcube_cos = (angle + self.subcube_max_angle())
+            .min(std::f64::consts::PI)
+            .cos();
+        self.of_vector(v)
+            .iter_range(self.range_for_angle(angle))
+            .filter(|s| {
+                s.cos_angle_on_sphere(v)
+                    .is_some_and(|c| c >= max_subcube_cos)
+            })
+            .collect()
+    }
+    fn cells_in_polygon(&self, vertices: &[Vec3]) -> Vec<Subcube> {
+        let Some(normals) = polygon_edge_normals(vertices) else {
+            return vec![];
+        };
+        let s = -self
+            .subcube_max_angle()
+            .min(std::f64::consts::FRAC_PI_2)
+            .sin();
+        self.iter_all()
+            .filter(|sub| sub.may_be_on_sphere())
+            .filter(|sub| {
+                let c = sub.center().normalize();
+                normals.iter().all(|n| c.dot(n) >= s)
+            })
+            .collect()
     }
 }
 
 //tp SubcubeRangeIter
 /// Iterator over a range of Subcubes
 pub struct SubcubeRangeIter {
+    ele_per_side: usize,
     xyz: (usize, usize, usize),
     xrange: std::ops::Range<usize>,
     yrange: std::ops::Range<usize>,
@@ -294,7 +551,7 @@ impl std::iter::Iterator for SubcubeRangeIter {
         if !self.zrange.contains(&self.xyz.2) {
             return None;
         }
-        let subcube = self.xyz.into();
+        let subcube = SubcubeIndex::new(self.ele_per_side).of_xyz(self.xyz);
         self.xyz.0 += 1;
         Some(subcube)
     }
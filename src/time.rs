@@ -0,0 +1,162 @@
+//! Times as Julian Dates, for the positions of solar system bodies
+//!
+//! Times are held as f64 Julian Dates (days since noon UT on 1 January
+//! 4713 BC of the Julian calendar). The difference between UT and
+//! Terrestrial Time (just over a minute at present) is ignored, as the
+//! positions derived from these times are of low precision.
+//!
+//! Dates are in the Gregorian calendar from 15 October 1582, and in
+//! the Julian calendar before that.
+
+//a Constants
+/// The Julian Date of the J2000.0 epoch (noon on 1 January 2000)
+pub const J2000: f64 = 2451545.0;
+
+/// The Julian Date of the Unix epoch (midnight on 1 January 1970)
+pub const UNIX_EPOCH: f64 = 2440587.5;
+
+/// The number of days in a Julian century
+pub const DAYS_PER_CENTURY: f64 = 36525.0;
+
+//a Functions
+//fp julian_date
+/// Get the Julian Date of a calendar date, where the day may include
+/// a fraction of a day (so 1.5 is noon on the first of the month)
+pub fn julian_date(year: i32, month: u32, day: f64) -> f64 {
+    let (y, m) = if month <= 2 {
+        (year - 1, month as i32 + 12)
+    } else {
+        (year, month as i32)
+    };
+    let gregorian = (year, month) > (1582, 10) || ((year, month) == (1582, 10) && day >= 15.0);
+    let b = if gregorian {
+        let a = y.div_euclid(100);
+        2 - a + a.div_euclid(4)
+    } else {
+        0
+    };
+    (365.25 * (y + 4716) as f64).floor() + (30.6001 * (m + 1) as f64).floor() + day + b as f64
+        - 1524.5
+}
+
+//fp calendar_date
+/// Get the calendar date (year, month and day with a fraction) of a
+/// Julian Date
+pub fn calendar_date(jd: f64) -> (i32, u32, f64) {
+    let jd = jd + 0.5;
+    let z = jd.floor();
+    let f = jd - z;
+    let a = if z < 2299161. {
+        z
+    } else {
+        let alpha = ((z - 1867216.25) / 36524.25).floor();
+        z + 1. + alpha - (alpha / 4.).floor()
+    };
+    let b = a + 1524.;
+    let c = ((b - 122.1) / 365.25).floor();
+    let d = (365.25 * c).floor();
+    let e = ((b - d) / 30.6001).floor();
+    let day = b - d - (30.6001 * e).floor() + f;
+    let month = if e < 14. { e - 1. } else { e - 13. };
+    let year = if month > 2. { c - 4716. } else { c - 4715. };
+    (year as i32, month as u32, day)
+}
+
+//fp format_date
+/// Format a Julian Date as 'YYYY-MM-DDTHH:MM:SS' (UT), to the nearest
+/// second, as accepted by [parse_date]
+pub fn format_date(jd: f64) -> String {
+    let seconds = ((jd + 0.5) * 86400.0).round();
+    let of_day = seconds.rem_euclid(86400.0) as u32;
+    let midnight = (seconds - of_day as f64) / 86400.0 - 0.5;
+    let (year, month, day) = calendar_date(midnight + 0.5 / 86400.0);
+    format!(
+        "{year:04}-{month:02}-{:02}T{:02}:{:02}:{:02}",
+        day.floor() as u32,
+        of_day / 3600,
+        (of_day / 60) % 60,
+        of_day % 60
+    )
+}
+
+//fp julian_date_of_unix
+/// Get the Julian Date of a Unix time (seconds since 1970-01-01
+/// 00:00:00 UTC)
+pub fn julian_date_of_unix(seconds: f64) -> f64 {
+    UNIX_EPOCH + seconds / 86400.0
+}
+
+//fp julian_date_now
+/// Get the Julian Date of the current time
+pub fn julian_date_now() -> f64 {
+    let seconds = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.);
+    julian_date_of_unix(seconds)
+}
+
+//fp julian_centuries
+/// Get the number of Julian centuries from J2000.0 to a Julian Date
+pub fn julian_centuries(jd: f64) -> f64 {
+    (jd - J2000) / DAYS_PER_CENTURY
+}
+
+//fp gmst
+/// Get the Greenwich mean sidereal time (in radians, 0 to 2*PI) at a
+/// Julian Date (UT)
+///
+/// This is the IAU 1982 expression; it gives the rotation of the
+/// Earth relative to the mean equinox of date, which is used here for
+/// the J2000 equinox without precession
+pub fn gmst(jd: f64) -> f64 {
+    let t = julian_centuries(jd);
+    let degrees = 280.460_618_37
+        + 360.985_647_366_29 * (jd - J2000)
+        + t * t * (0.000_387_933 - t / 38_710_000.0);
+    degrees.to_radians().rem_euclid(std::f64::consts::TAU)
+}
+
+//fp parse_date
+/// Parse a date and time (UT) to a Julian Date
+///
+/// This accepts 'YYYY-MM-DD', optionally followed by 'T' (or a space)
+/// and 'HH:MM' or 'HH:MM:SS' (with an optional fraction of a second
+/// and trailing 'Z'); or a Julian Date as a number, such as
+/// '2451545.0'
+pub fn parse_date(s: &str) -> Option<f64> {
+    let s = s.trim();
+    if let Ok(jd) = s.parse::<f64>() {
+        return Some(jd);
+    }
+    let s = s.strip_suffix('Z').unwrap_or(s);
+    let (date, time) = match s.split_once(['T', ' ']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (s, None),
+    };
+    let mut date = date.split('-');
+    let year: i32 = date.next()?.parse().ok()?;
+    let month: u32 = date.next()?.parse().ok()?;
+    let day: u32 = date.next()?.parse().ok()?;
+    if date.next().is_some() || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let mut fraction = 0.;
+    if let Some(time) = time {
+        let fields: Vec<&str> = time.split(':').collect();
+        if fields.len() < 2 || fields.len() > 3 {
+            return None;
+        }
+        let hours: u32 = fields[0].parse().ok()?;
+        let minutes: u32 = fields[1].parse().ok()?;
+        let seconds: f64 = match fields.get(2) {
+            Some(f) => f.parse().ok()?,
+            None => 0.,
+        };
+        if hours > 23 || minutes > 59 || !(0.0..61.0).contains(&seconds) {
+            return None;
+        }
+        fraction = (hours as f64 + minutes as f64 / 60.0 + seconds / 3600.0) / 24.0;
+    }
+    Some(julian_date(year, month, day as f64 + fraction))
+}
@@ -0,0 +1,147 @@
+//a Imports
+use crate::CatalogIndex;
+
+//a Useful functions
+//fi edge
+/// Get the index of the edge between two of the three corners of a
+/// triangle, given the order (0-1, 0-2, 1-2)
+fn edge(a: usize, b: usize) -> usize {
+    a + b - 1
+}
+
+//fi sum_of_squares
+/// Get the sum of the squares of three values, independent of the
+/// order of the values (so that equal permutations score identically)
+fn sum_of_squares(v: &[f64; 3]) -> f64 {
+    let mut sq = v.map(|x| x * x);
+    sq.sort_by(|a, b| a.total_cmp(b));
+    sq[0] + sq[1] + sq[2]
+}
+
+//fp cos_angle_range
+/// Get the range of cosines (min, max) that are acceptable for an
+/// angle, given a maximum angular delta
+///
+/// Note cos(0) > cos(0.1) so min cos is cos(angle + max); the angle
+/// is clamped to 0 to 180 degrees, as (for example) cos(181 degrees)
+/// is *greater* than cos(180 degrees)
+pub(crate) fn cos_angle_range(angle: f64, max_angle_delta: f64) -> (f64, f64) {
+    (
+        (angle + max_angle_delta).min(std::f64::consts::PI).cos(),
+        (angle - max_angle_delta).max(0.).cos(),
+    )
+}
+
+//fp cos_angle_ranges
+/// Get the range of cosines (min, max) that are acceptable for each
+/// of three angles, given a maximum angular delta
+pub(crate) fn cos_angle_ranges(angles: &[f64; 3], max_angle_delta: f64) -> [(f64, f64); 3] {
+    angles.map(|a| cos_angle_range(a, max_angle_delta))
+}
+
+//a TriangleMatch
+//tp TriangleMatch
+/// A triangle of stars (s0, s1, s2) found by
+/// [crate::Catalog::find_star_triangles_with], with its error
+/// measures
+///
+/// The angles, in radians, are in the same order as the angles to
+/// find: s0 to s1, s0 to s2, and s1 to s2.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TriangleMatch {
+    /// The three stars of the triangle
+    pub stars: [CatalogIndex; 3],
+    /// The actual angles between the stars
+    pub angles: [f64; 3],
+    /// The actual angles minus those that were being searched for
+    pub residuals: [f64; 3],
+    /// The root-mean-square of the residuals; smaller is a better match
+    pub score: f64,
+}
+
+//ip TriangleMatch
+impl TriangleMatch {
+    //cp new
+    /// Create a new [TriangleMatch] from the stars and the cosines
+    /// of the angles between them
+    pub(crate) fn new(stars: [CatalogIndex; 3], cos: [f64; 3], angles_to_find: &[f64; 3]) -> Self {
+        let angles = cos.map(|c| c.clamp(-1., 1.).acos());
+        let residuals = [
+            angles[0] - angles_to_find[0],
+            angles[1] - angles_to_find[1],
+            angles[2] - angles_to_find[2],
+        ];
+        let score = (sum_of_squares(&residuals) / 3.0).sqrt();
+        Self {
+            stars,
+            angles,
+            residuals,
+            score,
+        }
+    }
+
+    //mp is_canonical
+    /// Return true if this is the best of the orderings of its three
+    /// stars that match the angles being searched for
+    ///
+    /// When two or more of the angles being searched for are similar
+    /// the same three stars may match in more than one order; only one
+    /// of these is canonical, being the one with the lowest score (or,
+    /// if scores are equal, the lowest star indices)
+    pub(crate) fn is_canonical(
+        &self,
+        cos: &[f64; 3],
+        angles_to_find: &[f64; 3],
+        cos_ranges: &[(f64, f64); 3],
+    ) -> bool {
+        const PERMUTATIONS: [[usize; 3]; 5] =
+            [[0, 2, 1], [1, 0, 2], [1, 2, 0], [2, 0, 1], [2, 1, 0]];
+        for p in PERMUTATIONS {
+            let edges = [edge(p[0], p[1]), edge(p[0], p[2]), edge(p[1], p[2])];
+            let matches = edges
+                .iter()
+                .zip(cos_ranges.iter())
+                .all(|(e, (min, max))| cos[*e] >= *min && cos[*e] <= *max);
+            if !matches {
+                continue;
+            }
+            let residuals = [
+                self.angles[edges[0]] - angles_to_find[0],
+                self.angles[edges[1]] - angles_to_find[1],
+                self.angles[edges[2]] - angles_to_find[2],
+            ];
+            let score = (sum_of_squares(&residuals) / 3.0).sqrt();
+            let stars = p.map(|i| self.stars[i]);
+            if score < self.score || (score == self.score && stars < self.stars) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+//a TriangleUniqueness
+//tp TriangleUniqueness
+/// The uniqueness of one triangle of a set of stars, from
+/// [crate::Catalog::triangle_uniqueness]
+#[derive(Debug, Clone)]
+pub struct TriangleUniqueness {
+    /// The three stars of the triangle
+    pub stars: [CatalogIndex; 3],
+    /// The angles between the stars (s0 to s1, s0 to s2, and s1 to s2)
+    pub angles: [f64; 3],
+    /// The number of other triangles of stars in the catalog that
+    /// match the angles to within the tolerance
+    pub num_competing: usize,
+    /// The best matching of the other triangles, best first
+    pub nearest: Vec<TriangleMatch>,
+}
+
+//ip TriangleUniqueness
+impl TriangleUniqueness {
+    //ap is_unique
+    /// Return true if no other triangle of stars matches the angles
+    pub fn is_unique(&self) -> bool {
+        self.num_competing == 0
+    }
+}
@@ -0,0 +1,220 @@
+//a Imports
+use std::collections::HashSet;
+
+use crate::{Catalog, CatalogIndex};
+
+//a Useful functions
+//fi shape_of_sides
+/// Get the shape of a triangle from the lengths of its sides
+///
+/// The shape is the ratio of the middle side to the longest side,
+/// the ratio of the shortest side to the longest side, and the angle
+/// between the longest and middle sides (the smallest angle of the
+/// triangle); the order of the corners, as indices into the sides,
+/// is also returned, being the corner opposite the longest side,
+/// then the corner opposite the middle side, then the corner opposite
+/// the shortest side
+///
+/// The side i is taken to be opposite corner i
+fn shape_of_sides(sides: &[f64; 3]) -> ([f64; 3], [usize; 3]) {
+    let mut order = [0, 1, 2];
+    order.sort_by(|a, b| sides[*b].total_cmp(&sides[*a]));
+    let (l, m, s) = (sides[order[0]], sides[order[1]], sides[order[2]]);
+    let cos_angle = ((l * l + m * m - s * s) / (2.0 * l * m)).clamp(-1., 1.);
+    ([m / l, s / l, cos_angle.acos()], order)
+}
+
+//a ShapeMatch
+//tp ShapeMatch
+/// A candidate match of a triangle of points in an image to three
+/// stars from a [TriangleShapeIndex]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShapeMatch {
+    /// The stars corresponding to the three points, in the same
+    /// order as the points
+    pub stars: [CatalogIndex; 3],
+    /// The implied plate scale, in radians per pixel (or whatever
+    /// unit the points are given in)
+    ///
+    /// For a narrow field of view this is roughly the reciprocal of
+    /// the focal length in pixels
+    pub plate_scale: f64,
+    /// The root-mean-square of the differences in the two side ratios
+    /// and the included angle (in radians); smaller is a better match
+    pub score: f64,
+}
+
+//a TriangleShapeIndex
+//ti ShapeEntry
+/// A triangle of stars in the index
+#[derive(Debug, Clone, Copy)]
+struct ShapeEntry {
+    /// Middle to longest side ratio, shortest to longest side ratio,
+    /// and the included angle between the longest and middle sides
+    shape: [f64; 3],
+    /// Stars in corner order (opposite the longest, middle, and
+    /// shortest sides)
+    stars: [CatalogIndex; 3],
+    /// Sum of the angular lengths of the sides, in radians
+    perimeter: f64,
+}
+
+//tp TriangleShapeIndex
+/// An index of the shapes of triangles of stars in a [Catalog], for
+/// matching triangles of points in an image whose scale is not known
+/// (such as when the focal length of the lens is unknown)
+///
+/// Each triangle is keyed by its shape - the ratios of the middle
+/// and shortest sides to the longest side, and the angle between the
+/// longest and middle sides - which does not depend on the scale,
+/// rotation or reflection of the triangle.
+///
+/// The index is built from the neighbourhood of every star in the
+/// catalog (using the catalog's subcubes): each star is combined with
+/// pairs of the brightest stars within a maximum angle of it. The
+/// catalog filter is applied to the stars.
+///
+/// The sides of the catalog triangles are angles on the sky, and
+/// those of the image triangles are distances on the image; these
+/// are only similar for a triangle that is small compared to the
+/// field of view of a rectilinear lens, and hence the maximum angle
+/// should be kept small (such as 10 degrees).
+///
+/// The index refers to stars by [CatalogIndex], and so it becomes
+/// invalid if the catalog is sorted again or has stars added or
+/// removed.
+#[derive(Debug, Clone, Default)]
+pub struct TriangleShapeIndex {
+    /// Entries sorted by the first shape ratio
+    entries: Vec<ShapeEntry>,
+}
+
+//ip TriangleShapeIndex
+impl TriangleShapeIndex {
+    //cp new
+    /// Create a new [TriangleShapeIndex] from a catalog, using
+    /// triangles of stars within *max_angle* (in radians) of one of
+    /// their corners, using up to *max_neighbors* of the brightest
+    /// stars around each star
+    ///
+    /// Needs data to have been derived for the Catalog
+    pub fn new(catalog: &Catalog, max_angle: f64, max_neighbors: usize) -> Self {
+        let mut seen = HashSet::new();
+        let mut entries = vec![];
+        let mut accepted = 0;
+        for i0 in catalog
+            .iter_subcubes()
+            .flat_map(|s| catalog[s].iter().copied())
+        {
+            let s0 = &catalog[i0];
+            if !catalog.filter().call(s0, accepted) {
+                continue;
+            }
+            accepted += 1;
+            let neighbors: Vec<CatalogIndex> = catalog
+                .find_brightest_around(&s0.vector, max_angle, max_neighbors + 1)
+                .into_iter()
+                .filter(|i| *i != i0)
+                .take(max_neighbors)
+                .collect();
+            for (n, i1) in neighbors.iter().enumerate() {
+                for i2 in neighbors[n + 1..].iter() {
+                    let mut key = [i0, *i1, *i2];
+                    key.sort();
+                    if !seen.insert(key) {
+                        continue;
+                    }
+                    let stars = [i0, *i1, *i2];
+                    let sides = [
+                        catalog[stars[1]].cos_angle_between(&catalog[stars[2]]),
+                        catalog[stars[0]].cos_angle_between(&catalog[stars[2]]),
+                        catalog[stars[0]].cos_angle_between(&catalog[stars[1]]),
+                    ]
+                    .map(|c| c.clamp(-1., 1.).acos());
+                    if sides.iter().any(|s| *s <= 0.) {
+                        continue;
+                    }
+                    let (shape, order) = shape_of_sides(&sides);
+                    entries.push(ShapeEntry {
+                        shape,
+                        stars: order.map(|o| stars[o]),
+                        perimeter: sides.iter().sum(),
+                    });
+                }
+            }
+        }
+        entries.sort_by(|a, b| a.shape[0].total_cmp(&b.shape[0]));
+        Self { entries }
+    }
+
+    //ap len
+    /// Get the number of triangles in the index
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    //ap is_empty
+    /// Return true if the index has no triangles
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    //mp find_matches
+    /// Find the triangles of stars that have the same shape as a
+    /// triangle of points (such as star centroids in an image), to
+    /// within a tolerance for the side ratios and a tolerance (in
+    /// radians) for the included angle
+    ///
+    /// The result is sorted by score, best first
+    ///
+    /// If two sides of the triangle are nearly the same length then
+    /// the correspondence of the points to the stars is ambiguous;
+    /// such triangles are best avoided
+    pub fn find_matches(
+        &self,
+        points: &[[f64; 2]; 3],
+        max_ratio_delta: f64,
+        max_angle_delta: f64,
+    ) -> Vec<ShapeMatch> {
+        let length = |a: &[f64; 2], b: &[f64; 2]| (a[0] - b[0]).hypot(a[1] - b[1]);
+        let sides = [
+            length(&points[1], &points[2]),
+            length(&points[0], &points[2]),
+            length(&points[0], &points[1]),
+        ];
+        let mut result = vec![];
+        if sides.iter().any(|s| *s <= 0.) {
+            return result;
+        }
+        let perimeter: f64 = sides.iter().sum();
+        let (shape, order) = shape_of_sides(&sides);
+
+        let start = self
+            .entries
+            .partition_point(|e| e.shape[0] < shape[0] - max_ratio_delta);
+        for e in self.entries[start..].iter() {
+            if e.shape[0] > shape[0] + max_ratio_delta {
+                break;
+            }
+            let d = [
+                e.shape[0] - shape[0],
+                e.shape[1] - shape[1],
+                e.shape[2] - shape[2],
+            ];
+            if d[1].abs() > max_ratio_delta || d[2].abs() > max_angle_delta {
+                continue;
+            }
+            let mut stars = e.stars;
+            for (o, s) in order.iter().zip(e.stars.iter()) {
+                stars[*o] = *s;
+            }
+            result.push(ShapeMatch {
+                stars,
+                plate_scale: e.perimeter / perimeter,
+                score: ((d[0] * d[0] + d[1] * d[1] + d[2] * d[2]) / 3.0).sqrt(),
+            });
+        }
+        result.sort_by(|a, b| a.score.total_cmp(&b.score));
+        result
+    }
+}
@@ -0,0 +1,86 @@
+use std::error::Error;
+
+use star_catalog::{hipparcos, AsterismPattern, Catalog, CatalogIndex};
+
+#[test]
+fn test_asterism() -> Result<(), Box<dyn Error>> {
+    let s = std::fs::read_to_string("hipparcos.json")?;
+    let mut catalog: Catalog = serde_json::from_str(&s)?;
+    catalog.retain(move |s, _n| s.brighter_than(5.0));
+    catalog.sort();
+    catalog.add_names(hipparcos::HIP_ALIASES, true)?;
+    catalog.derive_data();
+
+    // The plough, with the angles along the chain of stars and a few
+    // across it; the other angles are unknown
+    let plough: Vec<CatalogIndex> = [
+        "Dubhe", "Merak", "Phad", "Megrez", "Alioth", "Mizar", "Alkaid",
+    ]
+    .iter()
+    .map(|n| catalog.find_name(n).expect("Star should be named"))
+    .collect();
+    let angle = |a: usize, b: usize| {
+        catalog[plough[a]]
+            .cos_angle_between(&catalog[plough[b]])
+            .clamp(-1., 1.)
+            .acos()
+    };
+    let mut pattern = AsterismPattern::new(plough.len());
+    for (a, b) in [
+        (0, 1),
+        (1, 2),
+        (2, 3),
+        (3, 0),
+        (3, 4),
+        (4, 5),
+        (5, 6),
+        (1, 3),
+    ] {
+        pattern.set_angle(a, b, angle(a, b));
+    }
+    assert_eq!(pattern.angle(1, 0), Some(angle(0, 1)));
+    assert_eq!(pattern.angle(0, 6), None);
+
+    let max_angle_delta = 0.1_f64.to_radians();
+    let matches = catalog.find_asterisms(catalog.iter_subcubes(), &pattern, max_angle_delta)?;
+    assert!(!matches.is_empty());
+    assert_eq!(matches[0].stars, plough);
+    assert!(matches[0].score < 1.0E-9);
+    for m in matches.iter() {
+        assert_eq!(m.stars.len(), plough.len());
+        for (a, b, angle) in pattern.iter_angles() {
+            let actual = catalog[m.stars[a]]
+                .cos_angle_between(&catalog[m.stars[b]])
+                .clamp(-1., 1.)
+                .acos();
+            assert!((actual - angle).abs() <= max_angle_delta + 1.0E-9);
+        }
+        let mut stars = m.stars.clone();
+        stars.sort();
+        stars.dedup();
+        assert_eq!(stars.len(), plough.len());
+    }
+
+    // A triangle pattern finds the same as the triangle search
+    let triangle = AsterismPattern::new(3)
+        .with_angle(0, 1, angle(0, 1))
+        .with_angle(0, 2, angle(0, 2))
+        .with_angle(1, 2, angle(1, 2));
+    let tri_matches =
+        catalog.find_asterisms(catalog.iter_subcubes(), &triangle, max_angle_delta)?;
+    let tris = catalog.find_star_triangles(
+        catalog.iter_subcubes(),
+        &[angle(0, 1), angle(0, 2), angle(1, 2)],
+        max_angle_delta,
+    );
+    assert_eq!(tri_matches.len(), tris.len());
+
+    // Every star must be connected to the pattern
+    let disconnected = AsterismPattern::new(4)
+        .with_angle(0, 1, 0.1)
+        .with_angle(2, 3, 0.1);
+    assert!(catalog
+        .find_asterisms(catalog.iter_subcubes(), &disconnected, max_angle_delta)
+        .is_err());
+    Ok(())
+}
@@ -0,0 +1,172 @@
+use std::error::Error;
+
+use geo_nd::Vector;
+use star_catalog::constellations::{
+    b1875_of_j2000, j2000_of_b1875, ConstellationBoundaries, CONSTELLATIONS,
+};
+use star_catalog::deep_sky::DeepSkyCatalog;
+use star_catalog::{Catalog, Star};
+
+/// A simple (not IAU) table: north of +60 is UMi; the north of the
+/// rest is Ori (0h to 12h) and Leo (12h to 24h); the south is Oct
+const TABLE: &str = "# Test boundaries
+ 0.0000 24.0000  60.0000 UMI
+ 0.0000 12.0000   0.0000 ORI
+12.0000 24.0000   0.0000 Leo
+
+ 0.0000 24.0000 -90.0000 oct
+";
+
+#[test]
+fn test_precession() {
+    // The B1875 pole in J2000 is moved by theta, about 0.6962 degrees
+    let pole = j2000_of_b1875(&[0., 0., 1.].into());
+    let de = pole[2].asin().to_degrees();
+    assert!((de - 89.3038).abs() < 0.001, "B1875 pole at {de}");
+    // and toward 12h in J2000 (stars near 0h move north with time)
+    assert!(pole[0] < 0.);
+
+    for (ra, de) in [(0.3, 0.2), (2.0, -1.0), (4.5, 1.4), (6.0, -0.1)] {
+        let v = Star::vec_of_ra_de(ra, de);
+        let v1 = j2000_of_b1875(&b1875_of_j2000(&v));
+        assert!((v - v1).length() < 1E-12);
+        // 125 years of precession is at most about 1.75 degrees
+        let angle = v.dot(&b1875_of_j2000(&v)).acos().to_degrees();
+        assert!(angle < 1.8, "Precessed by {angle}");
+    }
+}
+
+#[test]
+fn test_boundaries() -> Result<(), Box<dyn Error>> {
+    let boundaries: ConstellationBoundaries = TABLE.parse()?;
+    assert_eq!(boundaries.len(), 4);
+
+    let of_b1875 = |ra: f64, de: f64| {
+        let v = j2000_of_b1875(&Star::vec_of_ra_de(ra.to_radians(), de.to_radians()));
+        boundaries.constellation_of(&v)
+    };
+    assert_eq!(of_b1875(10., 61.), Some("UMi"));
+    assert_eq!(of_b1875(10., 59.), Some("Ori"));
+    assert_eq!(of_b1875(179., 1.), Some("Ori"));
+    assert_eq!(of_b1875(181., 1.), Some("Leo"));
+    assert_eq!(of_b1875(359., 1.), Some("Leo"));
+    assert_eq!(of_b1875(90., -1.), Some("Oct"));
+
+    assert_eq!(boundaries.edges().len(), 6);
+    let ori = boundaries.edges_of("ori");
+    // UMi, Oct, and Leo at both 12h and 0h
+    assert_eq!(ori.len(), 4);
+    for e in &ori {
+        assert!(e.constellations.contains(&"Ori"));
+        let points = e.points(0.01);
+        assert!(points.len() > 100);
+    }
+
+    assert!(matches!(
+        "0 24 60".parse::<ConstellationBoundaries>(),
+        Err(star_catalog::Error::BadBoundaryLine(1))
+    ));
+    assert!(matches!(
+        "0 24 60 XYZ".parse::<ConstellationBoundaries>(),
+        Err(star_catalog::Error::UnknownConstellation(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_builtin() -> Result<(), Box<dyn Error>> {
+    let boundaries = ConstellationBoundaries::builtin();
+    assert_eq!(boundaries.len(), 357);
+
+    // J2000 positions of some bright stars, in degrees
+    let of_ra_de = |ra: f64, de: f64| {
+        boundaries.constellation_of(&Star::vec_of_ra_de(ra.to_radians(), de.to_radians()))
+    };
+    assert_eq!(of_ra_de(88.793, 7.407), Some("Ori")); // Betelgeuse
+    assert_eq!(of_ra_de(37.955, 89.264), Some("UMi")); // Polaris
+    assert_eq!(of_ra_de(247.352, -26.432), Some("Sco")); // Antares
+    assert_eq!(of_ra_de(101.287, -16.716), Some("CMa")); // Sirius
+    assert_eq!(of_ra_de(279.235, 38.784), Some("Lyr")); // Vega
+    assert_eq!(of_ra_de(219.900, -60.834), Some("Cen")); // Alpha Centauri
+    assert_eq!(of_ra_de(95.988, -52.696), Some("Car")); // Canopus
+
+    // Every deep-sky object is within the constellation it is listed in
+    for o in DeepSkyCatalog::builtin().iter() {
+        assert_eq!(
+            boundaries.constellation_of(&o.vector),
+            Some(o.constellation.as_str()),
+            "{}",
+            o.designation
+        );
+    }
+
+    // Every constellation has a boundary
+    for (abbrev, _, _) in CONSTELLATIONS {
+        assert!(!boundaries.edges_of(abbrev).is_empty(), "{abbrev}");
+    }
+    Ok(())
+}
+
+#[test]
+fn test_stars_in_constellation() -> Result<(), Box<dyn Error>> {
+    let boundaries: ConstellationBoundaries = TABLE.parse()?;
+    let s = std::fs::read_to_string("hipparcos.json")?;
+    let mut catalog: Catalog = serde_json::from_str(&s)?;
+    catalog.sort();
+    catalog.derive_data();
+
+    let mut total = 0;
+    for abbrev in ["UMi", "Ori", "Leo", "Oct"] {
+        let stars = catalog.stars_in_constellation(&boundaries, abbrev)?;
+        for i in &stars {
+            assert_eq!(
+                boundaries.constellation_of(&catalog[*i].vector),
+                Some(abbrev)
+            );
+        }
+        total += stars.len();
+    }
+    assert_eq!(total, catalog.len());
+
+    // Polaris was at about +88 deg 39 min in 1875
+    let polaris = catalog.find_sorted(11767).unwrap();
+    let de = b1875_of_j2000(&catalog[polaris].vector)[2]
+        .asin()
+        .to_degrees();
+    assert!((de - 88.65).abs() < 0.05, "Polaris B1875 at {de}");
+    let umi = catalog.stars_in_constellation(&boundaries, "umi")?;
+    assert!(umi.contains(&polaris));
+
+    assert!(catalog.stars_in_constellation(&boundaries, "Xyz").is_err());
+    Ok(())
+}
+
+#[test]
+fn test_select_named_stars() -> Result<(), Box<dyn Error>> {
+    // Selecting the stars of a constellation after naming them keeps
+    // the names of the selected stars, and drops those of the others
+    let boundaries: ConstellationBoundaries = TABLE.parse()?;
+    let s = std::fs::read_to_string("hipparcos.json")?;
+    let mut catalog: Catalog = serde_json::from_str(&s)?;
+    catalog.retain(move |s, _n| s.brighter_than(6.0));
+    catalog.sort();
+    catalog.add_names(star_catalog::hipparcos::HIP_ALIASES, true)?;
+    catalog.derive_data();
+
+    let mut ids: Vec<usize> = catalog
+        .stars_in_constellation(&boundaries, "Ori")?
+        .into_iter()
+        .map(|i| catalog[i].id)
+        .collect();
+    ids.sort();
+    catalog.retain(move |s, _n| ids.binary_search(&s.id).is_ok());
+    catalog.sort();
+    catalog.derive_data();
+
+    let betelgeuse = catalog.find_name("Betelgeuse").unwrap();
+    assert_eq!(catalog[betelgeuse].id, 27989);
+    let bellatrix = catalog.find_name("Bellatrix").unwrap();
+    assert_eq!(catalog[bellatrix].id, 25336);
+    assert!(catalog.find_name("Dubhe").is_none());
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use std::error::Error;
+
+use star_catalog::{
+    combined_magnitude, position_angle, Catalog, ClosePairPolicy, Star, StarFilter,
+};
+
+#[test]
+fn test_pair_measures() {
+    assert!((combined_magnitude(2.0, 2.0) - (2.0 - 2.5 * 2.0_f32.log10())).abs() < 1.0E-5);
+    assert!((combined_magnitude(1.0, 20.0) - 1.0).abs() < 1.0E-5);
+
+    let a = Star::new(1, 1.0, 0.5, 0., 1., 0.);
+    let north = Star::new(2, 1.0, 0.501, 0., 1., 0.);
+    let east = Star::new(3, 1.001, 0.5, 0., 1., 0.);
+    let west = Star::new(4, 0.999, 0.5, 0., 1., 0.);
+    let pa = |s| position_angle(&a, s).to_degrees();
+    assert!(pa(&north).abs() < 0.01);
+    assert!((pa(&east) - 90.0).abs() < 0.1);
+    assert!((pa(&west) - 270.0).abs() < 0.1);
+}
+
+#[test]
+fn test_close_pairs() -> Result<(), Box<dyn Error>> {
+    let s = std::fs::read_to_string("hipparcos.json")?;
+    let mut catalog: Catalog = serde_json::from_str(&s)?;
+    catalog.retain(move |s, _n| s.brighter_than(6.0));
+    catalog.sort();
+    catalog.derive_data();
+
+    let max_angle = 0.25_f64.to_radians();
+    let pairs = catalog.close_pairs(max_angle);
+    assert!(!pairs.is_empty());
+    for w in pairs.windows(2) {
+        assert!(w[0].separation <= w[1].separation);
+    }
+    for p in pairs.iter() {
+        assert!(p.separation <= max_angle);
+        assert!(catalog[p.stars[0]].mag <= catalog[p.stars[1]].mag);
+        assert!(p.combined_magnitude <= catalog[p.stars[0]].mag);
+    }
+
+    // Mizar and Alcor are 11.8 arcminutes apart, with Alcor to the
+    // east-north-east of Mizar
+    let mizar = catalog.find_sorted(65378).unwrap();
+    let alcor = catalog.find_sorted(65477).unwrap();
+    let mizar_alcor = pairs
+        .iter()
+        .find(|p| p.stars == [mizar, alcor])
+        .expect("Mizar and Alcor should be a close pair");
+    assert!((mizar_alcor.separation.to_degrees() * 60.0 - 11.8).abs() < 0.2);
+    assert!((mizar_alcor.position_angle.to_degrees() - 71.0).abs() < 3.0);
+
+    // Filtering out the companions leaves Mizar; dropping both
+    // leaves neither
+    let near_mizar = |catalog: &Catalog| {
+        let mut ids: Vec<usize> = catalog
+            .find_stars_around(&catalog[mizar].vector, 0.5_f64.to_radians())
+            .into_iter()
+            .map(|i| catalog[i].id)
+            .collect();
+        ids.sort();
+        ids
+    };
+    let all = near_mizar(&catalog);
+    assert!(all.contains(&65378) && all.contains(&65477));
+    catalog.set_filter(StarFilter::close_pairs(
+        &catalog,
+        &pairs,
+        ClosePairPolicy::KeepBrighter,
+    ));
+    let merged = near_mizar(&catalog);
+    assert!(merged.contains(&65378) && !merged.contains(&65477));
+    catalog.set_filter(StarFilter::close_pairs(
+        &catalog,
+        &pairs,
+        ClosePairPolicy::Drop,
+    ));
+    let dropped = near_mizar(&catalog);
+    assert!(!dropped.contains(&65378) && !dropped.contains(&65477));
+    catalog.clear_filter();
+
+    // Merging the pairs combines Mizar and Alcor into one star
+    let num_stars = catalog.len();
+    let mizar_mag = catalog[mizar].mag;
+    let num_merged = catalog.merge_close_pairs(max_angle);
+    assert!(num_merged > 0);
+    assert_eq!(catalog.len(), num_stars - num_merged);
+    catalog.sort();
+    catalog.derive_data();
+    assert!(catalog.find_sorted(65477).is_none());
+    let mizar = catalog.find_sorted(65378).unwrap();
+    assert!((catalog[mizar].mag - mizar_alcor.combined_magnitude).abs() < 1.0E-5);
+    assert!(catalog[mizar].mag < mizar_mag);
+    Ok(())
+}
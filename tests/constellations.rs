@@ -0,0 +1,225 @@
+use std::collections::HashSet;
+use std::error::Error;
+
+use geo_nd::Vector;
+
+use star_catalog::constellations::{Constellation, CONSTELLATIONS, GENITIVES, NORTHERN_HEMISPHERE};
+use star_catalog::{Catalog, CatalogIndex};
+
+fn check_figure(catalog: &Catalog, name: &str, ids: &[usize]) {
+    let mut last: Option<CatalogIndex> = None;
+    for id in ids {
+        if *id == 0 {
+            last = None;
+            continue;
+        }
+        let Some(index) = catalog.find_sorted(*id) else {
+            panic!("{name}: star {id} is not in the Hipparcos catalog");
+        };
+        let star = &catalog[index];
+        assert!(star.mag < 6.5, "{name}: star {id} is too faint");
+        if let Some(l) = last {
+            let angle = catalog[l].cos_angle_between(star).acos().to_degrees();
+            assert!(
+                angle > 0.1 && angle < 30.0,
+                "{name}: line to star {id} is {angle} degrees long"
+            );
+        }
+        last = Some(index);
+    }
+}
+
+#[test]
+fn test_constellations() -> Result<(), Box<dyn Error>> {
+    let s = std::fs::read_to_string("hipparcos.json")?;
+    let mut catalog: Catalog = serde_json::from_str(&s)?;
+    catalog.sort();
+
+    assert_eq!(CONSTELLATIONS.len(), 88);
+    let abbrevs: HashSet<&str> = CONSTELLATIONS.iter().map(|(a, _, _)| *a).collect();
+    assert_eq!(abbrevs.len(), 88);
+    for (abbrev, name, ids) in CONSTELLATIONS {
+        assert_eq!(abbrev.len(), 3, "{name}: abbreviation {abbrev}");
+        check_figure(&catalog, name, ids);
+    }
+    for (name, ids) in NORTHERN_HEMISPHERE {
+        check_figure(&catalog, name, ids);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_western() -> Result<(), Box<dyn Error>> {
+    assert_eq!(GENITIVES.len(), 88);
+    for ((a, _, _), (b, _)) in CONSTELLATIONS.iter().zip(GENITIVES.iter()) {
+        assert_eq!(a, b);
+    }
+    let western = Constellation::western();
+    assert_eq!(western.len(), 88);
+    let ori = western.iter().find(|c| c.abbrev == "Ori").unwrap();
+    assert_eq!(ori.name, "Orion");
+    assert_eq!(ori.genitive, "Orionis");
+    let and = western.iter().find(|c| c.abbrev == "And").unwrap();
+    assert_eq!(and.polylines.len(), 3);
+    assert_eq!(and.iter_lines().count(), 6);
+    assert_eq!(and.polylines[1], vec![3881, 4436, 5447]);
+
+    let s = std::fs::read_to_string("hipparcos.json")?;
+    let mut catalog: Catalog = serde_json::from_str(&s)?;
+    catalog.sort();
+    for c in &western {
+        assert!(c.missing_ids(&catalog).is_empty(), "{}", c.name);
+        assert_eq!(c.iter_star_lines(&catalog).count(), c.iter_lines().count());
+        let v = c.label_vector(&catalog).unwrap();
+        for id in c.star_ids() {
+            let s = &catalog[catalog.find_sorted(id).unwrap()];
+            assert!(s.vector.dot(&v) > 0.0, "{}: label far from {id}", c.name);
+        }
+    }
+    let bogus = Constellation::new("Xyz", "Bogus").with_polyline(&[32349, 999_999_999]);
+    assert_eq!(bogus.missing_ids(&catalog), vec![999_999_999]);
+    assert_eq!(bogus.iter_star_lines(&catalog).count(), 0);
+    Ok(())
+}
+
+#[test]
+fn test_stellarium_fab() -> Result<(), Box<dyn Error>> {
+    let fab = "# Some figures
+Ori 4 26727 27989 27989 26207 26727 25930 25930 25336
+
+001 2 65474 69673 69673 71957
+Ori 1 27366 26727
+";
+    let figures = Constellation::read_stellarium_fab(fab)?;
+    assert_eq!(figures.len(), 2);
+    assert_eq!(figures[0].name, "Orion");
+    assert_eq!(figures[0].genitive, "Orionis");
+    assert_eq!(
+        figures[0].polylines,
+        vec![
+            vec![26727, 27989, 26207],
+            vec![26727, 25930, 25336],
+            vec![27366, 26727]
+        ]
+    );
+    assert_eq!(figures[1].abbrev, "001");
+    assert_eq!(figures[1].name, "001");
+    assert_eq!(figures[1].polylines, vec![vec![65474, 69673, 71957]]);
+
+    let mut figures = figures;
+    let names = "001\t\"Horn\"\t_(\"Horn\")\nXyz \"Nothing\"\n";
+    assert_eq!(
+        Constellation::apply_stellarium_names(&mut figures, names),
+        1
+    );
+    assert_eq!(figures[1].name, "Horn");
+
+    assert!(Constellation::read_stellarium_fab("Ori 2 26727 27989").is_err());
+    assert!(Constellation::read_stellarium_fab("Ori\nOri x").is_err());
+    assert!(Constellation::read_stellarium_fab("\nOri 1 26727 Betelgeuse").is_err());
+    Ok(())
+}
+
+#[test]
+fn test_constellation_json() -> Result<(), Box<dyn Error>> {
+    let western = Constellation::western();
+    let s = serde_json::to_string(&western)?;
+    let read: Vec<Constellation> = serde_json::from_str(&s)?;
+    assert_eq!(read, western);
+
+    let read: Vec<Constellation> = serde_json::from_str(
+        r#"[{"abbrev":"Tri","name":"Triangulum","polylines":[[10064,8796,10670,10064]]}]"#,
+    )?;
+    assert_eq!(read[0].genitive, "");
+    assert_eq!(read[0].label, None);
+    assert_eq!(read[0].iter_lines().count(), 3);
+
+    let index_json = r#"{
+        "id": "chinese",
+        "constellations": [
+            {
+                "id": "CON chinese 001",
+                "lines": [[65474, 69673, "thin", 71957, 73555]],
+                "common_name": {"english": "Horn", "native": "角"}
+            },
+            {"id": "CON chinese 002", "lines": [[65474]], "common_name": {"native": "亢"}}
+        ]
+    }"#;
+    let read = Constellation::read_stellarium_json(index_json)?;
+    assert_eq!(read.len(), 2);
+    assert_eq!(read[0].abbrev, "001");
+    assert_eq!(read[0].name, "Horn");
+    assert_eq!(
+        read[0].polylines,
+        vec![vec![65474, 69673], vec![71957, 73555]]
+    );
+    assert_eq!(read[1].name, "亢");
+    assert!(read[1].polylines.is_empty());
+    assert!(Constellation::read_stellarium_json("{}").is_err());
+    Ok(())
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn test_draw_constellations() -> Result<(), Box<dyn Error>> {
+    use geo_nd::Quaternion;
+    use image::GenericImageView;
+    use star_catalog::{ConstellationLabel, ConstellationStyle, ImageView, Quat};
+
+    let s = std::fs::read_to_string("hipparcos.json")?;
+    let mut catalog: Catalog = serde_json::from_str(&s)?;
+    catalog.sort();
+    // Betelgeuse to Bellatrix
+    let figure = vec![Constellation::new("Ori", "Orion").with_polyline(&[27989, 25336])];
+    let v0 = catalog[catalog.find_sorted(27989).unwrap()].vector;
+    let v1 = catalog[catalog.find_sorted(25336).unwrap()].vector;
+    let center = (v0 + v1).normalize();
+
+    let count = |style: &ConstellationStyle| {
+        let mut image_view = ImageView::new(image::DynamicImage::new_rgb8(200, 200));
+        image_view
+            .set_tan_hfov(0.2)
+            .set_orient(Quat::look_at(&center, &[0., 0., 1.].into()));
+        image_view.draw_constellations(&catalog, &figure, style);
+        let image = image_view.take_image();
+        let mut lines = 0;
+        let mut labels = 0;
+        for (_, _, p) in image.pixels() {
+            if p[0] == style.color[0] && p[1] == style.color[1] {
+                lines += 1;
+            } else if p[0] == style.label_color[0] && p[1] == style.label_color[1] {
+                labels += 1;
+            }
+        }
+        (lines, labels, image.get_pixel(100, 100)[1])
+    };
+    let style = ConstellationStyle {
+        color: [0, 255, 0, 255].into(),
+        label_color: [255, 0, 0, 255].into(),
+        ..Default::default()
+    };
+    let (thin, labels, middle) = count(&style);
+    assert!(thin > 50, "{thin} pixels drawn for the line");
+    assert_eq!(labels, 0);
+    assert_eq!(middle, 255, "line should pass through the middle");
+
+    let (wide, _, _) = count(&ConstellationStyle { width: 3, ..style });
+    assert!(
+        wide > 2 * thin,
+        "{wide} pixels for a wide line, {thin} for thin"
+    );
+
+    let (gapped, _, _) = count(&ConstellationStyle { gap: 10., ..style });
+    assert!(
+        gapped < thin - 10,
+        "{gapped} pixels with a gap, {thin} without"
+    );
+
+    let labelled = ConstellationStyle {
+        label: Some(ConstellationLabel::Name),
+        ..style
+    };
+    let (_, labels, _) = count(&labelled);
+    assert!(labels > 20, "{labels} pixels for the label");
+    Ok(())
+}
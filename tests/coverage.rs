@@ -0,0 +1,131 @@
+use std::error::Error;
+
+use geo_nd::Quaternion;
+use star_catalog::{Catalog, FieldCoverage, FieldOfView, Quat};
+
+#[test]
+fn test_field_of_view() {
+    let orient = Quat::look_at(&[1., 0., 0.].into(), &[0., 0., 1.].into());
+    let circle = FieldOfView::Circular(20.0_f64.to_radians());
+    let rect = FieldOfView::Rectangular(20.0_f64.to_radians(), 10.0_f64.to_radians());
+    assert!((circle.max_angle() - 10.0_f64.to_radians()).abs() < 1.0E-12);
+    assert!(rect.max_angle() > 10.0_f64.to_radians());
+    assert!(rect.max_angle() < 12.0_f64.to_radians());
+
+    let near = star_catalog::Star::vec_of_ra_de(9.0_f64.to_radians(), 0.);
+    let above = star_catalog::Star::vec_of_ra_de(0., 9.0_f64.to_radians());
+    let behind = star_catalog::Star::vec_of_ra_de(std::f64::consts::PI, 0.);
+    assert!(circle.contains(&orient, &near));
+    assert!(circle.contains(&orient, &above));
+    assert!(!circle.contains(&orient, &behind));
+    assert!(rect.contains(&orient, &near));
+    assert!(!rect.contains(&orient, &above));
+    assert!(!rect.contains(&orient, &behind));
+
+    // The angles must be more than 0 and less than 180 degrees
+    assert!(circle.validate().is_ok());
+    assert!(rect.validate().is_ok());
+    let pi = std::f64::consts::PI;
+    for fov in [
+        FieldOfView::Circular(0.),
+        FieldOfView::Circular(pi),
+        FieldOfView::Rectangular(pi, 0.1),
+        FieldOfView::Rectangular(0.1, 4.0),
+        FieldOfView::Rectangular(f64::NAN, 0.1),
+    ] {
+        assert!(matches!(
+            fov.validate(),
+            Err(star_catalog::Error::BadFieldOfView)
+        ));
+    }
+}
+
+#[test]
+fn test_field_coverage() -> Result<(), Box<dyn Error>> {
+    let s = std::fs::read_to_string("hipparcos.json")?;
+    let mut catalog: Catalog = serde_json::from_str(&s)?;
+    catalog.retain(move |s, _n| s.brighter_than(6.0));
+    catalog.sort();
+    catalog.derive_data();
+
+    let magnitude = 5.0;
+    let num_stars = catalog.iter_stars().filter(|s| s.mag < magnitude).count();
+    let fov = FieldOfView::Circular(20.0_f64.to_radians());
+    let coverage = catalog.field_coverage(fov, magnitude, 4)?;
+    assert!(catalog
+        .field_coverage(FieldOfView::Rectangular(3.2, 0.5), magnitude, 4)
+        .is_err());
+    assert_eq!(coverage.counts().len(), 3072);
+    assert!(coverage.min() <= coverage.median());
+    assert!(coverage.median() <= coverage.max());
+    assert_eq!(coverage.fraction_below(0), 0.0);
+    assert_eq!(coverage.fraction_below(coverage.max() + 1), 1.0);
+    assert!(coverage.fraction_below(coverage.median()) <= 0.5);
+
+    // The mean count is the number of stars times the fraction of the
+    // sphere covered by the field of view
+    let expected = num_stars as f64 * (1.0 - fov.max_angle().cos()) / 2.0;
+    assert!((coverage.mean() - expected).abs() < expected * 0.02);
+
+    // Every sample must match a direct count of the stars
+    let index = *coverage.index();
+    for pixel in (0..index.num_pixels()).step_by(37) {
+        let v = index.vector_of_pixel(pixel);
+        let orient = FieldCoverage::orient_of_sample(&index, pixel);
+        let direct = catalog
+            .iter_stars()
+            .filter(|s| s.mag < magnitude && fov.contains(&orient, &s.vector))
+            .count();
+        assert_eq!(coverage.counts()[pixel as usize], direct);
+        assert_eq!(coverage.count_at(&v), direct);
+    }
+
+    // A rectangle within the circle sees no more stars
+    let rect = FieldOfView::Rectangular(14.0_f64.to_radians(), 14.0_f64.to_radians());
+    let rect_coverage = catalog.field_coverage(rect, magnitude, 4)?;
+    for (r, c) in rect_coverage.counts().iter().zip(coverage.counts().iter()) {
+        assert!(r <= c);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn test_draw_field_coverage() -> Result<(), Box<dyn Error>> {
+    use geo_nd::Vector;
+    use image::GenericImageView;
+    use star_catalog::ImageView;
+
+    // The center of each face of a cubemap is along a different axis
+    let mut axes = vec![];
+    for face in 0..6 {
+        let (x, y, orient) = ImageView::cubemap_face(face);
+        assert!(x < 4 && y < 3);
+        let axis = orient.conjugate().apply3(&[0., 0., -1.].into());
+        assert!((axis.length() - 1.0).abs() < 1E-12);
+        assert!(axes.iter().all(|a: &star_catalog::Vec3| a.dot(&axis) < 0.5));
+        axes.push(axis);
+    }
+
+    let s = std::fs::read_to_string("hipparcos.json")?;
+    let mut catalog: Catalog = serde_json::from_str(&s)?;
+    catalog.retain(move |s, _n| s.brighter_than(5.0));
+    catalog.sort();
+    catalog.derive_data();
+    let coverage = catalog.field_coverage(FieldOfView::Circular(0.3), 5.0, 2)?;
+
+    // Count the red pixels of a heat map
+    let red = |min_stars: usize| {
+        let mut image_view = ImageView::new(image::DynamicImage::new_rgb8(32, 32));
+        image_view.set_tan_hfov(1.0);
+        image_view.draw_field_coverage(&coverage, min_stars);
+        let image = image_view.take_image();
+        image
+            .pixels()
+            .filter(|(_, _, p)| p[0] == 255 && p[2] == 0)
+            .count()
+    };
+    assert_eq!(red(0), 0);
+    assert_eq!(red(coverage.max() + 1), 32 * 32);
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use std::error::Error;
+
+use star_catalog::deep_sky::{
+    DeepSkyCatalog, DeepSkyKind, DeepSkyObject, CALDWELL, MESSIER, NGC_IC,
+};
+use star_catalog::Star;
+
+#[test]
+fn test_builtin() -> Result<(), Box<dyn Error>> {
+    let messier: DeepSkyCatalog = MESSIER.parse()?;
+    let caldwell: DeepSkyCatalog = CALDWELL.parse()?;
+    let ngc_ic: DeepSkyCatalog = NGC_IC.parse()?;
+    assert_eq!(messier.len(), 110);
+    assert_eq!(caldwell.len(), 109);
+    assert_eq!(ngc_ic.len(), 31);
+
+    let catalog = DeepSkyCatalog::builtin();
+    assert_eq!(catalog.len(), 250);
+    for o in catalog.iter() {
+        assert!(
+            o.minor <= o.major,
+            "{} minor axis exceeds major",
+            o.designation
+        );
+        assert!(o.de.abs() <= std::f64::consts::FRAC_PI_2);
+    }
+
+    let m31 = catalog.find_designation("m31").unwrap();
+    assert_eq!(catalog.find_designation("NGC 224"), Some(m31));
+    let m31 = &catalog[m31];
+    assert_eq!(m31.kind, DeepSkyKind::Galaxy);
+    assert_eq!(m31.constellation, "And");
+    assert_eq!(m31.name, "Andromeda Galaxy");
+    assert!((m31.ra.to_degrees() - 10.68).abs() < 0.1);
+    assert!((m31.de.to_degrees() - 41.27).abs() < 0.1);
+    assert!(m31.major.to_degrees() > 2.0);
+    assert!(catalog.find_designation("M 111").is_none());
+
+    let coalsack = &catalog[catalog.find_designation("C99").unwrap()];
+    assert_eq!(coalsack.kind, DeepSkyKind::DarkNebula);
+    assert!(coalsack.mag.is_none());
+    assert!(coalsack.alternate.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_find_around() -> Result<(), Box<dyn Error>> {
+    let catalog = DeepSkyCatalog::builtin();
+    let m42 = catalog.find_designation("M 42").unwrap();
+    let m43 = catalog.find_designation("M 43").unwrap();
+    let m31 = catalog.find_designation("M 31").unwrap();
+
+    let found = catalog.find_around(&catalog[m42].vector, 0.5_f64.to_radians());
+    assert!(found.contains(&m42));
+    assert!(found.contains(&m43));
+    assert!(!found.contains(&m31));
+
+    // M 31 is over 3 degrees across, so it is found from 2 degrees
+    // beyond its center, which is not within 2 degrees
+    let v = Star::vec_of_ra_de(catalog[m31].ra, catalog[m31].de + 3.0_f64.to_radians());
+    let found = catalog.find_around(&v, 2.0_f64.to_radians());
+    assert!(found.contains(&m31));
+
+    // All the objects are found around any direction with an angle
+    // of 180 degrees
+    let found = catalog.find_around(&v, std::f64::consts::PI);
+    assert_eq!(found.len(), catalog.len());
+    Ok(())
+}
+
+#[test]
+fn test_parse() -> Result<(), Box<dyn Error>> {
+    let table = "# A test table
+
+X 1 | NGC 1 | Gx | Peg | 00 07.3 | +27 42 | 13.7 | 1.7 | 1.1 | 90 | Test
+X 2 |       | PN | Peg | 12 00.0 | -00 30 |      | 0.5 | 0.5 | 0  |
+";
+    let catalog: DeepSkyCatalog = table.parse()?;
+    assert_eq!(catalog.len(), 2);
+    assert_eq!(catalog[0].alternate, "NGC 1");
+    assert_eq!(catalog[0].mag, Some(13.7));
+    assert!((catalog[0].position_angle.to_degrees() - 90.0).abs() < 1E-9);
+    assert!((catalog[1].ra.to_degrees() - 180.0).abs() < 1E-9);
+    assert!((catalog[1].de.to_degrees() + 0.5).abs() < 1E-9);
+    assert_eq!(catalog[1].kind, DeepSkyKind::PlanetaryNebula);
+
+    let bad_kind = table.replace("| PN |", "| XX |");
+    assert!(matches!(
+        bad_kind.parse::<DeepSkyCatalog>(),
+        Err(star_catalog::Error::BadDeepSkyLine(4))
+    ));
+    let bad_fields = table.replace("| Test", "");
+    assert!(matches!(
+        bad_fields.parse::<DeepSkyCatalog>(),
+        Err(star_catalog::Error::BadDeepSkyLine(3))
+    ));
+
+    let o = DeepSkyObject::new("Y 1", DeepSkyKind::OpenCluster, 0., 0.);
+    let catalog = DeepSkyCatalog::new(vec![o]);
+    assert!(catalog.find_designation("y1").is_some());
+    assert!(catalog.find_designation("").is_none());
+    Ok(())
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn test_draw_deep_sky() -> Result<(), Box<dyn Error>> {
+    use geo_nd::Quaternion;
+    use image::GenericImageView;
+    use star_catalog::{DeepSkyStyle, ImageView, Quat};
+
+    let builtin = DeepSkyCatalog::builtin();
+    let m31 = builtin[builtin.find_designation("M 31").unwrap()].clone();
+    let catalog = DeepSkyCatalog::new(vec![m31.clone()]);
+
+    let count = |style: &DeepSkyStyle| {
+        let mut image_view = ImageView::new(image::DynamicImage::new_rgb8(200, 200));
+        image_view
+            .set_tan_hfov(0.1)
+            .set_orient(Quat::look_at(&m31.vector, &[0., 0., 1.].into()));
+        image_view.draw_deep_sky_objects(&catalog, style);
+        let image = image_view.take_image();
+        let mut outlines = 0;
+        let mut labels = 0;
+        for (_, _, p) in image.pixels() {
+            if p[0] == style.color[0] && p[1] == style.color[1] {
+                outlines += 1;
+            } else if p[0] == style.label_color[0] && p[1] == style.label_color[1] {
+                labels += 1;
+            }
+        }
+        (outlines, labels, image.get_pixel(100, 100)[1])
+    };
+    let style = DeepSkyStyle {
+        color: [0, 255, 0, 255].into(),
+        label_color: [255, 0, 0, 255].into(),
+        ..Default::default()
+    };
+    let (outlines, labels, middle) = count(&style);
+    assert!(outlines > 100, "{outlines} pixels drawn for the outlines");
+    assert_eq!(labels, 0);
+    // A galaxy is an outline only
+    assert_eq!(middle, 0);
+
+    let (_, labels, _) = count(&DeepSkyStyle {
+        label: true,
+        ..style
+    });
+    assert!(labels > 20, "{labels} pixels drawn for the labels");
+    Ok(())
+}
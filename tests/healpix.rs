@@ -0,0 +1,112 @@
+use std::error::Error;
+
+use geo_nd::Vector;
+
+use star_catalog::{Catalog, HealpixIndex, SpatialCells, SpatialIndex, Star, SubcubeIndex};
+
+#[test]
+fn test_round_trip() -> Result<(), Box<dyn Error>> {
+    for order in 0..5 {
+        let hp = HealpixIndex::new(order);
+        for p in 0..hp.num_pixels() {
+            let v = hp.vector_of_pixel(p);
+            assert!((v.length() - 1.0).abs() < 1E-12);
+            assert_eq!(hp.pixel_of_vector(&v), p, "Order {order} pixel {p} {v}");
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_equal_area() -> Result<(), Box<dyn Error>> {
+    // A regular latitude/longitude sampling weighted by cos(de) should
+    // land roughly equally in each pixel
+    let hp = HealpixIndex::new(1);
+    let mut weights = vec![0.0; hp.num_pixels() as usize];
+    let n = 400;
+    for i in 0..n {
+        let de = ((i as f64 + 0.5) / n as f64 - 0.5) * std::f64::consts::PI;
+        for j in 0..2 * n {
+            let ra = (j as f64 + 0.5) / n as f64 * std::f64::consts::PI;
+            weights[hp.pixel_of_vector(&Star::vec_of_ra_de(ra, de)) as usize] += de.cos();
+        }
+    }
+    let total: f64 = weights.iter().sum();
+    for w in weights {
+        let f = w / total * hp.num_pixels() as f64;
+        assert!((f - 1.0).abs() < 0.01, "Pixel area fraction {f} is not 1");
+    }
+    Ok(())
+}
+
+#[test]
+fn test_neighbors() -> Result<(), Box<dyn Error>> {
+    for order in 1..5 {
+        let hp = HealpixIndex::new(order);
+        let max_angle = 4.0 * hp.max_pixel_radius();
+        for p in 0..hp.num_pixels() {
+            let neighbors = hp.neighbors(p);
+            assert!(neighbors.len() == 7 || neighbors.len() == 8);
+            let v = hp.vector_of_pixel(p);
+            for n in neighbors {
+                assert!(hp.neighbors(n).contains(&p), "{n} is a neighbor of {p}");
+                let a = hp.vector_of_pixel(n).dot(&v).acos();
+                assert!(a < max_angle, "{n} is too far from {p}");
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_cone_and_polygon() -> Result<(), Box<dyn Error>> {
+    let hp = HealpixIndex::new(4);
+    let v = Star::vec_of_ra_de(1.0, 0.5);
+    let angle = 0.2;
+    let cone = hp.pixels_in_cone(&v, angle);
+    let polygon: Vec<_> = [(0.8, 0.4), (1.2, 0.4), (1.2, 0.6), (0.8, 0.6)]
+        .iter()
+        .map(|(ra, de)| Star::vec_of_ra_de(*ra, *de))
+        .collect();
+    let in_polygon = hp.pixels_in_polygon(&polygon);
+    for i in 0..200 {
+        for j in 0..200 {
+            let ra = 0.5 + i as f64 / 200.0;
+            let de = 0.2 + j as f64 / 500.0;
+            let p = Star::vec_of_ra_de(ra, de);
+            let pixel = hp.pixel_of_vector(&p);
+            if p.dot(&v) >= angle.cos() {
+                assert!(cone.binary_search(&pixel).is_ok());
+            }
+            if (0.8..=1.2).contains(&ra) && (0.4..=0.6).contains(&de) {
+                assert!(in_polygon.binary_search(&pixel).is_ok());
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_spatial_cells() -> Result<(), Box<dyn Error>> {
+    let s = std::fs::read_to_string("hipparcos.json")?;
+    let mut catalog: Catalog = serde_json::from_str(&s)?;
+    catalog.retain(move |s, _n| s.brighter_than(6.0));
+    catalog.sort();
+    catalog.derive_data();
+
+    let healpix_cells = SpatialCells::new(HealpixIndex::new(4), &catalog);
+    let subcube_cells = SpatialCells::new(SubcubeIndex::default(), &catalog);
+    for (ra, de, angle) in [(0.0, 0.0, 0.1), (2.0, 1.5, 0.3), (4.0, -0.7, 0.05)] {
+        let v = Star::vec_of_ra_de(ra, de);
+        let mut expected = catalog.find_stars_around(&v, angle);
+        expected.sort();
+        let mut found = healpix_cells.find_stars_around(&catalog, &v, angle);
+        found.sort();
+        assert_eq!(expected, found);
+        let mut found = subcube_cells.find_stars_around(&catalog, &v, angle);
+        found.sort();
+        assert_eq!(expected, found);
+    }
+    assert!(subcube_cells.num_occupied() < subcube_cells.index().num_cells());
+    Ok(())
+}
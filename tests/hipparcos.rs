@@ -25,3 +25,61 @@ fn test_read_hipparcos_json() -> Result<(), Box<dyn Error>> {
     catalog.add_names(hipparcos::HIP_ALIASES, false)?;
     Ok(())
 }
+
+#[cfg(feature = "csv")]
+#[test]
+fn test_read_multiplicity() -> Result<(), Box<dyn Error>> {
+    let header = "Catalog,HIP,Proxy,RAhms,DEdms,Vmag,VarFlag,r_Vmag,RAdeg,DEdeg,\
+AstroRef,Plx,pmRA,pmDE,e_RAdeg,e_DEdeg,e_Plx,e_pmRA,e_pmDE,\
+DE:RA,Plx:RA,Plx:DE,pmRA:RA,pmRA:DE,pmRA:Plx,\
+pmDE:RA,pmDE:DE,pmDE:Plx,pmDE:pmRA,F1,F2,---,\
+BTmag,e_BTmag,VTmag,e_VTmag,m_BTmag,B-V,e_B-V,r_B-V,V-I,e_V-I,r_V-I,\
+CombMag,Hpmag,e_Hpmag,Hpscat,o_Hpmag,m_Hpmag,Hpmax,HPmin,\
+Period,HvarType,moreVar,morePhoto,CCDM,n_CCDM,Nsys,Ncomp,MultFlag,\
+Source,Qual,m_HIP,theta,rho,e_rho,dHp,e_dHp,Survey,Chart,Notes,\
+HD,BD,CoD,CPD,(V-I)red,SpType,r_SpType\n";
+    let polaris = "H,11767,,02 31 47.08,+89 15 50.9,1.97,1,H,\
+037.94614689,+89.26413805,,7.56,44.22,\
+-11.74,0.39,0.45,0.48,0.47,0.55,-0.16,\
+0.05,0.27,-0.01,0.08,0.05,0.04,-0.12,\
+-0.09,-0.36,1,1.22,11767,2.756,0.003,\
+2.067,0.003,,0.636,0.003,\
+T,0.70,0.00,\
+L,,2.1077,0.0021,0.014,102,,2.09,2.13,\
+3.97,P,1,A,02319+8915,I,1,1,,,,,,,,,,\
+S,,P,8890,B+88    8,,,0.68,F7:Ib-IIv SB,G\n";
+    let dubhe = "H,54061,H,11 03 43.84,+61 45 04.0,1.81,,H,\
+165.93265365,+61.75111888,A,26.38,\
+-136.46,-35.25,0.40,0.46,0.53,0.43,\
+0.45,0.19,0.08,-0.17,-0.13,0.00,-0.08,\
+-0.02,-0.30,0.00,0.14,4,2.15,54061,\
+3.185,0.002,1.934,0.003,*,1.061,0.003,\
+T,1.03,0.00,\
+L,*,1.9519,0.0004,0.005,158,*,1.94,1.96,,\
+D,,,11037+6145,I,1,2,C,,A,AB,270,\
+0.672,0.006,2.92,0.03,\
+S,,,95689,B+62 1161,,,1.04,F7V comp,G\n";
+    let csv = format!("{header}{polaris}{dubhe}");
+
+    let mut catalog = Catalog::default();
+    let multiplicity =
+        hipparcos::read_to_catalog_with_multiplicity(&mut catalog, csv.as_bytes(), 12.0)?;
+    assert_eq!(catalog.len(), 2);
+    assert_eq!(multiplicity.len(), 2);
+    assert_eq!(multiplicity[0].id, 11767);
+    assert_eq!(multiplicity[0].ccdm.as_deref(), Some("02319+8915"));
+    assert_eq!(multiplicity[0].mult_flag, None);
+    assert_eq!(multiplicity[1].id, 54061);
+    assert_eq!(multiplicity[1].ccdm.as_deref(), Some("11037+6145"));
+    assert_eq!(multiplicity[1].num_components, Some(2));
+    assert_eq!(multiplicity[1].mult_flag, Some('C'));
+
+    // Only the stars within the magnitude limit are read
+    let mut catalog = Catalog::default();
+    let multiplicity =
+        hipparcos::read_to_catalog_with_multiplicity(&mut catalog, csv.as_bytes(), 1.9)?;
+    assert_eq!(catalog.len(), 1);
+    assert_eq!(multiplicity.len(), 1);
+    assert_eq!(multiplicity[0].id, 54061);
+    Ok(())
+}
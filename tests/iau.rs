@@ -3,6 +3,7 @@ use std::error::Error;
 use star_catalog::{iau, Catalog};
 
 #[test]
+#[allow(clippy::unnecessary_cast, clippy::neg_cmp_op_on_partial_ord)]
 fn test_iau() -> Result<(), Box<dyn Error>> {
     let s = std::fs::read_to_string("hipparcos.json")?;
     let mut catalog: Catalog = serde_json::from_str(&s)?;
@@ -30,3 +31,162 @@ fn test_iau() -> Result<(), Box<dyn Error>> {
     }
     Ok(())
 }
+
+#[test]
+fn test_apply_iau_names() -> Result<(), Box<dyn Error>> {
+    let s = std::fs::read_to_string("hipparcos.json")?;
+    let mut catalog: Catalog = serde_json::from_str(&s)?;
+    catalog.sort();
+    catalog.derive_data();
+    let arcmin = (1.0_f64 / 60.0).to_radians();
+
+    let names = iau::IauName::builtin();
+    assert_eq!(names.len(), iau::NAMES_AND_RA_DE.len());
+    assert!(names.iter().all(|n| !n.designation.is_empty()));
+    let matches = catalog.match_iau_names(&names, arcmin);
+    assert_eq!(
+        matches.matched.len() + matches.unmatched.len() + matches.ambiguous.len(),
+        names.len()
+    );
+    let name_of = |i: usize| names[i].name.as_str();
+    // Proxima Centauri is too faint for the catalog
+    assert!(matches
+        .unmatched
+        .iter()
+        .any(|i| name_of(*i) == "Proxima Centauri"));
+    let (sirius_name, sirius) = matches
+        .matched
+        .iter()
+        .find(|(i, _)| name_of(*i) == "Sirius")
+        .unwrap();
+    assert_eq!(catalog[*sirius].id, 32349);
+    assert_eq!(names[*sirius_name].designation, "alf CMa A");
+
+    // Names without Hipparcos numbers are matched by position
+    let vega = catalog[catalog.find_sorted(91262).unwrap()].clone();
+    let by_position = vec![
+        iau::IauName {
+            name: "Not Vega".into(),
+            designation: "alf Lyr".into(),
+            hip: None,
+            ra: vega.ra + 0.1 * arcmin,
+            de: vega.de,
+        },
+        iau::IauName {
+            name: "Nowhere".into(),
+            designation: String::new(),
+            hip: None,
+            ra: vega.ra,
+            de: vega.de + 30. * arcmin,
+        },
+    ];
+    let matches = catalog.match_iau_names(&by_position, arcmin);
+    assert_eq!(matches.matched.len(), 1);
+    assert_eq!(catalog[matches.matched[0].1].id, 91262);
+    assert_eq!(matches.unmatched, vec![1]);
+
+    let matches = catalog.match_iau_names(&by_position[0..1], 120. * arcmin);
+    assert_eq!(matches.ambiguous.len(), 1);
+    assert!(matches.ambiguous[0].1.len() > 1);
+
+    let matches = catalog.apply_iau_names(&by_position, arcmin);
+    assert_eq!(matches.matched.len(), 1);
+    assert_eq!(catalog[catalog.find_name("Not Vega").unwrap()].id, 91262);
+    Ok(())
+}
+
+#[test]
+fn test_builtin() -> Result<(), Box<dyn Error>> {
+    // The included table has the names and positions of
+    // NAMES_AND_RA_DE, with designations
+    let names = iau::IauName::builtin();
+    for (name, _, ra, de) in iau::NAMES_AND_RA_DE.iter() {
+        let n = names.iter().find(|n| n.name == *name).unwrap();
+        assert!((n.ra.to_degrees() - ra).abs() < 1E-6, "{name}");
+        assert!((n.de.to_degrees() - de).abs() < 1E-6, "{name}");
+    }
+    let designation_of = |name: &str| {
+        let n = names.iter().find(|n| n.name == name).unwrap();
+        (n.designation.as_str(), n.hip)
+    };
+    assert_eq!(
+        designation_of("Rigil Kentaurus"),
+        ("alf Cen A", Some(71683))
+    );
+    assert_eq!(designation_of("Toliman"), ("alf Cen B", Some(71681)));
+    assert_eq!(designation_of("Absolutno"), ("XO-5", None));
+    assert_eq!(designation_of("Arcalís"), ("HD 131496", Some(72845)));
+    Ok(())
+}
+
+#[test]
+fn test_read_csn() -> Result<(), Box<dyn Error>> {
+    let rows = [
+        (
+            "#Name/ASCII",
+            "Name/Diacritics",
+            "Designation",
+            "HIP",
+            "RA(J2000)",
+            "Dec(J2000)",
+        ),
+        (
+            "Sirius",
+            "Sirius",
+            "alf CMa",
+            "32349",
+            "101.287155",
+            "-16.716116",
+        ),
+        (
+            "Rigil Kentaurus",
+            "Rigil Kentaurus",
+            "alf Cen A",
+            "71683",
+            "219.902066",
+            "-60.833975",
+        ),
+        (
+            "Absolutno",
+            "Absolutno",
+            "XO-5",
+            "_",
+            "116.716506",
+            "39.094572",
+        ),
+        (
+            "Arcalis",
+            "Arcalís",
+            "HD 131496",
+            "72845",
+            "223.345951",
+            "18.235409",
+        ),
+    ];
+    let mut s = "# IAU Catalog of Star Names\n\n".to_string();
+    for r in rows {
+        s += &format!(
+            "{:<18}{:<18}{:<12}{:<8}{:<12}{:<12}\n",
+            r.0, r.1, r.2, r.3, r.4, r.5
+        );
+    }
+    let names = iau::read_csn(&s)?;
+    assert_eq!(names.len(), 4);
+    assert_eq!(names[0].name, "Sirius");
+    assert_eq!(names[0].designation, "alf CMa");
+    assert_eq!(names[0].hip, Some(32349));
+    assert!((names[0].ra.to_degrees() - 101.287155).abs() < 1E-9);
+    assert!((names[0].de.to_degrees() + 16.716116).abs() < 1E-9);
+    assert_eq!(names[1].name, "Rigil Kentaurus");
+    assert_eq!(names[1].designation, "alf Cen A");
+    assert_eq!(names[2].hip, None);
+    assert_eq!(names[3].name, "Arcalís");
+
+    assert!(iau::read_csn("Sirius Sirius").is_err());
+    let bad = s.replace("-16.716116", "south     ");
+    assert!(matches!(
+        iau::read_csn(&bad),
+        Err(star_catalog::Error::BadIauNamesLine(4))
+    ));
+    Ok(())
+}
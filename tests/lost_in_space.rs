@@ -0,0 +1,120 @@
+use std::error::Error;
+
+use geo_nd::{Quaternion, Vector};
+
+use star_catalog::{hipparcos, Catalog, Quat, Star, Vec3};
+
+#[test]
+fn test_lost_in_space() -> Result<(), Box<dyn Error>> {
+    let s = std::fs::read_to_string("hipparcos.json")?;
+    let mut catalog: Catalog = serde_json::from_str(&s)?;
+    catalog.retain(move |s, _n| s.brighter_than(4.0));
+    catalog.sort();
+    catalog.add_names(hipparcos::HIP_ALIASES, true)?;
+    catalog.derive_data();
+
+    let fov = 20.0_f64.to_radians();
+    let tolerance = 0.05_f64.to_radians();
+    let axis: Vec3 = [0.3, -0.5, 0.8].into();
+    let attitude = Quat::of_axis_angle(&axis.normalize(), 2.1);
+
+    for name in ["Dubhe", "Betelgeuse", "Antares"] {
+        let center = catalog[catalog.find_name(name).unwrap()].vector;
+        let stars = catalog.find_brightest_around(&center, fov / 2.0, 7);
+
+        // Observe the stars (with a small error), and add a spurious
+        // observation
+        let mut observations: Vec<Vec3> = stars
+            .iter()
+            .enumerate()
+            .map(|(n, s)| {
+                let v = catalog[*s].vector;
+                let offset: Vec3 = [(n as f64).sin(), (n as f64).cos(), 0.].into();
+                (attitude.apply3(&v) + offset * (tolerance * 0.2)).normalize()
+            })
+            .collect();
+        let shift: Vec3 = [0.02, 0.03, -0.01].into();
+        let spurious = (center + shift).normalize();
+        observations.insert(3, attitude.apply3(&spurious));
+
+        let r = catalog
+            .lost_in_space(&observations, fov, tolerance)
+            .expect("Should have found the attitude");
+        eprintln!("{name}: confidence {}", r.confidence);
+        assert!(r.confidence > 0.5);
+        assert!(!r.truncated);
+        assert_eq!(r.stars[3], None);
+        // Each star must be identified, although close doubles (such
+        // as Beta Scorpii) may be identified as either star
+        let identified: Vec<_> = r.stars.iter().filter_map(|s| *s).collect();
+        assert_eq!(identified.len(), stars.len());
+        for (i, s) in identified.iter().zip(stars.iter()) {
+            assert!(catalog[*i].cos_angle_between(&catalog[*s]) > tolerance.cos());
+        }
+        let error = r.attitude.apply3(&center).dot(&attitude.apply3(&center));
+        assert!(error.acos() < tolerance);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_lost_in_space_triangle() -> Result<(), Box<dyn Error>> {
+    let s = std::fs::read_to_string("hipparcos.json")?;
+    let mut catalog: Catalog = serde_json::from_str(&s)?;
+    catalog.retain(move |s, _n| s.brighter_than(4.0));
+    catalog.sort();
+    catalog.add_names(hipparcos::HIP_ALIASES, true)?;
+    catalog.derive_data();
+
+    let fov = 20.0_f64.to_radians();
+    let tolerance = 0.05_f64.to_radians();
+    let center = catalog[catalog.find_name("Betelgeuse").unwrap()].vector;
+    let stars = catalog.find_brightest_around(&center, fov / 2.0, 3);
+
+    // With only three observations the confidence comes from how well
+    // the triangle fits, so it drops as the observation errors grow
+    let confidence = |error: f64| {
+        let observations: Vec<Vec3> = stars
+            .iter()
+            .enumerate()
+            .map(|(n, s)| {
+                let offset: Vec3 = [(n as f64).sin(), (n as f64).cos(), 0.].into();
+                (catalog[*s].vector + offset * error).normalize()
+            })
+            .collect();
+        let r = catalog
+            .lost_in_space(&observations, fov, tolerance)
+            .expect("Should have found the attitude");
+        assert_eq!(r.stars, stars.iter().map(|s| Some(*s)).collect::<Vec<_>>());
+        r.confidence
+    };
+    let exact = confidence(0.);
+    let rough = confidence(tolerance * 0.3);
+    eprintln!("Confidence {exact} exact, {rough} with errors");
+    assert!(exact > 0.9);
+    assert!(rough > 0. && rough < exact);
+    Ok(())
+}
+
+#[test]
+fn test_lost_in_space_truncated() -> Result<(), Box<dyn Error>> {
+    let s = std::fs::read_to_string("hipparcos.json")?;
+    let mut catalog: Catalog = serde_json::from_str(&s)?;
+    catalog.retain(move |s, _n| s.brighter_than(7.0));
+    catalog.sort();
+    catalog.derive_data();
+
+    // With a large tolerance many triangles of stars match, so not all
+    // are verified, and a lone triangle has no confidence
+    let fov = 20.0_f64.to_radians();
+    let tolerance = 1.0_f64.to_radians();
+    let center = Star::vec_of_ra_de(1.5, 0.3);
+    let stars = catalog.find_brightest_around(&center, fov / 2.0, 3);
+    let observations: Vec<Vec3> = stars.iter().map(|s| catalog[*s].vector).collect();
+    let r = catalog
+        .lost_in_space(&observations, fov, tolerance)
+        .expect("Should have found an attitude");
+    assert!(r.truncated);
+    assert_eq!(r.confidence, 0.);
+    Ok(())
+}
@@ -12,6 +12,7 @@ use star_catalog::{Quat, Vec3};
 type Vec2 = geo_nd::FArray<f64, 2>;
 
 #[cfg(test)]
+#[allow(clippy::redundant_static_lifetimes)]
 const EXTRA_ALIASES: &[(usize, &'static str)] = &[
     (61281, "Kappa Draconis"),
     (56211, "Lambda Draconis"),
@@ -0,0 +1,316 @@
+use std::error::Error;
+
+use geo_nd::Vector;
+use star_catalog::minor_bodies::{self, MagnitudeLaw, MinorBody, MinorBodyKind, GAUSSIAN_GRAVITY};
+use star_catalog::solar_system::Observer;
+use star_catalog::time::julian_date;
+use star_catalog::Star;
+
+/// Build a line of fixed columns, with each field starting at a
+/// (zero-based) column
+fn columns(fields: &[(usize, &str)]) -> String {
+    let mut line = vec![b' '; 200];
+    for (start, text) in fields {
+        line[*start..*start + text.len()].copy_from_slice(text.as_bytes());
+    }
+    String::from_utf8(line).unwrap().trim_end().to_string()
+}
+
+/// The elements of comet Encke from Meeus, 'Astronomical Algorithms'
+/// example 33.a
+fn encke() -> MinorBody {
+    let a: f64 = 2.2091404;
+    let e = 0.8502196;
+    MinorBody::new(
+        "2P/Encke",
+        MinorBodyKind::Comet,
+        a * (1.0 - e),
+        e,
+        julian_date(1990, 10, 28.54502),
+    )
+    .with_orientation(
+        11.94524_f64.to_radians(),
+        334.75006_f64.to_radians(),
+        186.23352_f64.to_radians(),
+    )
+}
+
+/// Get the angle in degrees between two unit vectors
+fn angle_between(v0: &star_catalog::Vec3, v1: &star_catalog::Vec3) -> f64 {
+    v0.dot(v1).clamp(-1., 1.).acos().to_degrees()
+}
+
+#[test]
+fn test_encke() -> Result<(), Box<dyn Error>> {
+    // Meeus gives the geometric position for J2000, corrected for
+    // light time, as 158.558965, +19.158496; the distances are
+    // 0.8243 AU from the Earth and 0.6525 AU from the Sun
+    let p = encke().position(julian_date(1990, 10, 6.0), None);
+    let expected = Star::vec_of_ra_de(158.558965_f64.to_radians(), 19.158496_f64.to_radians());
+    let error = angle_between(&p.vector, &expected);
+    assert!(
+        error < 0.02,
+        "Encke at {}, {} is {error} degrees out",
+        p.ra.to_degrees(),
+        p.de.to_degrees()
+    );
+    assert!((p.distance - 0.8243).abs() < 0.001);
+    assert!((p.sun_distance - 0.6525).abs() < 0.001);
+    assert!(p.mag.is_none());
+    // A body without a magnitude is always taken to be bright enough
+    assert!(p.brighter_than(-30.));
+    let p = encke()
+        .with_magnitude(MagnitudeLaw::Comet { h: 11.5, k: 10.0 })
+        .position(julian_date(1990, 10, 6.0), None);
+    let mag = p.mag.unwrap();
+    assert!(p.brighter_than(mag + 0.1));
+    assert!(!p.brighter_than(mag - 0.1));
+
+    let period = encke().period().unwrap();
+    assert!((period / 365.25 - 3.28).abs() < 0.01);
+    Ok(())
+}
+
+#[test]
+fn test_orbits() -> Result<(), Box<dyn Error>> {
+    // Near-parabolic orbits either side of e = 1 give nearly the same
+    // positions as a parabola
+    let t = julian_date(2024, 1, 1.0);
+    let comet = |e: f64| {
+        MinorBody::new("X", MinorBodyKind::Comet, 0.8, e, t).with_orientation(1.0, 0.5, 2.0)
+    };
+    for days in [-200., -30., 0., 10., 100., 1000.] {
+        let parabolic = comet(1.0).heliocentric(t + days);
+        for e in [0.99999, 1.00001] {
+            let v = comet(e).heliocentric(t + days);
+            assert!(
+                (v - parabolic).length() < 1E-3 * parabolic.length(),
+                "e {e} differs from a parabola at {days} days"
+            );
+        }
+        // At perihelion the distance is q, and it increases both
+        // before and after
+        let r = parabolic.length();
+        if days == 0. {
+            assert!((r - 0.8).abs() < 1E-9);
+        } else {
+            assert!(r > 0.8);
+        }
+    }
+
+    // Elliptic orbits given by their mean anomaly repeat with their
+    // period, and are at perihelion with a mean anomaly of zero
+    let a = 2.77;
+    let asteroid = MinorBody::of_mean_anomaly("Y", MinorBodyKind::Asteroid, a, 0.08, 1.0, t);
+    assert!((asteroid.perihelion_time - (t - 1.0 / (GAUSSIAN_GRAVITY / a.powf(1.5)))).abs() < 1E-6);
+    let period = asteroid.period().unwrap();
+    let v0 = asteroid.heliocentric(t);
+    let v1 = asteroid.heliocentric(t + period);
+    assert!((v0 - v1).length() < 1E-9);
+    let q = asteroid.heliocentric(asteroid.perihelion_time).length();
+    assert!((q - a * 0.92).abs() < 1E-9);
+
+    // A track has a position at each step and at its end
+    let track = asteroid.track(t, t + 10.5, 1.0, None);
+    assert_eq!(track.len(), 12);
+    assert_eq!(track[11].jd, t + 10.5);
+    assert!(asteroid.track(t, t + 10.5, 0.0, None).is_empty());
+    assert!(asteroid.track(t, t + 10.5, -1.0, None).is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_topocentric() -> Result<(), Box<dyn Error>> {
+    // A body 0.01 AU away is displaced by the parallax of an observer
+    // by up to about 6378 km / 0.01 AU, or 0.24 degrees
+    let t = julian_date(2024, 3, 20.0);
+    let earth = star_catalog::solar_system::earth_heliocentric(t);
+    let r = earth.length() + 0.01;
+    let body = MinorBody::new("Z", MinorBodyKind::Asteroid, r, 0., t);
+    let body = body.with_orientation(0., 0., earth[1].atan2(earth[0]));
+    let geocentric = body.position(t, None);
+    assert!((geocentric.distance - 0.01).abs() < 1E-4);
+
+    let mut largest: f64 = 0.;
+    for hour in 0..24 {
+        let jd = t + hour as f64 / 24.0;
+        let observer = Observer::new(0., 0., 0.);
+        let topocentric = body.position(jd, Some(&observer));
+        let geocentric = body.position(jd, None);
+        let parallax = angle_between(&topocentric.vector, &geocentric.vector);
+        assert!(parallax < 0.25, "Parallax of {parallax} degrees");
+        largest = largest.max(parallax);
+    }
+    assert!(largest > 0.2, "Largest parallax of {largest} degrees");
+    Ok(())
+}
+
+#[test]
+fn test_magnitudes() -> Result<(), Box<dyn Error>> {
+    let hg = MagnitudeLaw::HG { h: 3.3, g: 0.15 };
+    assert!((hg.magnitude(1., 1., 0.) - 3.3).abs() < 1E-6);
+    assert!((hg.magnitude(2., 1., 0.) - (3.3 + 5.0 * 2_f32.log10())).abs() < 1E-5);
+    // The phase function makes an asteroid fainter away from
+    // opposition
+    assert!(hg.magnitude(1., 1., 0.3) > hg.magnitude(1., 1., 0.1));
+
+    let comet = MagnitudeLaw::Comet { h: 5.5, k: 4.0 };
+    assert!((comet.magnitude(1., 1., 0.5) - 5.5).abs() < 1E-6);
+    assert!((comet.magnitude(10., 1., 0.5) - 15.5).abs() < 1E-5);
+    Ok(())
+}
+
+#[test]
+fn test_read_mpc() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        minor_bodies::unpack_date("K24AH"),
+        Some(julian_date(2024, 10, 17.0))
+    );
+    assert_eq!(
+        minor_bodies::unpack_date("J9611"),
+        Some(julian_date(1996, 1, 1.0))
+    );
+    assert!(minor_bodies::unpack_date("K24D1").is_none());
+
+    let ceres = columns(&[
+        (0, "00001"),
+        (8, " 3.34"),
+        (14, " 0.15"),
+        (20, "K24AH"),
+        (26, "145.84905"),
+        (37, " 73.28579"),
+        (48, " 80.25414"),
+        (59, " 10.58769"),
+        (70, "0.0794013"),
+        (80, " 0.21424651"),
+        (92, "  2.7660512"),
+        (166, "(1) Ceres"),
+    ]);
+    let halley = columns(&[
+        (0, "0001P"),
+        (14, "1986"),
+        (19, "02"),
+        (22, " 5.4682"),
+        (30, " 0.586108"),
+        (41, "0.967321"),
+        (51, "111.8641"),
+        (61, " 59.0870"),
+        (71, "162.2425"),
+        (81, "20140717"),
+        (91, " 4.0"),
+        (96, " 6.0"),
+        (102, "1P/Halley"),
+    ]);
+    let no_name = columns(&[
+        (4, "C"),
+        (5, "K23A030"),
+        (14, "2024"),
+        (19, "09"),
+        (22, "27.7405"),
+        (30, " 0.391425"),
+        (41, "1.000109"),
+        (51, "308.4925"),
+        (61, " 21.5596"),
+        (71, "139.1110"),
+    ]);
+    let file = format!("Header text\n\n-----------\n{ceres}\n\n{halley}\n{no_name}\n");
+    let bodies = minor_bodies::read_mpc(&file)?;
+    assert_eq!(bodies.len(), 3);
+
+    assert_eq!(bodies[0].designation, "(1) Ceres");
+    assert_eq!(bodies[0].kind, MinorBodyKind::Asteroid);
+    assert_eq!(bodies[0].epoch, julian_date(2024, 10, 17.0));
+    assert_eq!(
+        bodies[0].magnitude,
+        Some(MagnitudeLaw::HG { h: 3.34, g: 0.15 })
+    );
+    let expected = MinorBody::of_mean_anomaly(
+        "",
+        MinorBodyKind::Asteroid,
+        2.7660512,
+        0.0794013,
+        145.84905_f64.to_radians(),
+        julian_date(2024, 10, 17.0),
+    )
+    .with_orientation(
+        10.58769_f64.to_radians(),
+        80.25414_f64.to_radians(),
+        73.28579_f64.to_radians(),
+    );
+    let t = julian_date(2025, 1, 1.0);
+    assert!((bodies[0].heliocentric(t) - expected.heliocentric(t)).length() < 1E-12);
+    // The daily motion in the file matches that of the Gaussian
+    // constant
+    assert!((bodies[0].period().unwrap() - 360.0 / 0.21424651).abs() < 0.1);
+
+    assert_eq!(bodies[1].designation, "1P/Halley");
+    assert_eq!(bodies[1].kind, MinorBodyKind::Comet);
+    assert_eq!(bodies[1].epoch, julian_date(2014, 7, 17.0));
+    assert_eq!(
+        bodies[1].magnitude,
+        Some(MagnitudeLaw::Comet { h: 4.0, k: 6.0 })
+    );
+    assert!((bodies[1].period().unwrap() / 365.25 - 75.3).abs() < 1.0);
+
+    assert_eq!(bodies[2].designation, "CK23A030");
+    assert!(bodies[2].magnitude.is_none());
+    assert!(bodies[2].semi_major_axis().is_none());
+    let p = bodies[2].position(julian_date(2024, 9, 27.7405), None);
+    assert!((p.sun_distance - 0.391425).abs() < 0.001);
+
+    let bad = format!("{ceres}\n{}\n", &halley[0..40]);
+    assert!(matches!(
+        minor_bodies::read_mpc(&bad),
+        Err(star_catalog::Error::BadMpcLine(2))
+    ));
+
+    // The minor bodies around a position are found
+    let ceres_now = bodies[0].position(t, None);
+    let found = minor_bodies::positions_around(&bodies, t, None, &ceres_now.vector, 0.01);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].0, 0);
+    let star = found[0].1.to_star(7);
+    assert_eq!(star.id, 7);
+    assert!(star.mag > 6.0 && star.mag < 10.0);
+    Ok(())
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn test_draw_track() -> Result<(), Box<dyn Error>> {
+    use geo_nd::Quaternion;
+    use image::GenericImageView;
+    use star_catalog::{ImageView, Quat, TrackStyle};
+
+    let t = julian_date(1990, 10, 6.0);
+    let track: Vec<_> = encke()
+        .track(t, t + 4.0, 1.0, None)
+        .into_iter()
+        .map(|p| p.vector)
+        .collect();
+    let center = (track[0] + track[4]).normalize();
+    let style = TrackStyle {
+        color: [0, 255, 0, 255].into(),
+        label: true,
+        label_color: [255, 0, 0, 255].into(),
+        ..Default::default()
+    };
+    let mut image_view = ImageView::new(image::DynamicImage::new_rgb8(400, 400));
+    image_view
+        .set_tan_hfov(0.1)
+        .set_orient(Quat::look_at(&center, &[0., 0., 1.].into()));
+    image_view.draw_track(&track, "2P", &style);
+    let image = image_view.take_image();
+    let mut line = 0;
+    let mut label = 0;
+    for (_, _, p) in image.pixels() {
+        if p[1] == 255 {
+            line += 1;
+        } else if p[0] == 255 {
+            label += 1;
+        }
+    }
+    assert!(line > 200, "{line} pixels drawn for the track");
+    assert!(label > 10, "{label} pixels drawn for the label");
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use std::error::Error;
+
+use star_catalog::{Catalog, PairIndex};
+
+#[test]
+fn test_pair_index() -> Result<(), Box<dyn Error>> {
+    let s = std::fs::read_to_string("hipparcos.json")?;
+    let mut catalog: Catalog = serde_json::from_str(&s)?;
+    catalog.retain(move |s, _n| s.brighter_than(6.0));
+    catalog.sort();
+    catalog.derive_data();
+
+    let max_angle = 8.0_f64.to_radians();
+    let index = PairIndex::new(&catalog, max_angle, 5.0);
+    eprintln!("Index has {} pairs", index.len());
+
+    // Compare with all the pairs of the catalog
+    let stars: Vec<_> = catalog.iter_stars().filter(|s| s.mag < 5.0).collect();
+    let mut angles = vec![];
+    for (n, s0) in stars.iter().enumerate() {
+        for s1 in stars[n + 1..].iter() {
+            let angle = s0.cos_angle_between(s1).clamp(-1., 1.).acos() as f32;
+            if (angle as f64) <= max_angle {
+                angles.push(angle);
+            }
+        }
+    }
+    assert_eq!(index.len(), angles.len());
+
+    let json = serde_json::to_string(&index)?;
+    let index: PairIndex = serde_json::from_str(&json)?;
+
+    for (min, max) in [
+        (0.0, 0.5),
+        (1.0, 1.1),
+        (3.3, 3.3001),
+        (7.9, 9.0),
+        (2.0, 1.0),
+    ] {
+        let (min, max) = (f64::to_radians(min), f64::to_radians(max));
+        let pairs = index.pairs_with_angle(min, max);
+        let expected = angles
+            .iter()
+            .filter(|a| (**a as f64) >= min && (**a as f64) <= max)
+            .count();
+        assert_eq!(pairs.len(), expected);
+        for p in pairs {
+            assert!(p.ids[0] < p.ids[1]);
+            assert!((p.angle as f64) >= min && (p.angle as f64) <= max);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use std::error::Error;
+
+use geo_nd::{Vector, Vector3};
+
+use star_catalog::{Catalog, QuadIndex, Vec3};
+
+/// Project a star vector onto an image plane with a focal length (in
+/// pixels) looking along a direction, with an image rotation
+fn project(v: &Vec3, center: &Vec3, focal_length: f64, rotation: f64) -> [f64; 2] {
+    let up: Vec3 = [0., 0., 1.].into();
+    let x_axis = up.cross_product(center).normalize();
+    let y_axis = center.cross_product(&x_axis);
+    let z = v.dot(center);
+    let (x, y) = (v.dot(&x_axis) / z, v.dot(&y_axis) / z);
+    let (s, c) = rotation.sin_cos();
+    [
+        focal_length * (x * c - y * s) + 1000.,
+        focal_length * (x * s + y * c) + 700.,
+    ]
+}
+
+#[test]
+fn test_quad_index() -> Result<(), Box<dyn Error>> {
+    let s = std::fs::read_to_string("hipparcos.json")?;
+    let mut catalog: Catalog = serde_json::from_str(&s)?;
+    catalog.retain(move |s, _n| s.brighter_than(6.5));
+    catalog.sort();
+    catalog.derive_data();
+
+    let index = QuadIndex::new(
+        &catalog,
+        3,
+        12,
+        4,
+        3.0_f64.to_radians(),
+        10.0_f64.to_radians(),
+    );
+    eprintln!("Index has {} quads", index.len());
+    assert!(index.len() > 768);
+
+    // Every region has a magnitude limit
+    let v: Vec3 = [0., 0.6, 0.8].into();
+    assert!(index.region_limit(&v).is_some());
+
+    let json = serde_json::to_string(&index)?;
+    let index: QuadIndex = serde_json::from_str(&json)?;
+
+    let focal_length = 2500.0;
+    for (n, ids) in index.iter_quads().step_by(97).enumerate() {
+        let stars = ids.map(|id| catalog.find_sorted(id).unwrap());
+        let center = (catalog[stars[0]].vector + catalog[stars[1]].vector).normalize();
+        // Present the points in a different order, rotated, and
+        // (half the time) reflected
+        let order = [2, 0, 3, 1];
+        let mut points =
+            order.map(|i| project(&catalog[stars[i]].vector, &center, focal_length, n as f64));
+        if n % 2 == 1 {
+            for p in points.iter_mut() {
+                p[0] = -p[0];
+            }
+        }
+        let matches = index.find_matches(&points, 0.01);
+        let m = matches
+            .iter()
+            .find(|m| m.ids == order.map(|i| ids[i]))
+            .expect("Should have found the quad");
+        assert!((m.plate_scale * focal_length - 1.0).abs() < 0.01);
+    }
+    Ok(())
+}
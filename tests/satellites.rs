@@ -0,0 +1,294 @@
+use std::error::Error;
+
+use geo_nd::{Quaternion, Vector};
+use star_catalog::satellites::{self, Satellite, Tle};
+use star_catalog::solar_system::Observer;
+use star_catalog::time::{self, julian_date};
+use star_catalog::{FieldOfView, Quat, Vec3};
+
+/// The test elements of Vallado et al, 'Revisiting Spacetrack Report
+/// #3', for satellite 00005 (Vanguard 1)
+const VANGUARD_1: &str = "\
+1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753
+2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667";
+
+/// Elements for the International Space Station
+const ISS: &str = "\
+ISS (ZARYA)
+1 25544U 98067A   24292.51782528  .00018711  00000-0  33229-3 0  9997
+2 25544  51.6397  78.3386 0009177 105.6129 254.5927 15.50064436477709";
+
+/// Replace the checksum of a line of elements with the correct one
+fn with_checksum(line: &str) -> String {
+    let sum: u32 = line[0..68]
+        .chars()
+        .map(|c| match c {
+            '-' => 1,
+            _ => c.to_digit(10).unwrap_or(0),
+        })
+        .sum();
+    format!("{}{}", &line[0..68], sum % 10)
+}
+
+/// Check a vector against expected values to within a tolerance
+fn check_vec(v: &Vec3, expected: [f64; 3], tolerance: f64, what: &str) {
+    let error = (*v - Vec3::from(expected)).length();
+    assert!(
+        error < tolerance,
+        "{what} {v:?} is {error} from {expected:?}"
+    );
+}
+
+/// Get the angle in degrees between two unit vectors
+fn angle_between(v0: &Vec3, v1: &Vec3) -> f64 {
+    v0.dot(v1).clamp(-1., 1.).acos().to_degrees()
+}
+
+#[test]
+fn test_sgp4() -> Result<(), Box<dyn Error>> {
+    let tle = &satellites::read_tles(VANGUARD_1)?[0];
+    let satellite = Satellite::new(tle.clone());
+
+    // The positions (km) and velocities (km/s) given by Vallado for
+    // the epoch and six hours later
+    let (r, v) = satellite.teme(tle.epoch).unwrap();
+    check_vec(&r, [7022.46529266, -1400.08296755, 0.03995155], 1E-3, "r");
+    check_vec(&v, [1.893841015, 6.405893759, 4.534807250], 1E-6, "v");
+    let (r, v) = satellite.teme(tle.epoch + 0.25).unwrap();
+    check_vec(
+        &r,
+        [-7154.03120202, -3783.17682504, -3536.19412294],
+        1E-3,
+        "r",
+    );
+    check_vec(&v, [4.741887409, -4.151817765, -2.093935425], 1E-6, "v");
+
+    // The position is geocentric without an observer, and the orbit
+    // does not go inside the Earth
+    let p = satellite.position(tle.epoch, None).unwrap();
+    let (r, _) = satellite.teme(tle.epoch).unwrap();
+    assert!((p.distance - r.length()).abs() < 1E-9);
+    assert!(p.is_above_horizon());
+    for p in satellite.track(tle.epoch, tle.epoch + 1.0, 0.01, None) {
+        assert!(p.distance > 6378.0 + 600.0);
+        assert!((p.vector.length() - 1.0).abs() < 1E-9);
+    }
+    assert!(satellite
+        .track(tle.epoch, tle.epoch + 1.0, 0.0, None)
+        .is_empty());
+    Ok(())
+}
+
+/// Elements for a geostationary satellite (the ISS elements with the
+/// mean motion, eccentricity and inclination of one)
+fn geostationary() -> String {
+    let lines: Vec<&str> = ISS.lines().collect();
+    let line2 = lines[2]
+        .replace(" 51.6397 ", "  0.0300 ")
+        .replace(" 0009177 ", " 0002000 ")
+        .replace("15.50064436", " 1.00271000");
+    format!("GEO\n{}\n{}\n", lines[1], with_checksum(&line2))
+}
+
+#[test]
+fn test_tles() -> Result<(), Box<dyn Error>> {
+    let tles = satellites::read_tles(&format!("{ISS}\n\n0 VANGUARD 1\n{VANGUARD_1}\n"))?;
+    assert_eq!(tles.len(), 2);
+    let iss = &tles[0];
+    assert_eq!(iss.name, "ISS (ZARYA)");
+    assert_eq!(iss.catalog_number, 25544);
+    assert_eq!(iss.designator, "98067A");
+    assert!((iss.epoch - (julian_date(2024, 1, 0.0) + 292.51782528)).abs() < 1E-9);
+    assert!((iss.bstar - 0.33229E-3).abs() < 1E-12);
+    assert!((iss.inclination.to_degrees() - 51.6397).abs() < 1E-9);
+    assert!((iss.eccentricity - 0.0009177).abs() < 1E-12);
+    assert!((iss.period() - 92.9).abs() < 0.1);
+    assert!(!iss.is_deep_space());
+    // Vanguard 1 has a period of 133 minutes
+    assert!(!tles[1].is_deep_space());
+    let geo = &satellites::read_tles(&geostationary())?[0];
+    assert!((geo.period() - 1436.1).abs() < 0.1);
+    assert!(geo.is_deep_space());
+    assert_eq!(tles[1].name, "VANGUARD 1");
+    // The year 00 of Vanguard's elements is 2000, 58 would be 1958
+    assert!((tles[1].epoch - julian_date(2000, 6, 27.78495062)).abs() < 1E-6);
+
+    // Without a name, the international designator is used
+    let lines: Vec<&str> = VANGUARD_1.lines().collect();
+    let tle = Tle::parse(None, lines[0], lines[1]).unwrap();
+    assert_eq!(tle.name, "58002B");
+
+    // Bad checksums or mismatched catalog numbers are rejected
+    let bad = lines[0].replace("4753", "4754");
+    assert!(Tle::parse(None, &bad, lines[1]).is_none());
+    let other = with_checksum(&lines[1].replace("2 00005", "2 00006"));
+    assert!(Tle::parse(None, lines[0], &other).is_none());
+
+    // Alpha-5 catalog numbers have a letter for the ten-thousands
+    // (skipping I and O)
+    let line1 = with_checksum(&lines[0].replace("1 00005", "1 A0005"));
+    let line2 = with_checksum(&lines[1].replace("2 00005", "2 A0005"));
+    assert_eq!(
+        Tle::parse(None, &line1, &line2).unwrap().catalog_number,
+        100005
+    );
+    let line1 = with_checksum(&lines[0].replace("1 00005", "1 P1234"));
+    let line2 = with_checksum(&lines[1].replace("2 00005", "2 P1234"));
+    assert_eq!(
+        Tle::parse(None, &line1, &line2).unwrap().catalog_number,
+        231234
+    );
+
+    // Errors report the line of the file
+    let file = format!("{ISS}\n{bad}\n{}\n", lines[1]);
+    assert!(matches!(
+        satellites::read_tles(&file),
+        Err(star_catalog::Error::BadTleLine(4))
+    ));
+    let file = format!("{ISS}\nNAME\nOTHER NAME\n");
+    assert!(matches!(
+        satellites::read_tles(&file),
+        Err(star_catalog::Error::BadTleLine(4))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_topocentric() -> Result<(), Box<dyn Error>> {
+    let satellite = satellites::read_satellites(ISS)?.remove(0);
+    let epoch = satellite.tle().epoch;
+    let observer = Observer::new(51.5_f64.to_radians(), 0., 0.);
+
+    // Over a day the station is seen above the horizon some of the
+    // time, and is then within about 2500 km of the observer
+    let track = satellite.track(epoch, epoch + 1.0, 10.0 / 86400.0, Some(&observer));
+    let visible: Vec<_> = track.iter().filter(|p| p.is_above_horizon()).collect();
+    assert!(!visible.is_empty());
+    assert!(visible.len() < track.len() / 4);
+    for p in &visible {
+        assert!(p.distance > 400.0 && p.distance < 2600.0, "{}", p.distance);
+    }
+    let highest = visible
+        .iter()
+        .map(|p| p.elevation.unwrap())
+        .fold(0., f64::max);
+    assert!(highest > 10_f64.to_radians());
+
+    // The station spends some of each orbit in the shadow of the
+    // Earth
+    let sunlit = track.iter().filter(|p| p.sunlit).count();
+    assert!(sunlit > track.len() / 2 && sunlit < track.len());
+
+    // The passes are the runs of the track above the horizon
+    let passes = satellite.passes(epoch, epoch + 1.0, 10.0 / 86400.0, &observer);
+    assert!(passes.len() >= 2, "{} passes", passes.len());
+    assert_eq!(passes.iter().map(|p| p.len()).sum::<usize>(), visible.len());
+    for pass in &passes {
+        assert!(pass.iter().all(|p| p.is_above_horizon()));
+        for w in pass.windows(2) {
+            assert!((w[1].jd - w[0].jd - 10.0 / 86400.0).abs() < 1E-9);
+        }
+    }
+
+    // Near the zenith the topocentric direction differs greatly from
+    // the geocentric direction
+    let p = visible
+        .iter()
+        .max_by(|a, b| a.elevation.partial_cmp(&b.elevation).unwrap())
+        .unwrap();
+    let geocentric = satellite.position(p.jd, None).unwrap();
+    assert!(angle_between(&p.vector, &geocentric.vector) > 5.0);
+    Ok(())
+}
+
+#[test]
+fn test_crossings() -> Result<(), Box<dyn Error>> {
+    let satellites = satellites::read_satellites(&format!("{ISS}\n{VANGUARD_1}\n"))?;
+    let epoch = satellites[0].tle().epoch;
+    let observer = Observer::new(51.5_f64.to_radians(), 0., 0.);
+    let step = 10.0 / 86400.0;
+
+    // Point a field of view at the station at its highest during a
+    // pass
+    let track = satellites[0].track(epoch, epoch + 1.0, step, Some(&observer));
+    let p = track
+        .iter()
+        .max_by(|a, b| a.elevation.partial_cmp(&b.elevation).unwrap())
+        .unwrap();
+    let orient = Quat::look_at(&p.vector, &[0., 0., 1.].into());
+    let fov = FieldOfView::Rectangular(5_f64.to_radians(), 3_f64.to_radians());
+    assert!(fov.contains(&orient, &p.vector));
+
+    // Over ten minutes around the time it crosses the field once
+    let (start, end) = (p.jd - 300.0 / 86400.0, p.jd + 300.0 / 86400.0);
+    let found = satellites::crossings(&satellites, &observer, &fov, &orient, start, end, step);
+    let iss: Vec<_> = found.iter().filter(|c| c.satellite == 0).collect();
+    assert_eq!(iss.len(), 1);
+    let crossing = iss[0];
+    assert!(crossing.entry() <= p.jd && p.jd <= crossing.exit());
+    // Refined to a fraction of the field of view, so there are
+    // several positions even though the crossing is brief
+    assert!(crossing.positions.len() > 4);
+    assert!(crossing.exit() - crossing.entry() < 60.0 / 86400.0);
+    for p in &crossing.positions {
+        assert!(fov.contains(&orient, &p.vector));
+    }
+
+    // The field of view at the same place an hour later is not
+    // crossed by the station
+    let found = satellites::crossings(
+        &satellites,
+        &observer,
+        &fov,
+        &orient,
+        start + 1.0 / 24.0,
+        end + 1.0 / 24.0,
+        step,
+    );
+    assert!(found.iter().all(|c| c.satellite != 0));
+
+    // A deep-space satellite cannot be propagated accurately, so it is
+    // skipped even with the field of view pointed at it
+    let satellites = satellites::read_satellites(&geostationary())?;
+    assert!(satellites[0].is_deep_space());
+    let (r, _) = satellites[0].teme(start).unwrap();
+    let longitude = r[1].atan2(r[0]) - time::gmst(start);
+    let observer = Observer::new(0., longitude, 0.);
+    let p = satellites[0].position(start, Some(&observer)).unwrap();
+    assert!(p.elevation.unwrap() > 80_f64.to_radians());
+    let orient = Quat::look_at(&p.vector, &[0., 0., 1.].into());
+    assert!(fov.contains(&orient, &p.vector));
+    let found = satellites::crossings(&satellites, &observer, &fov, &orient, start, end, step);
+    assert!(found.is_empty());
+    Ok(())
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn test_draw_satellite_track() -> Result<(), Box<dyn Error>> {
+    use image::GenericImageView;
+    use star_catalog::{ImageView, TrackStyle};
+
+    let satellite = satellites::read_satellites(ISS)?.remove(0);
+    let epoch = satellite.tle().epoch;
+    let track: Vec<_> = satellite
+        .track(epoch, epoch + 5.0 / 1440.0, 10.0 / 86400.0, None)
+        .into_iter()
+        .map(|p| p.vector)
+        .collect();
+    let center = (track[0] + track[track.len() - 1]).normalize();
+    let style = TrackStyle {
+        color: [0, 255, 0, 255].into(),
+        dot_radius: 1,
+        ..Default::default()
+    };
+    let mut image_view = ImageView::new(image::DynamicImage::new_rgb8(400, 400));
+    image_view
+        .set_tan_hfov(0.5)
+        .set_orient(Quat::look_at(&center, &[0., 0., 1.].into()));
+    image_view.draw_track(&track, "ISS", &style);
+    let image = image_view.take_image();
+    let line = image.pixels().filter(|(_, _, p)| p[1] == 255).count();
+    assert!(line > 200, "{line} pixels drawn for the track");
+    Ok(())
+}
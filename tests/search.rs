@@ -1,9 +1,11 @@
 use std::error::Error;
 
-use star_catalog::Subcube;
-use star_catalog::{hipparcos, Catalog};
+use geo_nd::Vector;
+
+use star_catalog::{hipparcos, Catalog, CatalogIndex, Star, StarFilter, SubcubeIndex};
 
 #[test]
+#[allow(clippy::needless_range_loop)]
 fn test_find_stars() -> Result<(), Box<dyn Error>> {
     // Probably we should be testing with mag 5; we can test with 7 though
     let magnitude = 5.0;
@@ -28,7 +30,7 @@ fn test_find_stars() -> Result<(), Box<dyn Error>> {
     // let max_angle_delta = 0.15 / 180.0 * std::f64::consts::PI;
     let max_angle_delta = 0.06 / 180.0 * std::f64::consts::PI;
 
-    let subcube_iter = Subcube::iter_all();
+    let subcube_iter = catalog.iter_subcubes();
     let r = catalog.find_star_triangles(subcube_iter, &angles_to_find, max_angle_delta);
     let mut errs = 0;
     for (a, b, c) in &r {
@@ -52,3 +54,346 @@ fn test_find_stars() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[test]
+fn test_find_brightest() -> Result<(), Box<dyn Error>> {
+    let s = std::fs::read_to_string("hipparcos.json")?;
+    let mut catalog: Catalog = serde_json::from_str(&s)?;
+    catalog.retain(move |s, _n| s.brighter_than(7.0));
+    catalog.sort();
+    catalog.add_names(hipparcos::HIP_ALIASES, true)?;
+    catalog.derive_data();
+
+    let dubhe = catalog.find_name("Dubhe").unwrap();
+    let v = catalog[dubhe].vector;
+    let angle = 20.0_f64.to_radians();
+
+    let mut all = catalog.find_stars_around(&v, angle);
+    all.sort_by(|a, b| catalog[*a].mag.total_cmp(&catalog[*b].mag));
+
+    let brightest = catalog.find_brightest_around(&v, angle, 10);
+    assert_eq!(brightest.len(), 10);
+    for (b, a) in brightest.iter().zip(all.iter()) {
+        assert_eq!(catalog[*b].mag, catalog[*a].mag);
+    }
+
+    let brighter = catalog.find_stars_around_brighter_than(&v, angle, 4.0);
+    let expected = all.iter().filter(|i| catalog[**i].mag < 4.0).count();
+    assert_eq!(brighter.len(), expected);
+    for w in brighter.windows(2) {
+        assert!(catalog[w[0]].mag <= catalog[w[1]].mag);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_subcube_resolution() -> Result<(), Box<dyn Error>> {
+    let s = std::fs::read_to_string("hipparcos.json")?;
+    let mut catalog: Catalog = serde_json::from_str(&s)?;
+    catalog.retain(move |s, _n| s.brighter_than(6.0));
+    catalog.sort();
+    catalog.add_names(hipparcos::HIP_ALIASES, true)?;
+
+    let mut angles_to_find = [28.71, 10.22, 33.58];
+    for a in angles_to_find.iter_mut() {
+        *a = *a / 180.0 * std::f64::consts::PI;
+    }
+    let max_angle_delta = 0.06 / 180.0 * std::f64::consts::PI;
+
+    let mut results = vec![];
+    for ele_per_side in [None, Some(8), Some(32)] {
+        catalog.set_subcube_ele_per_side(ele_per_side);
+        catalog.derive_data();
+        if let Some(e) = ele_per_side {
+            assert_eq!(catalog.subcube_index().ele_per_side(), e);
+        }
+        let dubhe = catalog.find_name("Dubhe").unwrap();
+        let sub = catalog.subcube_of_vector(&catalog[dubhe].vector);
+        assert!(catalog[sub].contains(&dubhe));
+        let mut around = catalog.find_stars_around(&catalog[dubhe].vector, 0.4);
+        around.sort();
+        let mut triangles =
+            catalog.find_star_triangles(catalog.iter_subcubes(), &angles_to_find, max_angle_delta);
+        triangles.sort();
+        results.push((around, triangles));
+    }
+    assert!(!results[0].1.is_empty());
+    assert_eq!(results[0], results[1]);
+    assert_eq!(results[0], results[2]);
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "not from the catalog's subcubes")]
+fn test_subcube_of_other_resolution() {
+    let mut catalog = Catalog::default();
+    catalog.add_star(Star::new(1, 0.1, 0.2, 10., 2.0, 0.));
+    catalog.sort();
+    catalog.set_subcube_ele_per_side(Some(8));
+    catalog.derive_data();
+    // A subcube of the default resolution cannot index the catalog
+    let sub = SubcubeIndex::default().of_vector(&catalog[CatalogIndex::from(0)].vector);
+    let _ = &catalog[sub];
+}
+
+#[test]
+fn test_subcube_storage() -> Result<(), Box<dyn Error>> {
+    let s = std::fs::read_to_string("hipparcos.json")?;
+    let mut catalog: Catalog = serde_json::from_str(&s)?;
+    catalog.sort();
+    catalog.derive_data();
+
+    let mut seen = vec![false; catalog.len()];
+    for sub in catalog.iter_subcubes() {
+        assert!(sub.may_be_on_sphere());
+        assert!(!catalog[sub].is_empty());
+        for index in catalog[sub].iter() {
+            assert!(!seen[index.as_usize()]);
+            seen[index.as_usize()] = true;
+            assert_eq!(catalog[*index].subcube.as_usize(), sub.as_usize());
+        }
+    }
+    assert!(seen.iter().all(|s| *s));
+
+    for (ra, de) in [(0.1, 0.2), (3.0, -1.2), (5.5, 1.5)] {
+        let v = Star::vec_of_ra_de(ra, de);
+        let (_, closest) = catalog.closest_to(ra, de).unwrap();
+        let best = catalog
+            .iter_stars()
+            .map(|s| s.vector.dot(&v))
+            .fold(-1.0, f64::max);
+        assert_eq!(catalog[closest].vector.dot(&v), best);
+    }
+    Ok(())
+}
+
+#[test]
+#[ignore = "scans the whole catalog for 200 cone searches; slow without --release"]
+fn test_scan_stars() -> Result<(), Box<dyn Error>> {
+    // Cone searches using the catalog (which scans its star vector
+    // arrays) must find the same stars as scanning the Star records
+    // of the same subcubes
+    let s = std::fs::read_to_string("hipparcos.json")?;
+    let mut catalog: Catalog = serde_json::from_str(&s)?;
+    catalog.sort();
+    catalog.derive_data();
+
+    let angle = 10.0_f64.to_radians();
+    let max_cos = angle.cos();
+    let subcube_cos = (angle + catalog.subcube_index().subcube_max_angle()).cos();
+    let centers: Vec<_> = (0..200)
+        .map(|i| {
+            let i = i as f64;
+            Star::vec_of_ra_de(i * 0.7, (i * 0.37).sin() * 1.5)
+        })
+        .collect();
+
+    for v in centers.iter() {
+        let mut found = catalog.find_stars_around(v, angle);
+        found.sort_by_key(|i| i.as_usize());
+
+        let mut expected = vec![];
+        for sub in catalog
+            .iter_subcubes()
+            .filter(|sub| sub.cos_angle_on_sphere(v).is_some_and(|c| c >= subcube_cos))
+        {
+            for i in catalog[sub].iter() {
+                if catalog[*i].vector.dot(v) >= max_cos {
+                    expected.push(*i);
+                }
+            }
+        }
+        expected.sort_by_key(|i| i.as_usize());
+        assert_eq!(found, expected);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_wide_triangles() -> Result<(), Box<dyn Error>> {
+    let s = std::fs::read_to_string("hipparcos.json")?;
+    let mut catalog: Catalog = serde_json::from_str(&s)?;
+    catalog.retain(move |s, _n| s.brighter_than(3.5));
+    catalog.sort();
+    catalog.derive_data();
+
+    let n = catalog.len();
+    let angle = |a: usize, b: usize| {
+        catalog[CatalogIndex::from(a)]
+            .cos_angle_between(&catalog[CatalogIndex::from(b)])
+            .clamp(-1., 1.)
+            .acos()
+    };
+
+    // Use the most nearly antipodal pair of stars, with the third
+    // star of the triangle being a few different stars
+    let mut far = (0, 1);
+    for a in 0..n {
+        for b in (a + 1)..n {
+            if angle(a, b) > angle(far.0, far.1) {
+                far = (a, b);
+            }
+        }
+    }
+    assert!(angle(far.0, far.1) > 170.0_f64.to_radians());
+
+    let max_angle_delta = 0.2_f64.to_radians();
+    for c in [0, n / 3, n / 2] {
+        if c == far.0 || c == far.1 {
+            continue;
+        }
+        let angles_to_find = [angle(far.0, far.1), angle(far.0, c), angle(far.1, c)];
+        let mut found: Vec<_> = catalog
+            .find_star_triangles(catalog.iter_subcubes(), &angles_to_find, max_angle_delta)
+            .into_iter()
+            .map(|(a, b, c)| (a.as_usize(), b.as_usize(), c.as_usize()))
+            .collect();
+        found.sort();
+
+        let mut expected = vec![];
+        for a in 0..n {
+            for b in 0..n {
+                if a == b || (angle(a, b) - angles_to_find[0]).abs() > max_angle_delta {
+                    continue;
+                }
+                for c in 0..n {
+                    if a == c || b == c {
+                        continue;
+                    }
+                    if (angle(a, c) - angles_to_find[1]).abs() > max_angle_delta {
+                        continue;
+                    }
+                    if (angle(b, c) - angles_to_find[2]).abs() > max_angle_delta {
+                        continue;
+                    }
+                    expected.push((a, b, c));
+                }
+            }
+        }
+        eprintln!("{:?} : {} {}", angles_to_find, found.len(), expected.len());
+        assert!(found.contains(&(far.0, far.1, c)));
+        assert_eq!(found, expected);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_triangle_matches() -> Result<(), Box<dyn Error>> {
+    let s = std::fs::read_to_string("hipparcos.json")?;
+    let mut catalog: Catalog = serde_json::from_str(&s)?;
+    catalog.retain(move |s, _n| s.brighter_than(5.0));
+    catalog.sort();
+    catalog.derive_data();
+
+    // With three equal angles every triangle is found in all six orders
+    let angles_to_find = [20.0_f64.to_radians(); 3];
+    let max_angle_delta = 0.2_f64.to_radians();
+    let all = catalog.find_star_triangles_ranked(
+        catalog.iter_subcubes(),
+        &angles_to_find,
+        max_angle_delta,
+        false,
+    );
+    let dedup = catalog.find_star_triangles_ranked(
+        catalog.iter_subcubes(),
+        &angles_to_find,
+        max_angle_delta,
+        true,
+    );
+    assert!(!dedup.is_empty());
+    assert_eq!(all.len(), dedup.len() * 6);
+
+    let mut star_sets: Vec<_> = dedup
+        .iter()
+        .map(|m| {
+            let mut s = m.stars;
+            s.sort();
+            s
+        })
+        .collect();
+    star_sets.sort();
+    star_sets.dedup();
+    assert_eq!(star_sets.len(), dedup.len());
+
+    for w in all.windows(2) {
+        assert!(w[0].score <= w[1].score);
+    }
+    for m in all.iter() {
+        for r in m.residuals {
+            assert!(r.abs() <= max_angle_delta + 1.0E-9);
+        }
+    }
+
+    // The callback can stop the search early
+    let mut count = 0;
+    catalog.find_star_triangles_with(
+        catalog.iter_subcubes(),
+        &angles_to_find,
+        max_angle_delta,
+        false,
+        |_| {
+            count += 1;
+            count < 3
+        },
+    );
+    assert_eq!(count, 3);
+
+    // The filter applies to all three stars
+    catalog.set_filter(StarFilter::brighter_than(4.0));
+    let bright = catalog.find_star_triangles_ranked(
+        catalog.iter_subcubes(),
+        &angles_to_find,
+        max_angle_delta,
+        true,
+    );
+    let expected = dedup
+        .iter()
+        .filter(|m| m.stars.iter().all(|s| catalog[*s].mag < 4.0))
+        .count();
+    assert_eq!(bright.len(), expected);
+    Ok(())
+}
+
+#[test]
+fn test_triangle_uniqueness() -> Result<(), Box<dyn Error>> {
+    let s = std::fs::read_to_string("hipparcos.json")?;
+    let mut catalog: Catalog = serde_json::from_str(&s)?;
+    catalog.retain(move |s, _n| s.brighter_than(5.0));
+    catalog.sort();
+    catalog.add_names(hipparcos::HIP_ALIASES, true)?;
+    catalog.derive_data();
+
+    let stars: Vec<CatalogIndex> = ["Dubhe", "Megrez", "Alkaid", "Mizar"]
+        .iter()
+        .map(|n| catalog.find_name(n).expect("Star should be named"))
+        .collect();
+    let max_angle_delta = 0.1_f64.to_radians();
+    let r = catalog.triangle_uniqueness(&stars, max_angle_delta, 3);
+
+    // One result for each of the four triangles of the four stars
+    assert_eq!(r.len(), 4);
+    for t in r.iter() {
+        assert!(t.nearest.len() <= 3);
+        assert!(t.nearest.len() <= t.num_competing);
+        assert_eq!(t.is_unique(), t.num_competing == 0);
+        let mut own = t.stars;
+        own.sort();
+        for m in t.nearest.iter() {
+            let mut other = m.stars;
+            other.sort();
+            assert_ne!(own, other);
+            for r in m.residuals {
+                assert!(r.abs() <= max_angle_delta + 1.0E-9);
+            }
+        }
+        for w in t.nearest.windows(2) {
+            assert!(w[0].score <= w[1].score);
+        }
+    }
+
+    // Fewer than three stars have no triangles
+    assert!(catalog
+        .triangle_uniqueness(&stars[0..2], max_angle_delta, 3)
+        .is_empty());
+    Ok(())
+}
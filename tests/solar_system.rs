@@ -0,0 +1,153 @@
+use std::error::Error;
+
+use geo_nd::Vector;
+use star_catalog::solar_system::{self, Body};
+use star_catalog::time::julian_date;
+use star_catalog::Star;
+
+/// Check a position in degrees to within a tolerance in degrees
+fn check_ra_de(body: Body, jd: f64, ra: f64, de: f64, tolerance: f64) {
+    let p = body.position(jd);
+    let expected = Star::vec_of_ra_de(ra.to_radians(), de.to_radians());
+    let error = p.vector.dot(&expected).clamp(-1., 1.).acos().to_degrees();
+    assert!(
+        error < tolerance,
+        "{body:?} at {}, {} is {error} degrees from {ra}, {de}",
+        p.ra.to_degrees(),
+        p.de.to_degrees()
+    );
+}
+
+#[test]
+fn test_positions() -> Result<(), Box<dyn Error>> {
+    // The examples of Meeus, 'Astronomical Algorithms', precessed from
+    // the apparent place of date to J2000
+    check_ra_de(Body::Sun, julian_date(1992, 10, 13.0), 198.47, -7.82, 0.02);
+    check_ra_de(
+        Body::Venus,
+        julian_date(1992, 12, 20.0),
+        316.27,
+        -18.86,
+        0.02,
+    );
+    check_ra_de(Body::Moon, julian_date(1992, 4, 12.0), 134.79, 13.74, 0.02);
+    // The Sun at J2000.0
+    check_ra_de(Body::Sun, julian_date(2000, 1, 1.5), 281.29, -23.03, 0.02);
+
+    let moon = Body::Moon.position(julian_date(1992, 4, 12.0));
+    assert!((moon.distance * 149_597_870.7 - 368409.7).abs() < 100.);
+
+    // Meeus example 41.a gives an illuminated fraction of 0.647
+    let venus = Body::Venus.position(julian_date(1992, 12, 20.0));
+    assert!((venus.illuminated - 0.647).abs() < 0.005);
+    assert!(venus.mag < -3.5 && venus.mag > -4.5);
+
+    // Jupiter and Saturn at opposition in 2023 (Jupiter in Aries,
+    // Saturn in Aquarius)
+    let jupiter = Body::Jupiter.position(julian_date(2023, 11, 3.0));
+    assert!((jupiter.ra.to_degrees() - 38.3).abs() < 1.0);
+    assert!((jupiter.de.to_degrees() - 13.5).abs() < 1.0);
+    assert!(jupiter.phase_angle.to_degrees() < 1.0);
+    assert!((jupiter.mag + 2.9).abs() < 0.2);
+    let saturn = Body::Saturn.position(julian_date(2023, 8, 27.0));
+    assert!((saturn.ra.to_degrees() - 335.0).abs() < 3.0);
+    Ok(())
+}
+
+#[test]
+fn test_phases() -> Result<(), Box<dyn Error>> {
+    // Full moon on 2024-10-17 at 11:26 UT, new moon on 2024-10-02
+    // at 18:49 UT
+    let full = Body::Moon.position(julian_date(2024, 10, 17.48));
+    assert!(full.illuminated > 0.99);
+    assert!(full.mag < -12.5);
+    let new = Body::Moon.position(julian_date(2024, 10, 2.78));
+    assert!(new.illuminated < 0.01);
+    let sun = Body::Sun.position(julian_date(2024, 10, 2.78));
+    // A solar eclipse occurred then, so the Moon is close to the Sun
+    assert!(new.vector.dot(&sun.vector).acos() < 1.0_f64.to_radians());
+    assert!((sun.angular_radius.to_degrees() - 0.267).abs() < 0.005);
+    assert_eq!(sun.mag.round(), -27.);
+    Ok(())
+}
+
+#[test]
+fn test_positions_around() -> Result<(), Box<dyn Error>> {
+    let jd = julian_date(2024, 10, 16.0);
+    let all = solar_system::positions(jd);
+    assert_eq!(all.len(), Body::ALL.len());
+    for (p, b) in all.iter().zip(Body::ALL.iter()) {
+        assert_eq!(p.body, *b);
+        assert_eq!(Body::of_name(&b.name().to_uppercase()), Some(*b));
+        assert!((p.vector.length() - 1.0).abs() < 1E-9);
+    }
+    let jupiter = Body::Jupiter.position(jd);
+    let near = solar_system::positions_around(jd, &jupiter.vector, 5.0_f64.to_radians());
+    assert_eq!(near.len(), 1);
+    assert_eq!(near[0].body, Body::Jupiter);
+
+    let star = jupiter.to_star(1_000_000);
+    assert_eq!(star.id, 1_000_000);
+    assert_eq!(star.mag, jupiter.mag);
+    assert!((star.vector.dot(&jupiter.vector) - 1.0).abs() < 1E-12);
+    Ok(())
+}
+
+#[test]
+fn test_precess_to_j2000() -> Result<(), Box<dyn Error>> {
+    // Meeus example 21.b: theta Persei at 41.547214, +49.348483 for
+    // the equinox of 2028 Nov 13.19 is at 41.054063, +49.227750 for
+    // J2000 (with its proper motion to 2028)
+    let of_date = Star::vec_of_ra_de(41.547214_f64.to_radians(), 49.348483_f64.to_radians());
+    let j2000 = solar_system::precess_to_j2000(&of_date, 2462088.69);
+    let expected = Star::vec_of_ra_de(41.054063_f64.to_radians(), 49.227750_f64.to_radians());
+    let error = j2000.dot(&expected).clamp(-1., 1.).acos().to_degrees() * 3600.0;
+    assert!(error < 0.5, "Precessed position is {error} arcseconds out");
+    Ok(())
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn test_draw_solar_system() -> Result<(), Box<dyn Error>> {
+    use geo_nd::Quaternion;
+    use image::GenericImageView;
+    use star_catalog::{ImageView, Quat, SolarSystemStyle};
+
+    let jd = julian_date(2024, 10, 16.0);
+    let jupiter = Body::Jupiter.position(jd);
+    let count = |style: &SolarSystemStyle| {
+        let mut image_view = ImageView::new(image::DynamicImage::new_rgb8(200, 200));
+        image_view
+            .set_tan_hfov(0.1)
+            .set_star_size(2)
+            .set_orient(Quat::look_at(&jupiter.vector, &[0., 0., 1.].into()));
+        image_view.draw_solar_system(&solar_system::positions(jd), style);
+        let image = image_view.take_image();
+        let mut drawn = 0;
+        let mut labels = 0;
+        for (_, _, p) in image.pixels() {
+            if p[0] == style.label_color[0] && p[1] == style.label_color[1] {
+                labels += 1;
+            } else if p[0] != 0 {
+                drawn += 1;
+            }
+        }
+        (drawn, labels, image.get_pixel(100, 100)[0])
+    };
+    let style = SolarSystemStyle {
+        min_radius: 4.,
+        label_color: [0, 255, 0, 255].into(),
+        ..Default::default()
+    };
+    let (drawn, labels, middle) = count(&style);
+    // A disc of radius 4 pixels
+    assert!((45..=90).contains(&drawn), "{drawn} pixels drawn");
+    assert_eq!(labels, 0);
+    assert!(middle > 0);
+    let (_, labels, _) = count(&SolarSystemStyle {
+        label: true,
+        ..style
+    });
+    assert!(labels > 20, "{labels} pixels drawn for the labels");
+    Ok(())
+}
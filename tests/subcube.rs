@@ -2,9 +2,10 @@ use std::error::Error;
 
 use geo_nd::Vector;
 
-use star_catalog::{Subcube, Vec3};
+use star_catalog::{Subcube, SubcubeIndex, Vec3};
 
 #[test]
+#[allow(deprecated)]
 fn test_on_sphere() -> Result<(), Box<dyn Error>> {
     let s: Subcube = Subcube::of_vector(&[0.01, 0.01, 0.01].into());
     for sn in s.iter_range(1) {
@@ -56,6 +57,7 @@ fn test_on_sphere() -> Result<(), Box<dyn Error>> {
 }
 
 #[test]
+#[allow(deprecated)]
 fn test_all() -> Result<(), Box<dyn Error>> {
     let c = Subcube::iter_all().count();
     assert_eq!(
@@ -64,3 +66,26 @@ fn test_all() -> Result<(), Box<dyn Error>> {
     );
     Ok(())
 }
+
+#[test]
+fn test_ele_per_side() -> Result<(), Box<dyn Error>> {
+    for ele_per_side in [4, 8, 16, 64] {
+        let index = SubcubeIndex::new(ele_per_side);
+        assert_eq!(index.iter_all().count(), index.num_subcubes());
+        for sn in index.iter_all() {
+            assert_eq!(sn.ele_per_side(), ele_per_side);
+            let xyz: (usize, usize, usize) = sn.into();
+            assert_eq!(index.of_xyz(xyz).as_usize(), sn.as_usize());
+            let c = sn.center();
+            if sn.may_be_on_sphere() {
+                let v = c.normalize();
+                let s = index.of_vector(&v);
+                assert!(sn.iter_range(1).any(|n| n.as_usize() == s.as_usize()));
+            }
+        }
+    }
+    assert_eq!(SubcubeIndex::for_num_stars(300).ele_per_side(), 4);
+    assert_eq!(SubcubeIndex::for_num_stars(15_000).ele_per_side(), 16);
+    assert_eq!(SubcubeIndex::for_num_stars(116_812).ele_per_side(), 32);
+    Ok(())
+}
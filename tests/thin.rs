@@ -0,0 +1,124 @@
+use std::collections::HashSet;
+use std::error::Error;
+
+use star_catalog::{Catalog, Star};
+
+#[test]
+fn test_thin_uniform() -> Result<(), Box<dyn Error>> {
+    let s = std::fs::read_to_string("hipparcos.json")?;
+    let mut catalog: Catalog = serde_json::from_str(&s)?;
+    catalog.retain(move |s, _n| s.brighter_than(6.0));
+    catalog.sort();
+    catalog.set_subcube_ele_per_side(Some(16));
+    catalog.derive_data();
+
+    let occupied: HashSet<usize> = catalog.iter_subcubes().map(|s| s.as_usize()).collect();
+    let num_stars = catalog.len();
+
+    let min_separation = 0.2_f64.to_radians();
+    catalog.thin_uniform(2, min_separation);
+    catalog.sort();
+    catalog.derive_data();
+    eprintln!("Thinned from {num_stars} to {} stars", catalog.len());
+    assert!(catalog.len() < num_stars);
+
+    // At most two stars per subcube, with no holes; close doubles can
+    // only be dropped for a nearby brighter star that is kept, which
+    // may be in a neighboring subcube
+    let thinned: HashSet<usize> = catalog.iter_subcubes().map(|s| s.as_usize()).collect();
+    assert!(thinned.is_subset(&occupied));
+    assert!(thinned.len() * 100 >= occupied.len() * 99);
+    for sub in catalog.iter_subcubes() {
+        assert!(catalog[sub].len() <= 2);
+    }
+
+    // No stars closer than the minimum separation
+    let min_cos = min_separation.cos();
+    for (n, s0) in catalog.iter_stars().enumerate() {
+        for s1 in catalog.iter_stars().skip(n + 1) {
+            assert!(s0.cos_angle_between(s1) < min_cos);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_retain_brightest_per_subcube() -> Result<(), Box<dyn Error>> {
+    let s = std::fs::read_to_string("hipparcos.json")?;
+    let mut catalog: Catalog = serde_json::from_str(&s)?;
+    catalog.retain(move |s, _n| s.brighter_than(6.0));
+    catalog.sort();
+    catalog.set_subcube_ele_per_side(Some(16));
+    catalog.derive_data();
+
+    let brightest: Vec<usize> = catalog
+        .iter_subcubes()
+        .map(|sub| catalog[catalog[sub][0]].id)
+        .collect();
+
+    catalog.retain_brightest_per_subcube(1);
+    catalog.sort();
+    catalog.derive_data();
+    assert_eq!(catalog.len(), brightest.len());
+    for id in brightest {
+        assert!(catalog.find_sorted(id).is_some());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_thin_close_pair() -> Result<(), Box<dyn Error>> {
+    // A close pair straddling the boundary between two subcubes (at a
+    // right ascension of 0), and a star well away from them
+    let pair_catalog = || {
+        let mut catalog = Catalog::default();
+        catalog.add_star(Star::new(1, 0.001, 0.3, 10., 2.0, 0.));
+        catalog.add_star(Star::new(
+            2,
+            std::f64::consts::TAU - 0.001,
+            0.3,
+            10.,
+            4.0,
+            0.,
+        ));
+        catalog.add_star(Star::new(3, 1.0, -0.5, 10., 3.0, 0.));
+        catalog.sort();
+        for (id, name) in [(1, "Bright"), (2, "Faint"), (3, "Other")] {
+            let index = catalog.find_sorted(id).unwrap();
+            catalog.add_name(index, name);
+        }
+        catalog.set_subcube_ele_per_side(Some(4));
+        catalog.derive_data();
+        catalog
+    };
+
+    let mut catalog = pair_catalog();
+    let index = catalog.subcube_index();
+    let bright = index.of_vector(&catalog[catalog.find_sorted(1).unwrap()].vector);
+    let faint = index.of_vector(&catalog[catalog.find_sorted(2).unwrap()].vector);
+    assert_ne!(bright.as_usize(), faint.as_usize());
+    assert_eq!(catalog.iter_subcubes().count(), 3);
+
+    // Without a minimum separation every subcube keeps its star
+    catalog.thin_uniform(1, 0.);
+    catalog.sort();
+    catalog.derive_data();
+    assert_eq!(catalog.len(), 3);
+    assert_eq!(catalog.iter_subcubes().count(), 3);
+
+    // The pair cannot be resolved at 0.2 degrees, so the fainter is
+    // dropped even though that leaves its subcube empty
+    let mut catalog = pair_catalog();
+    catalog.thin_uniform(1, 0.2_f64.to_radians());
+    catalog.sort();
+    catalog.derive_data();
+    assert_eq!(catalog.len(), 2);
+    assert_eq!(catalog.iter_subcubes().count(), 2);
+
+    // The names of the kept stars refer to them, and the name of the
+    // dropped star is removed
+    assert_eq!(catalog[catalog.find_name("Bright").unwrap()].id, 1);
+    assert_eq!(catalog[catalog.find_name("Other").unwrap()].id, 3);
+    assert!(catalog.find_name("Faint").is_none());
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use star_catalog::time::{
+    calendar_date, format_date, gmst, julian_date, julian_date_of_unix, parse_date, J2000,
+};
+
+#[test]
+fn test_julian_date() {
+    // Examples from Meeus, 'Astronomical Algorithms', chapter 7
+    assert_eq!(julian_date(2000, 1, 1.5), J2000);
+    assert!((julian_date(1957, 10, 4.81) - 2436116.31).abs() < 1E-6);
+    assert_eq!(julian_date(333, 1, 27.5), 1842713.0);
+    assert_eq!(julian_date(-1000, 7, 12.5), 1356001.0);
+    assert_eq!(
+        julian_date(1582, 10, 15.0) - julian_date(1582, 10, 4.0),
+        1.0
+    );
+    assert_eq!(julian_date_of_unix(0.), julian_date(1970, 1, 1.0));
+
+    let (year, month, day) = calendar_date(2436116.31);
+    assert_eq!((year, month), (1957, 10));
+    assert!((day - 4.81).abs() < 1E-6);
+    assert_eq!(calendar_date(1842713.0), (333, 1, 27.5));
+}
+
+#[test]
+fn test_parse_date() {
+    assert_eq!(parse_date("2000-01-01T12:00"), Some(J2000));
+    assert_eq!(parse_date("2000-01-01 12:00:00Z"), Some(J2000));
+    assert_eq!(parse_date("2451545.0"), Some(J2000));
+    assert_eq!(parse_date("2000-01-02"), Some(J2000 + 0.5));
+    let jd = parse_date("2024-10-16T06:30:36").unwrap();
+    assert!((jd - julian_date(2024, 10, 16.0 + (6.5 + 0.01) / 24.0)).abs() < 1E-9);
+    assert_eq!(parse_date("2024-13-01"), None);
+    assert_eq!(parse_date("2024-10-16T25:00"), None);
+    assert_eq!(parse_date("2024-10"), None);
+    assert_eq!(parse_date("yesterday"), None);
+}
+
+#[test]
+fn test_format_date() {
+    assert_eq!(format_date(J2000), "2000-01-01T12:00:00");
+    let jd = parse_date("2024-10-16T06:30:36").unwrap();
+    assert_eq!(format_date(jd), "2024-10-16T06:30:36");
+    // Times round to the nearest second, into the next day if need be
+    assert_eq!(format_date(jd + 0.4 / 86400.0), "2024-10-16T06:30:36");
+    assert_eq!(
+        format_date(julian_date(2024, 12, 31.0) + 1.0 - 0.2 / 86400.0),
+        "2025-01-01T00:00:00"
+    );
+    assert_eq!(parse_date(&format_date(jd)), Some(jd));
+}
+
+#[test]
+fn test_gmst() {
+    // Meeus example 12.a and 12.b: 13h10m46.3668s and 8h34m57.0896s
+    let expected = (13.0 + 10.0 / 60.0 + 46.3668 / 3600.0) * 15.0;
+    let error = gmst(julian_date(1987, 4, 10.0)).to_degrees() - expected;
+    assert!(error.abs() < 1E-5, "GMST out by {error} degrees");
+    let expected = (8.0 + 34.0 / 60.0 + 57.0896 / 3600.0) * 15.0;
+    let jd = julian_date(1987, 4, 10.0) + (19.0 + 21.0 / 60.0) / 24.0;
+    let error = gmst(jd).to_degrees() - expected;
+    assert!(error.abs() < 1E-5, "GMST out by {error} degrees");
+}
@@ -0,0 +1,60 @@
+use std::error::Error;
+
+use geo_nd::{Vector, Vector3};
+
+use star_catalog::{hipparcos, Catalog, TriangleShapeIndex, Vec3};
+
+/// Project a star vector onto an image plane with a focal length (in
+/// pixels) looking along a direction, with an image rotation
+fn project(v: &Vec3, center: &Vec3, focal_length: f64, rotation: f64) -> [f64; 2] {
+    let up: Vec3 = [0., 0., 1.].into();
+    let x_axis = up.cross_product(center).normalize();
+    let y_axis = center.cross_product(&x_axis);
+    let z = v.dot(center);
+    let (x, y) = (v.dot(&x_axis) / z, v.dot(&y_axis) / z);
+    let (s, c) = rotation.sin_cos();
+    [
+        focal_length * (x * c - y * s) + 1000.,
+        focal_length * (x * s + y * c) + 700.,
+    ]
+}
+
+#[test]
+fn test_shape_index() -> Result<(), Box<dyn Error>> {
+    let s = std::fs::read_to_string("hipparcos.json")?;
+    let mut catalog: Catalog = serde_json::from_str(&s)?;
+    catalog.retain(move |s, _n| s.brighter_than(5.5));
+    catalog.sort();
+    catalog.add_names(hipparcos::HIP_ALIASES, true)?;
+    catalog.derive_data();
+
+    let index = TriangleShapeIndex::new(&catalog, 6.0_f64.to_radians(), 6);
+    assert!(!index.is_empty());
+
+    // Betelgeuse and two of the brightest stars around it
+    let betelgeuse = catalog.find_name("Betelgeuse").unwrap();
+    let center = catalog[betelgeuse].vector;
+    let around = catalog.find_brightest_around(&center, 6.0_f64.to_radians(), 3);
+    let stars: Vec<_> = around.into_iter().filter(|i| *i != betelgeuse).collect();
+    let stars = [stars[1], betelgeuse, stars[0]];
+
+    let focal_length = 3000.0;
+    let points = stars.map(|s| project(&catalog[s].vector, &center, focal_length, 0.7));
+    let matches = index.find_matches(&points, 0.005, 0.005);
+    assert!(!matches.is_empty());
+    let m = matches
+        .iter()
+        .find(|m| m.stars == stars)
+        .expect("Should have found the triangle");
+    assert!((m.plate_scale * focal_length - 1.0).abs() < 0.01);
+
+    // The shape is independent of reflection (and scale)
+    let reflected = points.map(|p| [-p[0] * 2., p[1] * 2.]);
+    let matches = index.find_matches(&reflected, 0.005, 0.005);
+    let m = matches
+        .iter()
+        .find(|m| m.stars == stars)
+        .expect("Should have found the reflected triangle");
+    assert!((m.plate_scale * focal_length * 2. - 1.0).abs() < 0.01);
+    Ok(())
+}